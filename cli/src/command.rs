@@ -0,0 +1,322 @@
+//! Command line parsing
+use std::fs;
+
+use stellar_xdr::curr::{HostFunction, ScVal};
+
+use crate::{
+    error::Error,
+    spec::PoolSpec,
+    tx::{self, TxParams},
+};
+
+pub const USAGE: &str = "\
+usage: comet <command> [args] --source <account> --sequence <n> [options]
+
+commands:
+  install <wasm>                                    install a contract wasm
+  deploy <wasm hash>                                deploy an installed wasm
+  factory-init <factory> <pool wasm hash> <admin>   initialize the factory
+  new-pool <factory> <spec.toml>                    create a pool from a pool spec
+  propose-controller <pool> <manager>               propose a new pool controller
+  accept-controller <pool>                          accept the pool controller role
+  set-freeze-status <pool> <true|false>             freeze or unfreeze a pool
+  quote-swap-in <pool> <token in> <amount in> <token out>
+  quote-swap-out <pool> <token in> <token out> <amount out>
+  quote-join <pool> <pool amount out>
+  quote-exit <pool> <pool amount in>
+  spot-price <pool> <token in> <token out>
+
+options:
+  --source <account>   account submitting the transaction
+  --sequence <n>       sequence number of the transaction
+  --fee <stroops>      inclusion fee, 100 by default
+  --network <name>     futurenet, testnet, mainnet or a passphrase, futurenet by default
+  --salt <hex>         salt for deploy and new-pool, derived from the source and sequence by default";
+
+pub const DEFAULT_FEE: u32 = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Install {
+        wasm: String,
+    },
+    Deploy {
+        wasm_hash: [u8; 32],
+    },
+    FactoryInit {
+        factory: String,
+        pool_wasm_hash: [u8; 32],
+        admin: String,
+    },
+    NewPool {
+        factory: String,
+        spec: String,
+    },
+    ProposeController {
+        pool: String,
+        manager: String,
+    },
+    AcceptController {
+        pool: String,
+    },
+    SetFreezeStatus {
+        pool: String,
+        frozen: bool,
+    },
+    QuoteSwapIn {
+        pool: String,
+        token_in: String,
+        amount_in: i128,
+        token_out: String,
+    },
+    QuoteSwapOut {
+        pool: String,
+        token_in: String,
+        token_out: String,
+        amount_out: i128,
+    },
+    QuoteJoin {
+        pool: String,
+        pool_amount_out: i128,
+    },
+    QuoteExit {
+        pool: String,
+        pool_amount_in: i128,
+    },
+    SpotPrice {
+        pool: String,
+        token_in: String,
+        token_out: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub tx: TxParams,
+    pub network: String,
+    pub salt: Option<[u8; 32]>,
+}
+
+impl Options {
+    // The given salt, or a hash of the source and sequence number so every
+    // transaction from an account deploys to a new address
+    pub fn salt(&self) -> [u8; 32] {
+        self.salt.unwrap_or_else(|| {
+            let mut preimage = self.tx.source.as_bytes().to_vec();
+            preimage.extend_from_slice(&self.tx.sequence.to_be_bytes());
+            tx::sha256(&preimage)
+        })
+    }
+}
+
+pub fn parse(args: &[String]) -> Result<(Command, Options), Error> {
+    let mut positional = Vec::new();
+    let mut source = None;
+    let mut sequence = None;
+    let mut fee = DEFAULT_FEE;
+    let mut network = String::from("futurenet");
+    let mut salt = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            positional.push(arg.as_str());
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| Error::Usage(format!("missing value for {}", arg)))?;
+        match arg.as_str() {
+            "--source" => source = Some(value.clone()),
+            "--sequence" => sequence = Some(parse_value(value)?),
+            "--fee" => fee = parse_value(value)?,
+            "--network" => network = value.clone(),
+            "--salt" => salt = Some(tx::hash(value)?),
+            _ => return Err(Error::Usage(format!("unknown option {}", arg))),
+        }
+    }
+
+    let command = parse_command(&positional)?;
+    let tx = TxParams {
+        source: source.ok_or_else(|| Error::Usage(String::from("missing --source")))?,
+        sequence: sequence.ok_or_else(|| Error::Usage(String::from("missing --sequence")))?,
+        fee,
+    };
+    Ok((command, Options { tx, network, salt }))
+}
+
+fn parse_command(args: &[&str]) -> Result<Command, Error> {
+    let s = |v: &str| String::from(v);
+    let command = match args {
+        ["install", wasm] => Command::Install { wasm: s(wasm) },
+        ["deploy", wasm_hash] => Command::Deploy {
+            wasm_hash: tx::hash(wasm_hash)?,
+        },
+        ["factory-init", factory, pool_wasm_hash, admin] => Command::FactoryInit {
+            factory: s(factory),
+            pool_wasm_hash: tx::hash(pool_wasm_hash)?,
+            admin: s(admin),
+        },
+        ["new-pool", factory, spec] => Command::NewPool {
+            factory: s(factory),
+            spec: s(spec),
+        },
+        ["propose-controller", pool, manager] => Command::ProposeController {
+            pool: s(pool),
+            manager: s(manager),
+        },
+        ["accept-controller", pool] => Command::AcceptController { pool: s(pool) },
+        ["set-freeze-status", pool, frozen] => Command::SetFreezeStatus {
+            pool: s(pool),
+            frozen: parse_value(frozen)?,
+        },
+        ["quote-swap-in", pool, token_in, amount_in, token_out] => Command::QuoteSwapIn {
+            pool: s(pool),
+            token_in: s(token_in),
+            amount_in: parse_value(amount_in)?,
+            token_out: s(token_out),
+        },
+        ["quote-swap-out", pool, token_in, token_out, amount_out] => Command::QuoteSwapOut {
+            pool: s(pool),
+            token_in: s(token_in),
+            token_out: s(token_out),
+            amount_out: parse_value(amount_out)?,
+        },
+        ["quote-join", pool, pool_amount_out] => Command::QuoteJoin {
+            pool: s(pool),
+            pool_amount_out: parse_value(pool_amount_out)?,
+        },
+        ["quote-exit", pool, pool_amount_in] => Command::QuoteExit {
+            pool: s(pool),
+            pool_amount_in: parse_value(pool_amount_in)?,
+        },
+        ["spot-price", pool, token_in, token_out] => Command::SpotPrice {
+            pool: s(pool),
+            token_in: s(token_in),
+            token_out: s(token_out),
+        },
+        [] => return Err(Error::Usage(String::from("missing command"))),
+        [command, ..] => {
+            return Err(Error::Usage(format!(
+                "unknown command or wrong arguments: {}",
+                command
+            )))
+        }
+    };
+    Ok(command)
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidArgument(String::from(value)))
+}
+
+impl Command {
+    // The host function invoked by the command's transaction
+    pub fn host_function(&self, options: &Options) -> Result<HostFunction, Error> {
+        match self {
+            Command::Install { wasm } => tx::upload_wasm(&fs::read(wasm)?),
+            Command::Deploy { wasm_hash } => {
+                tx::create_contract(&options.tx.source, *wasm_hash, options.salt())
+            }
+            Command::FactoryInit {
+                factory,
+                pool_wasm_hash,
+                admin,
+            } => tx::invoke_contract(
+                factory,
+                "init",
+                vec![tx::bytes_val(pool_wasm_hash)?, tx::address_val(admin)?],
+            ),
+            Command::NewPool { factory, spec } => {
+                let spec = PoolSpec::from_toml(&fs::read_to_string(spec)?)?;
+                new_pool(factory, &spec, options)
+            }
+            Command::ProposeController { pool, manager } => {
+                tx::invoke_contract(pool, "propose_controller", vec![tx::address_val(manager)?])
+            }
+            Command::AcceptController { pool } => {
+                tx::invoke_contract(pool, "accept_controller", vec![])
+            }
+            Command::SetFreezeStatus { pool, frozen } => {
+                tx::invoke_contract(pool, "set_freeze_status", vec![ScVal::Bool(*frozen)])
+            }
+            Command::QuoteSwapIn {
+                pool,
+                token_in,
+                amount_in,
+                token_out,
+            } => tx::invoke_contract(
+                pool,
+                "quote_swap_exact_amount_in",
+                vec![
+                    tx::address_val(token_in)?,
+                    tx::i128_val(*amount_in),
+                    tx::address_val(token_out)?,
+                ],
+            ),
+            Command::QuoteSwapOut {
+                pool,
+                token_in,
+                token_out,
+                amount_out,
+            } => tx::invoke_contract(
+                pool,
+                "quote_swap_exact_amount_out",
+                vec![
+                    tx::address_val(token_in)?,
+                    tx::address_val(token_out)?,
+                    tx::i128_val(*amount_out),
+                ],
+            ),
+            Command::QuoteJoin {
+                pool,
+                pool_amount_out,
+            } => tx::invoke_contract(
+                pool,
+                "quote_join_pool",
+                vec![tx::i128_val(*pool_amount_out)],
+            ),
+            Command::QuoteExit {
+                pool,
+                pool_amount_in,
+            } => tx::invoke_contract(pool, "quote_exit_pool", vec![tx::i128_val(*pool_amount_in)]),
+            Command::SpotPrice {
+                pool,
+                token_in,
+                token_out,
+            } => tx::invoke_contract(
+                pool,
+                "get_spot_price",
+                vec![tx::address_val(token_in)?, tx::address_val(token_out)?],
+            ),
+        }
+    }
+}
+
+// `new_c_pool` on the factory, with the pool controlled by the source account
+// unless the spec names a controller
+pub fn new_pool(factory: &str, spec: &PoolSpec, options: &Options) -> Result<HostFunction, Error> {
+    let controller = spec.controller.as_ref().unwrap_or(&options.tx.source);
+    let mut tokens = Vec::new();
+    let mut weights = Vec::new();
+    let mut balances = Vec::new();
+    for token in &spec.tokens {
+        tokens.push(tx::address_val(&token.address)?);
+        weights.push(tx::i128_val(token.weight));
+        balances.push(tx::i128_val(token.balance));
+    }
+    tx::invoke_contract(
+        factory,
+        "new_c_pool",
+        vec![
+            tx::bytes_val(&options.salt())?,
+            tx::address_val(controller)?,
+            tx::vec_val(tokens)?,
+            tx::vec_val(weights)?,
+            tx::vec_val(balances)?,
+            tx::i128_val(spec.swap_fee),
+        ],
+    )
+}
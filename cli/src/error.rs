@@ -0,0 +1,54 @@
+use std::fmt;
+
+// Errors returned while parsing arguments and building transactions
+#[derive(Debug)]
+pub enum Error {
+    // The command line is not a valid command
+    Usage(String),
+    // A file given on the command line could not be read
+    Io(std::io::Error),
+    // The pool spec is not valid TOML, or is missing a field
+    Toml(toml::de::Error),
+    // The pool spec parses but does not describe a valid pool
+    InvalidSpec(String),
+    // An account or contract address is not a valid strkey
+    InvalidAddress(String),
+    // A hash, salt, amount or flag could not be parsed
+    InvalidArgument(String),
+    // A value does not fit in its XDR type
+    Xdr(stellar_xdr::curr::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(msg) => write!(f, "{}", msg),
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Toml(err) => write!(f, "invalid pool spec: {}", err),
+            Error::InvalidSpec(msg) => write!(f, "invalid pool spec: {}", msg),
+            Error::InvalidAddress(addr) => write!(f, "invalid address: {}", addr),
+            Error::InvalidArgument(arg) => write!(f, "invalid argument: {}", arg),
+            Error::Xdr(err) => write!(f, "invalid xdr: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Toml(err)
+    }
+}
+
+impl From<stellar_xdr::curr::Error> for Error {
+    fn from(err: stellar_xdr::curr::Error) -> Self {
+        Error::Xdr(err)
+    }
+}
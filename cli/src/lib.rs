@@ -0,0 +1,39 @@
+//! Builds unsigned transactions to deploy and administer Comet pools
+use stellar_xdr::curr::{Hash, HostFunction, Transaction};
+
+// Command Line Parsing
+pub mod command;
+// Errors Listed
+pub mod error;
+// Pool Spec
+pub mod spec;
+// Transaction Building
+pub mod tx;
+
+mod test;
+
+use crate::{
+    command::{Command, Options},
+    error::Error,
+};
+
+// The transaction for `command`, with notes on what it creates
+pub fn build(command: &Command, options: &Options) -> Result<(Transaction, Vec<String>), Error> {
+    let passphrase = tx::network_passphrase(&options.network);
+    let host_function = command.host_function(options)?;
+    let mut notes = Vec::new();
+    match &host_function {
+        HostFunction::UploadContractWasm(wasm) => {
+            notes.push(format!("wasm hash {}", Hash(tx::sha256(wasm))));
+        }
+        HostFunction::CreateContract(args) => {
+            let contract = tx::contract_id(passphrase, &args.contract_id_preimage)?;
+            notes.push(format!("contract  {}", contract));
+        }
+        HostFunction::InvokeContract(_) => {}
+    }
+    let tx = tx::transaction(&options.tx, host_function)?;
+    let hash = Hash(tx::transaction_hash(passphrase, &tx)?);
+    notes.push(format!("tx hash   {}", hash));
+    Ok((tx, notes))
+}
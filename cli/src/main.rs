@@ -0,0 +1,38 @@
+//! Print the unsigned transaction for a deployment or administration call
+//!
+//! The base64 XDR envelope is printed to stdout, and the wasm hash, deployed
+//! contract address and transaction hash to stderr. Simulate the envelope to
+//! add its resources and authorization, then sign it offline.
+use std::{env, process};
+
+use cli::{
+    build,
+    command::{parse, USAGE},
+    error::Error,
+    tx::envelope_xdr,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => {}
+        Err(Error::Usage(msg)) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let (command, options) = parse(args)?;
+    let (tx, notes) = build(&command, &options)?;
+    for note in notes {
+        eprintln!("{}", note);
+    }
+    println!("{}", envelope_xdr(tx)?);
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Pool spec, the TOML description of a pool created through the factory
+//!
+//! ```toml
+//! controller = "G..."   # optional, defaults to the source account
+//! swap_fee = 30000      # 0.3%, with 7 decimals
+//!
+//! [[tokens]]
+//! address = "C..."
+//! weight = 5000000      # 50%, with 7 decimals
+//! balance = 1000000000  # in the token's own decimals
+//! ```
+//!
+//! Amounts are TOML integers, or strings for amounts beyond 64 bits.
+//! The pool contract checks the weight, balance and fee bounds.
+use std::collections::BTreeSet;
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{error::Error, tx};
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PoolSpec {
+    pub controller: Option<String>,
+    #[serde(deserialize_with = "amount")]
+    pub swap_fee: i128,
+    pub tokens: Vec<TokenSpec>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TokenSpec {
+    pub address: String,
+    #[serde(deserialize_with = "amount")]
+    pub weight: i128,
+    #[serde(deserialize_with = "amount")]
+    pub balance: i128,
+}
+
+impl PoolSpec {
+    pub fn from_toml(input: &str) -> Result<Self, Error> {
+        let spec: PoolSpec = toml::from_str(input)?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if let Some(controller) = &self.controller {
+            tx::address(controller)?;
+        }
+        if self.tokens.len() < 2 {
+            return Err(Error::InvalidSpec(String::from(
+                "a pool needs at least two tokens",
+            )));
+        }
+        let mut seen = BTreeSet::new();
+        for token in &self.tokens {
+            tx::contract_address(&token.address)?;
+            if !seen.insert(&token.address) {
+                return Err(Error::InvalidSpec(format!(
+                    "duplicate token {}",
+                    token.address
+                )));
+            }
+            if token.weight <= 0 || token.balance <= 0 {
+                return Err(Error::InvalidSpec(format!(
+                    "token {} needs a positive weight and balance",
+                    token.address
+                )));
+            }
+        }
+        if self.swap_fee <= 0 {
+            return Err(Error::InvalidSpec(String::from(
+                "the swap fee must be positive",
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i128, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Amount {
+        Int(i64),
+        Str(String),
+    }
+    match Amount::deserialize(deserializer)? {
+        Amount::Int(v) => Ok(v as i128),
+        Amount::Str(s) => s
+            .parse()
+            .map_err(|_| de::Error::custom(format!("invalid amount {:?}", s))),
+    }
+}
@@ -0,0 +1,227 @@
+#![cfg(test)]
+
+use std::{env, fs};
+
+use stellar_xdr::curr::{
+    Hash, HostFunction, Limits, OperationBody, ReadXdr, ScVal, TransactionEnvelope,
+};
+
+use crate::{
+    build,
+    command::{parse, Command, Options, DEFAULT_FEE},
+    error::Error,
+    spec::PoolSpec,
+    tx::{self, TxParams},
+};
+
+const FIXTURE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/pool_spec.toml");
+const FIXTURE: &str = include_str!("../fixtures/pool_spec.toml");
+
+const SOURCE: &str = "GAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCF6M";
+const CONTROLLER: &str = "GAJBEEQSCIJBEEQSCIJBEEQSCIJBEEQSCIJBEEQSCIJBEEQSCIJBFVWL";
+const FACTORY: &str = "CAAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQC526";
+const TOKEN_A: &str = "CABAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAEAQCAIBAFNSZ";
+const TOKEN_B: &str = "CABQGAYDAMBQGAYDAMBQGAYDAMBQGAYDAMBQGAYDAMBQGAYDAMBQGCK3";
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+fn options() -> Options {
+    Options {
+        tx: TxParams {
+            source: String::from(SOURCE),
+            sequence: 7,
+            fee: DEFAULT_FEE,
+        },
+        network: String::from("futurenet"),
+        salt: None,
+    }
+}
+
+// Build `command` and decode its envelope back from base64 XDR
+fn invocation(command: &Command, options: &Options) -> HostFunction {
+    let (tx, _) = build(command, options).unwrap();
+    let xdr = tx::envelope_xdr(tx.clone()).unwrap();
+    let envelope = TransactionEnvelope::from_xdr_base64(xdr, Limits::none()).unwrap();
+    let TransactionEnvelope::Tx(envelope) = envelope else {
+        panic!("not a v1 envelope");
+    };
+    assert_eq!(envelope.tx, tx);
+    assert!(envelope.signatures.is_empty());
+    let OperationBody::InvokeHostFunction(op) = &envelope.tx.operations[0].body else {
+        panic!("not a host function invocation");
+    };
+    assert!(op.auth.is_empty());
+    op.host_function.clone()
+}
+
+#[test]
+fn test_pool_spec() {
+    let spec = PoolSpec::from_toml(FIXTURE).unwrap();
+    assert_eq!(spec.controller.as_deref(), Some(CONTROLLER));
+    assert_eq!(spec.swap_fee, 30000);
+    assert_eq!(spec.tokens.len(), 2);
+    assert_eq!(spec.tokens[0].address, TOKEN_A);
+    assert_eq!(spec.tokens[0].weight, 8000000);
+    assert_eq!(spec.tokens[1].balance, 20_000_000_000_000_000_000);
+
+    let one_token = format!(
+        "swap_fee = 30000\n[[tokens]]\naddress = \"{}\"\nweight = 1\nbalance = 1\n",
+        TOKEN_A
+    );
+    assert!(matches!(
+        PoolSpec::from_toml(&one_token),
+        Err(Error::InvalidSpec(_))
+    ));
+    let duplicate = format!("{}{}", one_token, &one_token[17..]);
+    assert!(matches!(
+        PoolSpec::from_toml(&duplicate),
+        Err(Error::InvalidSpec(_))
+    ));
+    // tokens are contracts
+    let account_token = FIXTURE.replace(TOKEN_B, SOURCE);
+    assert!(matches!(
+        PoolSpec::from_toml(&account_token),
+        Err(Error::InvalidAddress(_))
+    ));
+    let typo = FIXTURE.replace("swap_fee", "swapfee");
+    assert!(matches!(PoolSpec::from_toml(&typo), Err(Error::Toml(_))));
+}
+
+#[test]
+fn test_parse_args() {
+    let line = format!(
+        "new-pool {} {} --source {} --sequence 7 --network testnet",
+        FACTORY, FIXTURE_PATH, SOURCE
+    );
+    let (command, options) = parse(&args(&line)).unwrap();
+    assert_eq!(
+        command,
+        Command::NewPool {
+            factory: String::from(FACTORY),
+            spec: String::from(FIXTURE_PATH),
+        }
+    );
+    assert_eq!(options.tx.sequence, 7);
+    assert_eq!(options.tx.fee, DEFAULT_FEE);
+    assert_eq!(tx::network_passphrase(&options.network), tx::TESTNET);
+
+    let (command, _) = parse(&args(&format!(
+        "set-freeze-status {} true --source {} --sequence 1",
+        TOKEN_A, SOURCE
+    )))
+    .unwrap();
+    assert_eq!(
+        command,
+        Command::SetFreezeStatus {
+            pool: String::from(TOKEN_A),
+            frozen: true,
+        }
+    );
+
+    let missing_source = parse(&args("accept-controller CA --sequence 1"));
+    assert!(matches!(missing_source, Err(Error::Usage(_))));
+    let wrong_arity = parse(&args("accept-controller --source G --sequence 1"));
+    assert!(matches!(wrong_arity, Err(Error::Usage(_))));
+    let bad_amount = parse(&args("quote-join CA 1.5 --source G --sequence 1"));
+    assert!(matches!(bad_amount, Err(Error::InvalidArgument(_))));
+}
+
+#[test]
+fn test_new_pool_transaction() {
+    let command = Command::NewPool {
+        factory: String::from(FACTORY),
+        spec: String::from(FIXTURE_PATH),
+    };
+    let options = options();
+    let HostFunction::InvokeContract(call) = invocation(&command, &options) else {
+        panic!("not a contract call");
+    };
+    assert_eq!(call.contract_address, tx::address(FACTORY).unwrap());
+    assert_eq!(call.function_name.0.to_string(), "new_c_pool");
+    assert_eq!(
+        call.args.to_vec(),
+        vec![
+            tx::bytes_val(&options.salt()).unwrap(),
+            tx::address_val(CONTROLLER).unwrap(),
+            tx::vec_val(vec![
+                tx::address_val(TOKEN_A).unwrap(),
+                tx::address_val(TOKEN_B).unwrap(),
+            ])
+            .unwrap(),
+            tx::vec_val(vec![tx::i128_val(8000000), tx::i128_val(2000000)]).unwrap(),
+            tx::vec_val(vec![
+                tx::i128_val(800000000),
+                tx::i128_val(20_000_000_000_000_000_000),
+            ])
+            .unwrap(),
+            tx::i128_val(30000),
+        ]
+    );
+
+    // the default salt is unique per transaction, and can be pinned
+    let mut next = options.clone();
+    next.tx.sequence += 1;
+    assert_ne!(next.salt(), options.salt());
+    next.salt = Some([9; 32]);
+    assert_eq!(next.salt(), [9; 32]);
+
+    let quote = Command::QuoteSwapIn {
+        pool: String::from(FACTORY),
+        token_in: String::from(TOKEN_A),
+        amount_in: -1,
+        token_out: String::from(TOKEN_B),
+    };
+    let HostFunction::InvokeContract(call) = invocation(&quote, &options) else {
+        panic!("not a contract call");
+    };
+    assert_eq!(
+        call.function_name.0.to_string(),
+        "quote_swap_exact_amount_in"
+    );
+    let ScVal::I128(amount) = &call.args[1] else {
+        panic!("not an i128");
+    };
+    assert_eq!((amount.hi, amount.lo), (-1, u64::MAX));
+
+    // pools and factories are contracts
+    let account_pool = Command::AcceptController {
+        pool: String::from(SOURCE),
+    };
+    assert!(matches!(
+        build(&account_pool, &options),
+        Err(Error::InvalidAddress(_))
+    ));
+}
+
+#[test]
+fn test_install_and_deploy() {
+    let wasm = b"\0asm\x01\0\0\0";
+    let path = env::temp_dir().join("comet_cli_test.wasm");
+    fs::write(&path, wasm).unwrap();
+    let install = Command::Install {
+        wasm: path.to_string_lossy().into_owned(),
+    };
+    let options = options();
+    let (_, notes) = build(&install, &options).unwrap();
+    let wasm_hash = tx::sha256(wasm);
+    assert_eq!(notes[0], format!("wasm hash {}", Hash(wasm_hash)));
+    assert_eq!(
+        invocation(&install, &options),
+        HostFunction::UploadContractWasm(wasm.try_into().unwrap())
+    );
+    fs::remove_file(path).unwrap();
+
+    let deploy = Command::Deploy { wasm_hash };
+    let HostFunction::CreateContract(args) = invocation(&deploy, &options) else {
+        panic!("not a contract deployment");
+    };
+    // the address depends on the deployer, salt and network
+    let (_, notes) = build(&deploy, &options).unwrap();
+    let contract = tx::contract_id(tx::FUTURENET, &args.contract_id_preimage).unwrap();
+    assert_eq!(notes[0], format!("contract  {}", contract));
+    assert!(notes[0].contains(" C"));
+    let testnet = tx::contract_id(tx::TESTNET, &args.contract_id_preimage).unwrap();
+    assert_ne!(testnet, contract);
+}
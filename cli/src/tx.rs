@@ -0,0 +1,178 @@
+//! Unsigned Soroban transactions
+//!
+//! Transactions carry a single `InvokeHostFunction` operation, without
+//! authorization entries or resources. Those are filled in by simulating
+//! the transaction before it is signed.
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    ContractExecutable, ContractIdPreimage, ContractIdPreimageFromAddress, CreateContractArgs,
+    Hash, HashIdPreimage, HashIdPreimageContractId, HostFunction, Int128Parts, InvokeContractArgs,
+    InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
+    ScAddress, ScBytes, ScSymbol, ScVal, ScVec, SequenceNumber, Transaction, TransactionEnvelope,
+    TransactionExt, TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV1Envelope, Uint256, VecM, WriteXdr,
+};
+
+use crate::error::Error;
+
+pub const FUTURENET: &str = "Test SDF Future Network ; October 2022";
+pub const TESTNET: &str = "Test SDF Network ; September 2015";
+pub const MAINNET: &str = "Public Global Stellar Network ; September 2015";
+
+// The passphrase of a well known network, any other name is taken as a passphrase
+pub fn network_passphrase(network: &str) -> &str {
+    match network {
+        "futurenet" => FUTURENET,
+        "testnet" => TESTNET,
+        "mainnet" | "pubnet" => MAINNET,
+        passphrase => passphrase,
+    }
+}
+
+pub fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+// An account (G...) or contract (C...) address
+pub fn address(s: &str) -> Result<ScAddress, Error> {
+    s.parse()
+        .map_err(|_| Error::InvalidAddress(String::from(s)))
+}
+
+// A contract (C...) address
+pub fn contract_address(s: &str) -> Result<ScAddress, Error> {
+    match address(s)? {
+        ScAddress::Contract(hash) => Ok(ScAddress::Contract(hash)),
+        ScAddress::Account(_) => Err(Error::InvalidAddress(String::from(s))),
+    }
+}
+
+// A 32 byte hash or salt as hex
+pub fn hash(s: &str) -> Result<[u8; 32], Error> {
+    s.parse::<Hash>()
+        .map(|hash| hash.0)
+        .map_err(|_| Error::InvalidArgument(String::from(s)))
+}
+
+/********** Contract Arguments **********/
+
+pub fn address_val(s: &str) -> Result<ScVal, Error> {
+    Ok(ScVal::Address(address(s)?))
+}
+
+pub fn i128_val(v: i128) -> ScVal {
+    ScVal::I128(Int128Parts {
+        hi: (v >> 64) as i64,
+        lo: v as u64,
+    })
+}
+
+pub fn bytes_val(bytes: &[u8]) -> Result<ScVal, Error> {
+    Ok(ScVal::Bytes(ScBytes(bytes.try_into()?)))
+}
+
+pub fn vec_val(vals: Vec<ScVal>) -> Result<ScVal, Error> {
+    Ok(ScVal::Vec(Some(ScVec(vals.try_into()?))))
+}
+
+/********** Host Functions **********/
+
+// Call `function` on `contract`
+pub fn invoke_contract(
+    contract: &str,
+    function: &str,
+    args: Vec<ScVal>,
+) -> Result<HostFunction, Error> {
+    Ok(HostFunction::InvokeContract(InvokeContractArgs {
+        contract_address: contract_address(contract)?,
+        function_name: ScSymbol(function.try_into()?),
+        args: args.try_into()?,
+    }))
+}
+
+// Install `wasm`, it can then be deployed by its hash
+pub fn upload_wasm(wasm: &[u8]) -> Result<HostFunction, Error> {
+    Ok(HostFunction::UploadContractWasm(wasm.try_into()?))
+}
+
+// Deploy an installed wasm from the `deployer` account
+pub fn create_contract(
+    deployer: &str,
+    wasm_hash: [u8; 32],
+    salt: [u8; 32],
+) -> Result<HostFunction, Error> {
+    Ok(HostFunction::CreateContract(CreateContractArgs {
+        contract_id_preimage: ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+            address: address(deployer)?,
+            salt: Uint256(salt),
+        }),
+        executable: ContractExecutable::Wasm(Hash(wasm_hash)),
+    }))
+}
+
+// The address a contract is deployed at on the network
+pub fn contract_id(
+    network_passphrase: &str,
+    preimage: &ContractIdPreimage,
+) -> Result<ScAddress, Error> {
+    let preimage = HashIdPreimage::ContractId(HashIdPreimageContractId {
+        network_id: Hash(sha256(network_passphrase.as_bytes())),
+        contract_id_preimage: preimage.clone(),
+    });
+    Ok(ScAddress::Contract(Hash(sha256(
+        &preimage.to_xdr(Limits::none())?,
+    ))))
+}
+
+/********** Transactions **********/
+
+// The account submitting the transaction
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxParams {
+    pub source: String,
+    // the sequence number of the transaction, one more than the account's
+    pub sequence: i64,
+    // the inclusion fee in stroops, simulation adds the resource fee
+    pub fee: u32,
+}
+
+pub fn transaction(params: &TxParams, host_function: HostFunction) -> Result<Transaction, Error> {
+    let source_account: MuxedAccount = params
+        .source
+        .parse()
+        .map_err(|_| Error::InvalidAddress(params.source.clone()))?;
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function,
+            auth: VecM::default(),
+        }),
+    };
+    Ok(Transaction {
+        source_account,
+        fee: params.fee,
+        seq_num: SequenceNumber(params.sequence),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![operation].try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+// The hash signed for `tx` on the network
+pub fn transaction_hash(network_passphrase: &str, tx: &Transaction) -> Result<[u8; 32], Error> {
+    let payload = TransactionSignaturePayload {
+        network_id: Hash(sha256(network_passphrase.as_bytes())),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    Ok(sha256(&payload.to_xdr(Limits::none())?))
+}
+
+// The transaction envelope without signatures, as base64 XDR
+pub fn envelope_xdr(tx: Transaction) -> Result<String, Error> {
+    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: VecM::default(),
+    });
+    Ok(envelope.to_xdr_base64(Limits::none())?)
+}
@@ -1,20 +1,34 @@
 //! Comet Pool Constants
 
-/// c_math 256 bit constants
-/// kept as i128 to avoid requiring `env` to define constants
-pub const BONE: i128 = 10i128.pow(18);
-pub const MIN_CPOW_BASE: i128 = 1;
-pub const MAX_CPOW_BASE: i128 = (2 * BONE) - 1;
-pub const CPOW_PRECISION: i128 = 10i128.pow(8);
+/// c_math constants, defined with the pool math
+pub use math::c_consts::{
+    BONE, CPOW_PRECISION, MAX_CPOW_BASE, MAX_PROTOCOL_FEE, MIN_CPOW_BASE, STROOP, STROOP_SCALAR,
+};
 
 /// constants
-pub const STROOP: i128 = 10i128.pow(7);
-pub const STROOP_SCALAR: i128 = 10i128.pow(11);
 pub const MAX_IN_RATIO: i128 = (STROOP / 3) + 1;
 pub const MAX_OUT_RATIO: i128 = (STROOP / 3) + 1;
 pub const INIT_POOL_SUPPLY: i128 = STROOP * 100;
 pub const MIN_FEE: i128 = 10; // 0.0001%
 pub const MAX_FEE: i128 = STROOP / 10; // 10%
+pub const MAX_FEE_DELTA: i128 = STROOP / 100; // 1%
+pub const MAX_VOLATILITY: i128 = STROOP / 10; // 10% price move charges the dynamic fee ceiling
+pub const VOLATILITY_DECAY_LEDGERS: u32 = 720; // ~1 hour of ledgers for volatility to decay to zero
+pub const MIN_FEE_UPDATE_DELAY: u32 = 720; // ~1 hour of ledgers
+pub const MAX_EXIT_FEE: i128 = STROOP / 100; // 1%
+pub const OBSERVATION_INTERVAL: u64 = 60; // seconds between stored observations
+pub const OBSERVATION_CARDINALITY: u32 = 1440; // 24 hours of observations
+pub const STORAGE_VERSION: u32 = 1; // storage layout version, see `migrate`
+pub const EVENT_VERSION: u32 = 2; // version of the trade and sync event payloads
+pub const MIN_UPGRADE_DELAY: u64 = 86400; // 1 day
+pub const MAX_UPGRADE_DELAY: u64 = 30 * 86400; // 30 days
+pub const PAUSE_SWAP: u32 = 1 << 0; // swaps and flash loans
+pub const PAUSE_JOIN: u32 = 1 << 1; // proportional joins
+pub const PAUSE_DEPOSIT: u32 = 1 << 2; // single-sided deposits
+pub const PAUSE_WITHDRAW: u32 = 1 << 3; // exits and single-sided withdrawals
+pub const PAUSE_ALL: u32 = PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT | PAUSE_WITHDRAW;
+pub const WITHDRAW_PAUSE_DELAY: u64 = 86400; // 1 day for users to exit before withdrawals pause
+pub const WITHDRAW_PAUSE_GRACE: u64 = 86400; // 1 day to use a matured withdrawal pause proposal
 pub const MIN_BOUND_TOKENS: u32 = 2;
 pub const MAX_BOUND_TOKENS: u32 = 8;
 pub const MAX_TOTAL_WEIGHT: i128 = STROOP * 50;
@@ -1,32 +1,43 @@
 //! Comet Pool Math Utilities
-use soroban_fixed_point_math::{FixedPoint, SorobanFixedPoint};
-use soroban_sdk::{assert_with_error, unwrap::UnwrapOptimized, Env, I256};
+//!
+//! The pool math lives in the `math` crate, shared with off-chain code,
+//! these wrappers raise its errors as contract errors
+use math::{c_math, I256};
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Env, Vec};
 
 use crate::{
-    c_consts::{BONE, STROOP, STROOP_SCALAR},
-    c_num::{c_pow, sub_no_negative},
-    c_pool::{error::Error, storage_types::Record},
+    c_consts::MAX_BOUND_TOKENS,
+    c_pool::{
+        error::Error,
+        storage_types::{Record, WeightSchedule},
+    },
 };
 
+fn math_record(record: &Record) -> c_math::Record {
+    c_math::Record {
+        balance: record.balance,
+        weight: record.weight,
+        scalar: record.scalar,
+    }
+}
+
+pub(crate) fn unwrap_math<T>(e: &Env, result: Result<T, math::error::Error>) -> T {
+    result.unwrap_or_else(|err| panic_with_error!(e, Error::from(err)))
+}
+
 // Calculates the spot price for a token pair
 // based on weights and balances for that pair of tokens,
 // accounting for fees
 pub fn calc_spot_price(in_record: &Record, out_record: &Record, swap_fee: i128) -> i128 {
-    // don't upscale to preserve "token in" / "token out" precision
-    let numer = in_record
-        .balance
-        .fixed_div_floor(in_record.weight, STROOP)
-        .unwrap_optimized();
-    let denom = out_record
-        .balance
-        .fixed_div_floor(out_record.weight, STROOP)
-        .unwrap_optimized();
-    let ratio = numer.fixed_div_floor(denom, STROOP).unwrap_optimized();
-    ratio
-        .fixed_div_floor(STROOP - swap_fee, STROOP)
+    c_math::calc_spot_price(&math_record(in_record), &math_record(out_record), swap_fee)
         .unwrap_optimized()
 }
 
+// Calculates the balance of a token per unit of its weight
+pub fn calc_weighted_balance(record: &Record) -> i128 {
+    c_math::calc_weighted_balance(&math_record(record)).unwrap_optimized()
+}
+
 /// Calculates the amount of token out sent to user,
 /// for a given amount of token in
 ///
@@ -38,29 +49,13 @@ pub fn calc_token_out_given_token_in(
     amount_in: i128,
     swap_fee: i128,
 ) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
-    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
-    let token_amount_in = upscale(e, amount_in, in_record.scalar);
-
-    let fee_adjust_ratio = upscale(e, STROOP - swap_fee, STROOP_SCALAR);
-    let weight_ratio = upscale(
-        e,
-        in_record
-            .weight
-            .fixed_div_floor(out_record.weight, STROOP)
-            .unwrap_optimized(),
-        STROOP_SCALAR,
+    let result = c_math::calc_token_out_given_token_in(
+        &math_record(in_record),
+        &math_record(out_record),
+        amount_in,
+        swap_fee,
     );
-
-    let adjusted_in = token_amount_in.fixed_mul_floor(&e, &fee_adjust_ratio, &bone);
-
-    let base = token_balance_in.fixed_div_floor(&e, &token_balance_in.add(&adjusted_in), &bone);
-    let power = c_pow(e, &base, &weight_ratio, true);
-    let balance_ratio = sub_no_negative(e, &bone, &power);
-    let result = token_balance_out.fixed_mul_floor(&e, &balance_ratio, &bone);
-
-    downscale_floor(e, &result, out_record.scalar)
+    unwrap_math(e, result)
 }
 
 /// Calculates the amount of token in required by pool,
@@ -74,29 +69,13 @@ pub fn calc_token_in_given_token_out(
     amount_out: i128,
     swap_fee: i128,
 ) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
-    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
-    let token_amount_out = upscale(e, amount_out, out_record.scalar);
-
-    let fee_adjust_ratio = upscale(e, STROOP - swap_fee, STROOP_SCALAR);
-    let weight_ratio = upscale(
-        e,
-        out_record
-            .weight
-            .fixed_div_ceil(in_record.weight, STROOP)
-            .unwrap_optimized(),
-        STROOP_SCALAR,
+    let result = c_math::calc_token_in_given_token_out(
+        &math_record(in_record),
+        &math_record(out_record),
+        amount_out,
+        swap_fee,
     );
-
-    let base =
-        token_balance_out.fixed_div_ceil(&e, &token_balance_out.sub(&token_amount_out), &bone);
-    let power = c_pow(e, &base, &weight_ratio, true);
-    let balance_ratio = sub_no_negative(e, &power, &bone);
-
-    let token_amount_in = token_balance_in.fixed_mul_ceil(&e, &balance_ratio, &bone);
-    let adjusted_in = token_amount_in.fixed_div_ceil(&e, &fee_adjust_ratio, &bone);
-    downscale_ceil(e, &adjusted_in, in_record.scalar)
+    unwrap_math(e, result)
 }
 
 /// Calculates the amount of LP tokens being minted to user,
@@ -110,27 +89,13 @@ pub fn calc_lp_token_amount_given_token_deposits_in(
     token_amount_in: i128,
     swap_fee: i128,
 ) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
-    let token_amount_in = upscale(e, token_amount_in, in_record.scalar);
-    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
-    let fee = upscale(e, swap_fee, STROOP_SCALAR);
-
-    let normalized_weight = upscale(e, in_record.weight, STROOP_SCALAR);
-    let zaz = bone.sub(&normalized_weight).fixed_mul_floor(e, &fee, &bone);
-    let token_amount_in_after_fee = token_amount_in.fixed_mul_floor(&e, &bone.sub(&zaz), &bone);
-
-    let new_token_balance_in = token_balance_in.add(&token_amount_in_after_fee);
-    let balance_ratio = new_token_balance_in.fixed_div_floor(&e, &token_balance_in, &bone);
-
-    let pool_ratio = c_pow(e, &balance_ratio, &normalized_weight, false);
-    let new_pool_supply = pool_ratio.fixed_mul_floor(&e, &pool_supply, &bone);
-
-    downscale_floor(
-        e,
-        &sub_no_negative(e, &new_pool_supply, &pool_supply),
-        STROOP_SCALAR,
-    )
+    let result = c_math::calc_lp_token_amount_given_token_deposits_in(
+        &math_record(in_record),
+        pool_supply,
+        token_amount_in,
+        swap_fee,
+    );
+    unwrap_math(e, result)
 }
 
 /// Calculates the amount of deposited tokens required by pool,
@@ -144,30 +109,18 @@ pub fn calc_token_deposits_in_given_lp_token_amount(
     pool_amount_out: i128,
     swap_fee: i128,
 ) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
-    let pool_amount_out = upscale(e, pool_amount_out, STROOP_SCALAR);
-    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
-    let fee = upscale(e, swap_fee, STROOP_SCALAR);
-
-    let normalized_weight = upscale(e, in_record.weight, STROOP_SCALAR);
-
-    let new_pool_supply = pool_supply.add(&pool_amount_out);
-    let pool_ratio = new_pool_supply.fixed_div_ceil(&e, &pool_supply, &bone);
-
-    let boo = bone.fixed_div_ceil(e, &normalized_weight, &bone);
-    let token_in_ratio = c_pow(e, &pool_ratio, &boo, false);
-    let new_token_balance_in = token_balance_in.fixed_mul_ceil(&e, &token_in_ratio, &bone);
-
-    let token_amount_in_after_fee = sub_no_negative(e, &new_token_balance_in, &token_balance_in);
-    let zar = bone.sub(&normalized_weight).fixed_mul_floor(e, &fee, &bone);
-    let result = token_amount_in_after_fee.fixed_div_ceil(&e, &bone.sub(&zar), &bone);
-
-    downscale_ceil(e, &result, in_record.scalar)
+    let result = c_math::calc_token_deposits_in_given_lp_token_amount(
+        &math_record(in_record),
+        pool_supply,
+        pool_amount_out,
+        swap_fee,
+    );
+    unwrap_math(e, result)
 }
 
 /// Calculating the amount of LP tokens a user needs to burn,
 /// for a given amount of tokens being withdrawn.
+/// The result includes the exit fee kept from the LP tokens.
 ///
 /// Rounds up to benefit the pool
 pub fn calc_lp_token_amount_given_token_withdrawal_amount(
@@ -176,31 +129,21 @@ pub fn calc_lp_token_amount_given_token_withdrawal_amount(
     pool_supply: i128,
     token_amount_out: i128,
     swap_fee: i128,
+    exit_fee: i128,
 ) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
-    let token_amount_out = upscale(e, token_amount_out, out_record.scalar);
-    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
-    let fee = upscale(e, swap_fee, STROOP_SCALAR);
-
-    let normalized_weight = upscale(e, out_record.weight, STROOP_SCALAR);
-
-    let zoo = bone.sub(&normalized_weight);
-    let zar = zoo.fixed_mul_floor(e, &fee, &bone);
-
-    let token_amount_out_before_fee = token_amount_out.fixed_div_ceil(&e, &bone.sub(&zar), &bone);
-    let new_token_balance_out = token_balance_out.sub(&token_amount_out_before_fee);
-    let balance_ratio = new_token_balance_out.fixed_div_ceil(&e, &token_balance_out, &bone);
-
-    let pool_ratio = c_pow(e, &balance_ratio, &normalized_weight, true);
-    let new_pool_supply = pool_ratio.fixed_mul_ceil(&e, &pool_supply, &bone);
-    let result = sub_no_negative(&e, &pool_supply, &new_pool_supply);
-
-    downscale_ceil(e, &result, STROOP_SCALAR)
+    let result = c_math::calc_lp_token_amount_given_token_withdrawal_amount(
+        &math_record(out_record),
+        pool_supply,
+        token_amount_out,
+        swap_fee,
+        exit_fee,
+    );
+    unwrap_math(e, result)
 }
 
 /// Calculating the amount of tokens being withdrawn,
 /// given how many LP tokens the user wants to burn.
+/// The exit fee is kept from `pool_amount_in` before the withdrawal.
 ///
 /// Rounds down to benefit the pool
 pub fn calc_token_withdrawal_amount_given_lp_token_amount(
@@ -209,148 +152,80 @@ pub fn calc_token_withdrawal_amount_given_lp_token_amount(
     pool_supply: i128,
     pool_amount_in: i128,
     swap_fee: i128,
+    exit_fee: i128,
 ) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
-    let pool_amount_in = upscale(e, pool_amount_in, STROOP_SCALAR);
-    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
-    let fee = upscale(e, swap_fee, STROOP_SCALAR);
-
-    let normalized_weight = upscale(e, out_record.weight, STROOP_SCALAR);
-
-    let new_pool_supply = pool_supply.sub(&pool_amount_in);
-    let pool_ratio = new_pool_supply.fixed_div_floor(&e, &pool_supply, &bone);
-
-    let exp = bone.fixed_div_floor(e, &normalized_weight, &bone);
-    let token_out_ratio = c_pow(e, &pool_ratio, &exp, false);
-    let new_token_balance_out = token_balance_out.fixed_mul_floor(&e, &token_out_ratio, &bone);
-
-    let token_amount_out_before_fee =
-        sub_no_negative(e, &token_balance_out, &new_token_balance_out);
-
-    let zaz = bone.sub(&normalized_weight).fixed_mul_floor(e, &fee, &bone);
-    let result = token_amount_out_before_fee.fixed_mul_floor(&e, &bone.sub(&zaz), &bone);
-
-    downscale_floor(e, &result, out_record.scalar)
+    let result = c_math::calc_token_withdrawal_amount_given_lp_token_amount(
+        &math_record(out_record),
+        pool_supply,
+        pool_amount_in,
+        swap_fee,
+        exit_fee,
+    );
+    unwrap_math(e, result)
 }
 
 /// Calculate the join balance ratio
 ///
 /// Rounds up to benefit the pool
-pub fn calc_join_ratio(e: &Env, pool_supply: i128, pool_amount_out: i128) -> I256 {
-    let bone = I256::from_i128(e, BONE);
-    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
-    let pool_amount_out = upscale(e, pool_amount_out, STROOP_SCALAR);
-
-    pool_amount_out.fixed_div_ceil(&e, &pool_supply, &bone)
+pub fn calc_join_ratio(pool_supply: i128, pool_amount_out: i128) -> I256 {
+    c_math::calc_join_ratio(pool_supply, pool_amount_out)
 }
 
 /// Calculate the join deposit amount given the join balance ratio
 ///
 /// Rounds up to benefit the pool
 pub fn calc_join_deposit_amount(e: &Env, in_record: &Record, join_ratio: &I256) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
-
-    let result = token_balance_in.fixed_mul_ceil(&e, join_ratio, &bone);
-    downscale_ceil(e, &result, in_record.scalar)
+    let result = c_math::calc_join_deposit_amount(&math_record(in_record), join_ratio);
+    unwrap_math(e, result)
 }
 
 /// Calculate the exit balance ratio
 ///
 /// Rounds down to benefit the pool
-pub fn calc_exit_ratio(e: &Env, pool_supply: i128, pool_amount_in: i128) -> I256 {
-    let bone = I256::from_i128(e, BONE);
-    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
-    let pool_amount_in = upscale(e, pool_amount_in, STROOP_SCALAR);
-
-    pool_amount_in.fixed_div_floor(&e, &pool_supply, &bone)
+pub fn calc_exit_ratio(pool_supply: i128, pool_amount_in: i128) -> I256 {
+    c_math::calc_exit_ratio(pool_supply, pool_amount_in)
 }
 
 /// Calculate the exit withdrawal amount given the exit balance ratio
 ///
 /// Rounds down to benefit the pool
 pub fn calc_exit_withdrawal_amount(e: &Env, out_record: &Record, exit_ratio: &I256) -> i128 {
-    let bone = I256::from_i128(e, BONE);
-    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
-
-    let result = token_balance_out.fixed_mul_floor(&e, exit_ratio, &bone);
-    downscale_floor(e, &result, out_record.scalar)
+    let result = c_math::calc_exit_withdrawal_amount(&math_record(out_record), exit_ratio);
+    unwrap_math(e, result)
 }
 
-/********** Scaling Utils **********/
-
-/// Upscale a number to 18 decimals and 256 bits for use in pool math
-///
-/// Requires that "amount" is less that 1.7e19 * scalar
-///
-/// Will fail if `amount` is greater than 1e18 * scalar
-fn upscale(e: &Env, amount: i128, scalar: i128) -> I256 {
-    I256::from_i128(e, amount * scalar)
-}
-
-/// Downscale a number from 18 decimals and 256 bits to i128 to represent a token amount.
+/// Calculate the weights of a linear weight schedule at the given ledger sequence
 ///
-/// Rounds floor if there is any remainder.
-fn downscale_floor(e: &Env, amount: &I256, scalar: i128) -> i128 {
-    let scale_256 = I256::from_i128(e, scalar);
-    let one = I256::from_i32(e, 1);
-    let result = amount.fixed_div_floor(&e, &scale_256, &one).to_i128();
-    assert_with_error!(&e, result.is_some(), Error::ErrMathApprox);
-    result.unwrap_optimized()
-}
+/// See `math::c_math::calc_scheduled_weights`
+pub fn calc_scheduled_weights(e: &Env, schedule: &WeightSchedule, sequence: u32) -> Vec<i128> {
+    let len = schedule.start_weights.len() as usize;
+    let mut start_weights = [0i128; MAX_BOUND_TOKENS as usize];
+    let mut end_weights = [0i128; MAX_BOUND_TOKENS as usize];
+    let scheduled = schedule
+        .start_weights
+        .iter()
+        .zip(schedule.end_weights.iter());
+    for (i, (start_weight, end_weight)) in scheduled.enumerate() {
+        start_weights[i] = start_weight;
+        end_weights[i] = end_weight;
+    }
 
-/// Descale a number from 18 decimals and 256 bits to i128 to represent a token amount.
-///
-/// Rounds up if there is any remainder.
-fn downscale_ceil(e: &Env, amount: &I256, scalar: i128) -> i128 {
-    let scale_256 = I256::from_i128(e, scalar);
-    let one = I256::from_i32(e, 1);
-    let result = amount.fixed_div_ceil(&e, &scale_256, &one).to_i128();
-    assert_with_error!(&e, result.is_some(), Error::ErrMathApprox);
-    result.unwrap_optimized()
+    let mut weights = [0i128; MAX_BOUND_TOKENS as usize];
+    c_math::calc_scheduled_weights(
+        schedule.start_ledger,
+        schedule.end_ledger,
+        &start_weights[..len],
+        &end_weights[..len],
+        sequence,
+        &mut weights[..len],
+    );
+    Vec::from_slice(e, &weights[..len])
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_scale_stroop() {
-        let env = Env::default();
-        let x: i128 = 12345_1234567i128;
-
-        let mut scaled = upscale(&env, x, STROOP_SCALAR);
-        let expected = I256::from_i128(&env, 12345_1234567_00_000_000_000i128);
-        assert_eq!(scaled, expected);
-
-        // takes floor
-        scaled = scaled.add(&I256::from_i128(&env, STROOP_SCALAR / 10));
-        let floor = downscale_floor(&env, &scaled, STROOP_SCALAR);
-        assert_eq!(x, floor);
-
-        // takes ceil
-        let ceil = downscale_ceil(&env, &scaled, STROOP_SCALAR);
-        assert_eq!(x + 1, ceil);
-    }
-
-    #[test]
-    #[should_panic(expected = "Error(Contract, #18)")]
-    fn test_downscale_floor_too_large_panics() {
-        let env = Env::default();
-        let x = I256::from_i128(&env, i128::MAX);
-        let too_large = x.mul(&I256::from_i128(&env, STROOP_SCALAR)).add(&x);
-        downscale_floor(&env, &too_large, STROOP_SCALAR);
-    }
-
-    #[test]
-    #[should_panic(expected = "Error(Contract, #18)")]
-    fn test_downscale_ceil_too_large_panics() {
-        let env = Env::default();
-        let x = I256::from_i128(&env, i128::MAX);
-        let too_large = x.mul(&I256::from_i128(&env, STROOP_SCALAR)).add(&x);
-        downscale_ceil(&env, &too_large, STROOP_SCALAR);
-    }
+    use crate::c_consts::{BONE, STROOP, STROOP_SCALAR};
 
     #[test]
     fn test_calc_stroop_inputs_round_correctly() {
@@ -384,21 +259,18 @@ mod tests {
         assert_eq!(result, 1);
 
         // exit
-        let result = calc_exit_ratio(&env, 10 * STROOP, 1);
-        assert_eq!(result, I256::from_i128(&env, STROOP_SCALAR / 10));
+        let result = calc_exit_ratio(10 * STROOP, 1);
+        assert_eq!(result, I256::from_i128(STROOP_SCALAR / 10));
 
-        let result = calc_exit_withdrawal_amount(
-            &env,
-            &record_2,
-            &I256::from_i128(&env, STROOP_SCALAR / 10),
-        );
+        let result =
+            calc_exit_withdrawal_amount(&env, &record_2, &I256::from_i128(STROOP_SCALAR / 10));
         assert_eq!(result, 0);
 
         // join
-        let result = calc_join_ratio(&env, BONE, 1);
-        assert_eq!(result, I256::from_i32(&env, 1));
+        let result = calc_join_ratio(BONE, 1);
+        assert_eq!(result, I256::from_i32(1));
 
-        let result = calc_join_deposit_amount(&env, &record_1, &I256::from_i32(&env, 1));
+        let result = calc_join_deposit_amount(&env, &record_1, &I256::from_i32(1));
         assert_eq!(result, 1);
 
         // deposit
@@ -420,23 +292,55 @@ mod tests {
 
         // withdraw
         let result = calc_lp_token_amount_given_token_withdrawal_amount(
-            &env, &record_1, supply, 1, swap_fee,
+            &env, &record_1, supply, 1, swap_fee, 0,
         );
         assert_eq!(result, 1);
 
         let result = calc_token_withdrawal_amount_given_lp_token_amount(
-            &env, &record_1, supply, 1, swap_fee,
+            &env, &record_1, supply, 1, swap_fee, 0,
         );
         assert_eq!(result, 3);
 
         let result = calc_lp_token_amount_given_token_withdrawal_amount(
-            &env, &record_2, supply, 1, swap_fee,
+            &env, &record_2, supply, 1, swap_fee, 0,
         );
         assert_eq!(result, 1);
 
         let result = calc_token_withdrawal_amount_given_lp_token_amount(
-            &env, &record_2, supply, 1, swap_fee,
+            &env, &record_2, supply, 1, swap_fee, 0,
         );
         assert_eq!(result, 1);
     }
+
+    #[test]
+    fn test_calc_scheduled_weights_sum_to_total() {
+        let env = Env::default();
+        let schedule = WeightSchedule {
+            start_ledger: 100,
+            end_ledger: 103,
+            start_weights: soroban_sdk::vec![&env, 0_8000000, 0_1000000, 0_1000000],
+            end_weights: soroban_sdk::vec![&env, 0_1000000, 0_4500000, 0_4500000],
+        };
+
+        let weights = calc_scheduled_weights(&env, &schedule, 99);
+        assert_eq!(weights, schedule.start_weights);
+        let weights = calc_scheduled_weights(&env, &schedule, 103);
+        assert_eq!(weights, schedule.end_weights);
+
+        // 1/3 of the way: 0.5666666.., 0.2166666.., 0.2166666..
+        let weights = calc_scheduled_weights(&env, &schedule, 101);
+        assert_eq!(
+            weights,
+            soroban_sdk::vec![&env, 0_5666667, 0_2166667, 0_2166666]
+        );
+        assert_eq!(weights.iter().sum::<i128>(), STROOP);
+
+        // 2/3 of the way: 0.3333333.., 0.3333333.., 0.3333333..
+        let weights = calc_scheduled_weights(&env, &schedule, 102);
+        assert_eq!(
+            weights,
+            soroban_sdk::vec![&env, 0_3333334, 0_3333333, 0_3333333]
+        );
+        assert_eq!(weights.iter().sum::<i128>(), STROOP);
+    }
 }
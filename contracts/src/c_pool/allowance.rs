@@ -1,8 +1,10 @@
 //! Allowance Utilities for the LP Token
 
 use crate::c_pool::error::Error;
-use crate::c_pool::storage_types::{AllowanceDataKey, AllowanceValue, DataKeyToken};
-use soroban_sdk::{panic_with_error, Address, Env};
+use crate::c_pool::storage_types::{
+    AllowanceDataKey, AllowanceValue, DataKeyToken, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD,
+};
+use soroban_sdk::{panic_with_error, Address, BytesN, Env};
 
 pub fn read_allowance(e: &Env, from: Address, spender: Address) -> AllowanceValue {
     let key = DataKeyToken::Allowance(AllowanceDataKey { from, spender });
@@ -67,3 +69,45 @@ pub fn spend_allowance(e: &Env, from: Address, spender: Address, amount: i128) {
         );
     }
 }
+
+pub fn read_nonce(e: &Env, owner: Address) -> u64 {
+    let key = DataKeyToken::Nonce(owner);
+    if let Some(nonce) = e.storage().persistent().get::<DataKeyToken, u64>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+        nonce
+    } else {
+        0
+    }
+}
+
+pub fn write_nonce(e: &Env, owner: Address, nonce: u64) {
+    let key = DataKeyToken::Nonce(owner);
+    e.storage().persistent().set(&key, &nonce);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+pub fn read_permit_key(e: &Env, owner: Address) -> Option<BytesN<32>> {
+    let key = DataKeyToken::PermitKey(owner);
+    let public_key = e
+        .storage()
+        .persistent()
+        .get::<DataKeyToken, BytesN<32>>(&key);
+    if public_key.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+    }
+    public_key
+}
+
+pub fn write_permit_key(e: &Env, owner: Address, public_key: &BytesN<32>) {
+    let key = DataKeyToken::PermitKey(owner);
+    e.storage().persistent().set(&key, public_key);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
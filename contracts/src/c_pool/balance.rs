@@ -1,8 +1,10 @@
 use crate::c_pool::{
-    error::Error,
-    storage_types::{DataKeyToken, BALANCE_BUMP_AMOUNT},
+    error::{Error, ErrorExt},
+    storage_types::{Checkpoint, DataKeyToken, BALANCE_BUMP_AMOUNT},
+};
+use soroban_sdk::{
+    assert_with_error, unwrap::UnwrapOptimized, Address, Env, IntoVal, TryFromVal, Val,
 };
-use soroban_sdk::{assert_with_error, Address, Env};
 
 use super::storage_types::BALANCE_LIFETIME_THRESHOLD;
 
@@ -18,21 +20,179 @@ pub fn read_balance(e: &Env, addr: Address) -> i128 {
     }
 }
 
-fn write_balance(e: &Env, addr: Address, amount: i128) {
-    let key = DataKeyToken::Balance(addr);
+fn write_balance(e: &Env, addr: Address, balance: i128, amount: i128) {
+    let key = DataKeyToken::Balance(addr.clone());
     e.storage().persistent().set(&key, &amount);
     e.storage()
         .persistent()
         .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+    seed_balance_checkpoint(e, &addr, balance);
+    write_checkpoint(
+        e,
+        DataKeyToken::CheckpointCount(addr.clone()),
+        |i| DataKeyToken::Checkpoint(addr.clone(), i),
+        amount,
+    );
 }
 
 pub fn receive_balance(e: &Env, addr: Address, amount: i128) {
     let balance = read_balance(e, addr.clone());
-    write_balance(e, addr, balance + amount);
+    write_balance(e, addr, balance, balance + amount);
 }
 
 pub fn spend_balance(e: &Env, addr: Address, amount: i128) {
     let balance = read_balance(e, addr.clone());
     assert_with_error!(e, balance >= amount, Error::ErrInsufficientBalance);
-    write_balance(e, addr, balance - amount);
+    write_balance(e, addr, balance, balance - amount);
+}
+
+// Balances held before a migrated pool started recording checkpoints have none,
+// record `balance` from the ledger of the migration before the first change of it
+fn seed_balance_checkpoint(e: &Env, addr: &Address, balance: i128) {
+    let count_key = DataKeyToken::CheckpointCount(addr.clone());
+    if balance == 0 || read_persistent::<u32>(e, &count_key).is_some() {
+        return;
+    }
+    if let Some(ledger) = read_history_start(e) {
+        write_persistent(e, &count_key, &1u32);
+        write_persistent(
+            e,
+            &DataKeyToken::Checkpoint(addr.clone(), 0),
+            &Checkpoint {
+                ledger,
+                amount: balance,
+            },
+        );
+    }
+}
+
+// Read the balance of `addr` at the end of `ledger`
+//
+// Balances that have not changed since the migration have no checkpoints
+// and are read from the current balance
+pub fn read_balance_at(e: &Env, addr: Address, ledger: u32) -> i128 {
+    check_ledger_final(e, ledger);
+    check_history_available(e, ledger);
+    if read_persistent::<u32>(e, &DataKeyToken::CheckpointCount(addr.clone())).is_none() {
+        return read_balance(e, addr);
+    }
+    read_checkpoint(
+        e,
+        DataKeyToken::CheckpointCount(addr.clone()),
+        |i| DataKeyToken::Checkpoint(addr.clone(), i),
+        ledger,
+    )
+}
+
+// Read the total supply at the end of `ledger`
+pub fn read_total_supply_at(e: &Env, ledger: u32) -> i128 {
+    check_ledger_final(e, ledger);
+    check_history_available(e, ledger);
+    read_checkpoint(
+        e,
+        DataKeyToken::SupplyCheckpointCount,
+        DataKeyToken::SupplyCheckpoint,
+        ledger,
+    )
+}
+
+// Check that `ledger` has closed, balances in the current ledger can still change
+fn check_ledger_final(e: &Env, ledger: u32) {
+    assert_with_error!(
+        e,
+        ledger < e.ledger().sequence(),
+        ErrorExt::ErrLedgerNotFinal
+    );
+}
+
+// Check that checkpoints were recorded at `ledger`, pools migrated from before
+// checkpoints existed only have them from the ledger of the migration on
+fn check_history_available(e: &Env, ledger: u32) {
+    if let Some(start) = read_history_start(e) {
+        assert_with_error!(e, ledger >= start, ErrorExt::ErrHistoryUnavailable);
+    }
+}
+
+fn read_history_start(e: &Env) -> Option<u32> {
+    read_persistent::<u32>(e, &DataKeyToken::HistoryStart)
+}
+
+// Start recording checkpoints from the current ledger, with the current `total_supply`
+pub fn write_history_start(e: &Env, total_supply: i128) {
+    write_persistent(e, &DataKeyToken::HistoryStart, &e.ledger().sequence());
+    write_supply_checkpoint(e, total_supply);
+}
+
+// Record the new total supply for the current ledger
+pub fn write_supply_checkpoint(e: &Env, amount: i128) {
+    write_checkpoint(
+        e,
+        DataKeyToken::SupplyCheckpointCount,
+        DataKeyToken::SupplyCheckpoint,
+        amount,
+    );
+}
+
+// Record `amount` from the current ledger on, checkpoints are stored one per entry
+// under `checkpoint_key(i)` and updated in place within the same ledger
+fn write_checkpoint(
+    e: &Env,
+    count_key: DataKeyToken,
+    checkpoint_key: impl Fn(u32) -> DataKeyToken,
+    amount: i128,
+) {
+    let ledger = e.ledger().sequence();
+    let count = read_persistent::<u32>(e, &count_key).unwrap_or(0);
+    let index = match count.checked_sub(1) {
+        Some(last) if read_checkpoint_at(e, &checkpoint_key(last)).ledger == ledger => last,
+        _ => {
+            write_persistent(e, &count_key, &(count + 1));
+            count
+        }
+    };
+    write_persistent(e, &checkpoint_key(index), &Checkpoint { ledger, amount });
+}
+
+// Binary search the last checkpoint at or before `ledger`, 0 if there is none
+fn read_checkpoint(
+    e: &Env,
+    count_key: DataKeyToken,
+    checkpoint_key: impl Fn(u32) -> DataKeyToken,
+    ledger: u32,
+) -> i128 {
+    let mut low = 0;
+    let mut high = read_persistent::<u32>(e, &count_key).unwrap_or(0);
+    let mut amount = 0;
+    while low < high {
+        let mid = (low + high) / 2;
+        let checkpoint = read_checkpoint_at(e, &checkpoint_key(mid));
+        if checkpoint.ledger <= ledger {
+            amount = checkpoint.amount;
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    amount
+}
+
+fn read_checkpoint_at(e: &Env, key: &DataKeyToken) -> Checkpoint {
+    read_persistent::<Checkpoint>(e, key).unwrap_optimized()
+}
+
+fn read_persistent<V: TryFromVal<Env, Val>>(e: &Env, key: &DataKeyToken) -> Option<V> {
+    let value = e.storage().persistent().get::<DataKeyToken, V>(key);
+    if value.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+    }
+    value
+}
+
+fn write_persistent<V: IntoVal<Env, Val>>(e: &Env, key: &DataKeyToken, value: &V) {
+    e.storage().persistent().set(key, value);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
 }
@@ -0,0 +1,213 @@
+use soroban_sdk::{assert_with_error, panic_with_error, symbol_short, Address, Env, Vec};
+
+use crate::{
+    c_consts::{
+        MAX_EXIT_FEE, MAX_FEE, MAX_FEE_DELTA, MAX_WEIGHT, MIN_FEE, MIN_FEE_UPDATE_DELAY,
+        MIN_WEIGHT, STROOP,
+    },
+    c_pool::{
+        error::{Error, ErrorExt},
+        event::{ControllerEvent, FeeChangeEvent, GuardianEvent, WeightScheduleEvent},
+        metadata::{
+            read_exit_fee, read_finalize, read_guardian, read_pending_controller, read_records,
+            read_swap_fee, read_swap_fee_ledger, read_tokens, remove_guardian,
+            remove_pending_controller, write_controller, write_exit_fee, write_guardian,
+            write_pending_controller, write_pending_exit_fee, write_swap_fee,
+            write_swap_fee_ledger, write_weight_schedule,
+        },
+        storage_types::{PendingExitFee, WeightSchedule},
+    },
+};
+
+use super::pool::{publish_sync_event, POOL};
+
+// Update the swap fee, bounded in size and frequency
+pub fn execute_set_swap_fee(e: Env, swap_fee: i128, caller: Address) {
+    assert_with_error!(&e, swap_fee >= MIN_FEE, Error::ErrMinFee);
+    assert_with_error!(&e, swap_fee <= MAX_FEE, Error::ErrMaxFee);
+
+    let old_fee = read_swap_fee(&e);
+    check_fee_delta(&e, old_fee, swap_fee);
+    record_fee_update(&e);
+
+    write_swap_fee(&e, swap_fee);
+
+    let event: FeeChangeEvent = FeeChangeEvent {
+        caller,
+        old_fee,
+        new_fee: swap_fee,
+    };
+    e.events().publish((POOL, symbol_short!("set_fee")), event);
+
+    publish_sync_event(&e);
+}
+
+// Check that a swap fee, or a bound of the dynamic fee, moves by at most MAX_FEE_DELTA
+pub(crate) fn check_fee_delta(e: &Env, old_fee: i128, new_fee: i128) {
+    let delta = (new_fee - old_fee).abs();
+    assert_with_error!(e, delta <= MAX_FEE_DELTA, Error::ErrMaxFeeDelta);
+}
+
+// Check that MIN_FEE_UPDATE_DELAY ledgers passed since the last swap fee update,
+// shared by the fixed and the dynamic fee, and record this one
+pub(crate) fn record_fee_update(e: &Env) {
+    let sequence = e.ledger().sequence();
+    if let Some(last_update) = read_swap_fee_ledger(e) {
+        assert_with_error!(
+            e,
+            sequence >= last_update + MIN_FEE_UPDATE_DELAY,
+            Error::ErrFeeUpdateTooSoon
+        );
+    }
+    write_swap_fee_ledger(e, sequence);
+}
+
+// Update the exit fee charged on the LP tokens burned when exiting the pool
+//
+// Decreases apply at once, increases only after MIN_FEE_UPDATE_DELAY ledgers
+// so they cannot front-run a withdrawal
+pub fn execute_set_exit_fee(e: Env, exit_fee: i128, caller: Address) {
+    assert_with_error!(&e, exit_fee >= 0, Error::ErrNegative);
+    assert_with_error!(&e, exit_fee <= MAX_EXIT_FEE, Error::ErrMaxExitFee);
+
+    let old_fee = read_exit_fee(&e);
+    if exit_fee <= old_fee {
+        write_exit_fee(&e, exit_fee);
+    } else {
+        write_exit_fee(&e, old_fee);
+        write_pending_exit_fee(
+            &e,
+            PendingExitFee {
+                exit_fee,
+                ledger: e.ledger().sequence() + MIN_FEE_UPDATE_DELAY,
+            },
+        );
+    }
+
+    let event: FeeChangeEvent = FeeChangeEvent {
+        caller,
+        old_fee,
+        new_fee: exit_fee,
+    };
+    e.events().publish((POOL, symbol_short!("exit_fee")), event);
+}
+
+// Schedule a linear change from the current weights to `end_weights`
+// between `start_ledger` and `end_ledger`
+pub fn execute_set_weight_schedule(
+    e: Env,
+    end_weights: Vec<i128>,
+    start_ledger: u32,
+    end_ledger: u32,
+    caller: Address,
+) {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    assert_with_error!(
+        &e,
+        start_ledger >= e.ledger().sequence() && end_ledger > start_ledger,
+        Error::ErrInvalidSchedule
+    );
+
+    let tokens = read_tokens(&e);
+    assert_with_error!(
+        &e,
+        end_weights.len() == tokens.len(),
+        Error::ErrInvalidVectorLen
+    );
+
+    let records = read_records(&e);
+    let mut start_weights = Vec::<i128>::new(&e);
+    let mut total_weight: i128 = 0;
+    for i in 0..tokens.len() {
+        let weight = end_weights.get_unchecked(i);
+        assert_with_error!(&e, weight >= MIN_WEIGHT, Error::ErrMinWeight);
+        assert_with_error!(&e, weight <= MAX_WEIGHT, Error::ErrMaxWeight);
+        total_weight += weight;
+
+        let rec = records.get_unchecked(tokens.get_unchecked(i));
+        start_weights.push_back(rec.weight);
+    }
+    assert_with_error!(&e, total_weight == STROOP, Error::ErrTotalWeight);
+
+    write_weight_schedule(
+        &e,
+        WeightSchedule {
+            start_ledger,
+            end_ledger,
+            start_weights: start_weights.clone(),
+            end_weights: end_weights.clone(),
+        },
+    );
+
+    let event: WeightScheduleEvent = WeightScheduleEvent {
+        caller,
+        start_ledger,
+        end_ledger,
+        start_weights,
+        end_weights,
+    };
+    e.events().publish((POOL, symbol_short!("weights")), event);
+
+    publish_sync_event(&e);
+}
+
+// Propose a new controller, which has to accept the role before it is transferred
+pub fn execute_propose_controller(e: Env, pending_controller: Address, controller: Address) {
+    write_pending_controller(&e, pending_controller.clone());
+
+    let event: ControllerEvent = ControllerEvent {
+        controller,
+        pending_controller,
+    };
+    e.events()
+        .publish((POOL, symbol_short!("ctrl_prop")), event);
+}
+
+// Transfer the controller role to the pending controller
+pub fn execute_accept_controller(e: Env, pending_controller: Address, controller: Address) {
+    remove_pending_controller(&e);
+    write_controller(&e, pending_controller.clone());
+
+    let event: ControllerEvent = ControllerEvent {
+        controller,
+        pending_controller,
+    };
+    e.events()
+        .publish((POOL, symbol_short!("ctrl_acpt")), event);
+}
+
+// Cancel the pending controller transfer
+pub fn execute_cancel_controller(e: Env, controller: Address) {
+    let pending_controller = read_pending_controller(&e)
+        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNoPendingController));
+    remove_pending_controller(&e);
+
+    let event: ControllerEvent = ControllerEvent {
+        controller,
+        pending_controller,
+    };
+    e.events()
+        .publish((POOL, symbol_short!("ctrl_cncl")), event);
+}
+
+// Set the guardian, which can only pause the pool, or remove it if `guardian` is None
+pub fn execute_set_guardian(e: Env, guardian: Option<Address>, controller: Address) {
+    let (guardian, topic) = match guardian {
+        Some(guardian) => {
+            write_guardian(&e, guardian.clone());
+            (guardian, symbol_short!("guardian"))
+        }
+        None => {
+            let guardian =
+                read_guardian(&e).unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrNoGuardian));
+            remove_guardian(&e);
+            (guardian, symbol_short!("guard_rm"))
+        }
+    };
+
+    let event: GuardianEvent = GuardianEvent {
+        controller,
+        guardian,
+    };
+    e.events().publish((POOL, topic), event);
+}
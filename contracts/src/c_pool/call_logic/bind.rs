@@ -0,0 +1,177 @@
+use core::cmp::Ordering;
+
+use soroban_sdk::{
+    assert_with_error, panic_with_error, symbol_short, token::TokenClient, Address, Env,
+};
+
+use crate::{
+    c_consts::{
+        INIT_POOL_SUPPLY, MAX_BOUND_TOKENS, MAX_WEIGHT, MIN_BALANCE, MIN_BOUND_TOKENS, MIN_WEIGHT,
+        STROOP,
+    },
+    c_pool::{
+        error::Error,
+        event::{BindEvent, FinalizeEvent, UnbindEvent},
+        metadata::{
+            read_finalize, read_records, read_tokens, remove_record, write_finalize,
+            write_public_swap, write_record, write_tokens,
+        },
+        storage_types::Record,
+        token_utility::{mint_shares, push_underlying},
+    },
+};
+
+use super::pool::{publish_sync_event, POOL};
+
+// Bind a new token to the pool, transferring the starting balance from the controller
+pub fn execute_bind(e: Env, token: Address, balance: i128, weight: i128, controller: Address) {
+    assert_with_error!(&e, !read_finalize(&e), Error::ErrFinalized);
+
+    let mut tokens = read_tokens(&e);
+    let records = read_records(&e);
+    assert_with_error!(&e, !records.contains_key(token.clone()), Error::ErrIsBound);
+    assert_with_error!(&e, tokens.len() < MAX_BOUND_TOKENS, Error::ErrMaxTokens);
+    assert_with_error!(&e, weight >= MIN_WEIGHT, Error::ErrMinWeight);
+    assert_with_error!(&e, weight <= MAX_WEIGHT, Error::ErrMaxWeight);
+    assert_with_error!(&e, balance >= MIN_BALANCE, Error::ErrMinBalance);
+
+    let mut total_weight = weight;
+    for (_, rec) in records.iter() {
+        total_weight += rec.weight;
+    }
+    assert_with_error!(&e, total_weight <= STROOP, Error::ErrTotalWeight);
+
+    let token_client = TokenClient::new(&e, &token);
+    let decimals = token_client.decimals();
+    assert_with_error!(&e, decimals <= 18, Error::ErrTokenInvalid);
+    let scalar = 10i128.pow(18 - decimals);
+
+    token_client.transfer(&controller, &e.current_contract_address(), &balance);
+
+    let record = Record {
+        balance,
+        weight,
+        scalar,
+        index: tokens.len(),
+    };
+    write_record(&e, &token, &record);
+    tokens.push_back(token.clone());
+    write_tokens(&e, tokens);
+
+    let event: BindEvent = BindEvent {
+        caller: controller,
+        token,
+        balance,
+        weight,
+    };
+    e.events().publish((POOL, symbol_short!("bind")), event);
+
+    publish_sync_event(&e);
+}
+
+// Update the balance and weight of a bound token, settling the
+// balance difference with the controller
+pub fn execute_rebind(e: Env, token: Address, balance: i128, weight: i128, controller: Address) {
+    assert_with_error!(&e, !read_finalize(&e), Error::ErrFinalized);
+
+    let records = read_records(&e);
+    let mut record = records
+        .get(token.clone())
+        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    assert_with_error!(&e, weight >= MIN_WEIGHT, Error::ErrMinWeight);
+    assert_with_error!(&e, weight <= MAX_WEIGHT, Error::ErrMaxWeight);
+    assert_with_error!(&e, balance >= MIN_BALANCE, Error::ErrMinBalance);
+
+    let mut total_weight = weight;
+    for (t, rec) in records.iter() {
+        if t != token {
+            total_weight += rec.weight;
+        }
+    }
+    assert_with_error!(&e, total_weight <= STROOP, Error::ErrTotalWeight);
+
+    match balance.cmp(&record.balance) {
+        Ordering::Greater => TokenClient::new(&e, &token).transfer(
+            &controller,
+            &e.current_contract_address(),
+            &(balance - record.balance),
+        ),
+        Ordering::Less => push_underlying(&e, &token, &controller, record.balance - balance),
+        Ordering::Equal => (),
+    }
+
+    record.balance = balance;
+    record.weight = weight;
+    write_record(&e, &token, &record);
+
+    let event: BindEvent = BindEvent {
+        caller: controller,
+        token,
+        balance,
+        weight,
+    };
+    e.events().publish((POOL, symbol_short!("rebind")), event);
+
+    publish_sync_event(&e);
+}
+
+// Remove a token from the pool and return its balance to the controller
+pub fn execute_unbind(e: Env, token: Address, controller: Address) {
+    assert_with_error!(&e, !read_finalize(&e), Error::ErrFinalized);
+
+    let mut tokens = read_tokens(&e);
+    let records = read_records(&e);
+    let record = records
+        .get(token.clone())
+        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+
+    // move the last token into the unbound token's index
+    let last_index = tokens.len() - 1;
+    if record.index != last_index {
+        let last_token = tokens.get_unchecked(last_index);
+        let mut last_record = records.get_unchecked(last_token.clone());
+        last_record.index = record.index;
+        tokens.set(record.index, last_token.clone());
+        write_record(&e, &last_token, &last_record);
+    }
+    tokens.pop_back();
+    remove_record(&e, &token);
+    write_tokens(&e, tokens);
+
+    push_underlying(&e, &token, &controller, record.balance);
+
+    let event: UnbindEvent = UnbindEvent {
+        caller: controller,
+        token,
+        token_amount_out: record.balance,
+    };
+    e.events().publish((POOL, symbol_short!("unbind")), event);
+
+    publish_sync_event(&e);
+}
+
+// Finalize the pool, enabling public swaps and joins and minting the
+// initial pool supply to the controller
+pub fn execute_finalize(e: Env, controller: Address) {
+    assert_with_error!(&e, !read_finalize(&e), Error::ErrFinalized);
+
+    let records = read_records(&e);
+    assert_with_error!(&e, records.len() >= MIN_BOUND_TOKENS, Error::ErrMinTokens);
+    let mut total_weight: i128 = 0;
+    for (_, rec) in records.iter() {
+        total_weight += rec.weight;
+    }
+    assert_with_error!(&e, total_weight == STROOP, Error::ErrTotalWeight);
+
+    write_finalize(&e, true);
+    write_public_swap(&e, true);
+    mint_shares(&e, &controller, INIT_POOL_SUPPLY);
+
+    let event: FinalizeEvent = FinalizeEvent {
+        caller: controller,
+        pool_amount_out: INIT_POOL_SUPPLY,
+    };
+    e.events().publish((POOL, symbol_short!("finalize")), event);
+
+    publish_sync_event(&e);
+}
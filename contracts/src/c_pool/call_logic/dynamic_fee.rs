@@ -0,0 +1,146 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    assert_with_error, panic_with_error, symbol_short, unwrap::UnwrapOptimized, Address, Env,
+};
+
+use crate::{
+    c_consts::{MAX_FEE, MAX_VOLATILITY, MIN_FEE, STROOP, VOLATILITY_DECAY_LEDGERS},
+    c_pool::{
+        error::{Error, ErrorExt},
+        event::DynamicFeeEvent,
+        metadata::{
+            read_dynamic_fee, read_swap_fee, read_volatility, remove_dynamic_fee,
+            remove_volatility, write_dynamic_fee, write_volatility,
+        },
+        storage_types::{DynamicFee, Volatility},
+    },
+};
+
+use super::{
+    admin::{check_fee_delta, record_fee_update},
+    pool::{publish_sync_event, POOL},
+};
+
+// Get the swap fee charged by the next swap
+//
+// If the dynamic fee is enabled, the fee moves linearly from `min_fee` to `max_fee`
+// as the decayed volatility grows to MAX_VOLATILITY, otherwise it is the fixed swap fee
+pub fn read_effective_swap_fee(e: &Env) -> i128 {
+    match read_dynamic_fee(e) {
+        Some(dynamic_fee) => {
+            let volatility = decayed_volatility(e, &read_volatility(e));
+            dynamic_fee.min_fee
+                + (dynamic_fee.max_fee - dynamic_fee.min_fee)
+                    .fixed_mul_floor(volatility, MAX_VOLATILITY)
+                    .unwrap_optimized()
+        }
+        None => read_swap_fee(e),
+    }
+}
+
+// Add the relative move from `spot_price_before` to `spot_price_after`
+// to the volatility, if the dynamic fee is enabled
+pub fn update_volatility(e: &Env, spot_price_before: i128, spot_price_after: i128) {
+    if read_dynamic_fee(e).is_none() {
+        return;
+    }
+
+    let price_move = (spot_price_after - spot_price_before)
+        .abs()
+        .fixed_div_floor(spot_price_before, STROOP)
+        .unwrap_optimized();
+    let volatility = decayed_volatility(e, &read_volatility(e)) + price_move;
+    write_volatility(
+        e,
+        Volatility {
+            volatility: volatility.min(MAX_VOLATILITY),
+            last_ledger: e.ledger().sequence(),
+        },
+    );
+}
+
+// Add the price move of a single-sided deposit or withdrawal, which only changes
+// the balance of one token, to the volatility, if the dynamic fee is enabled
+//
+// The spot price of that token against every other one is inversely proportional
+// to its balance, so it moves by `balance_before / balance_after`
+pub fn update_volatility_single_sided(e: &Env, balance_before: i128, balance_after: i128) {
+    update_volatility(e, balance_after, balance_before);
+}
+
+// Decay the volatility linearly to zero over VOLATILITY_DECAY_LEDGERS
+fn decayed_volatility(e: &Env, volatility: &Volatility) -> i128 {
+    let elapsed = e.ledger().sequence().saturating_sub(volatility.last_ledger);
+    if elapsed >= VOLATILITY_DECAY_LEDGERS {
+        return 0;
+    }
+    volatility
+        .volatility
+        .fixed_mul_floor(
+            (VOLATILITY_DECAY_LEDGERS - elapsed) as i128,
+            VOLATILITY_DECAY_LEDGERS as i128,
+        )
+        .unwrap_optimized()
+}
+
+// Get the bounds of the swap fee, the fixed swap fee bounds it on both sides
+// while the dynamic fee is disabled
+fn read_fee_bounds(e: &Env) -> (i128, i128) {
+    match read_dynamic_fee(e) {
+        Some(dynamic_fee) => (dynamic_fee.min_fee, dynamic_fee.max_fee),
+        None => {
+            let swap_fee = read_swap_fee(e);
+            (swap_fee, swap_fee)
+        }
+    }
+}
+
+// Enable the dynamic fee, or update its bounds
+//
+// Like the swap fee, each bound moves by at most MAX_FEE_DELTA
+// and once every MIN_FEE_UPDATE_DELAY ledgers
+pub fn execute_set_dynamic_fee(e: Env, min_fee: i128, max_fee: i128, caller: Address) {
+    assert_with_error!(&e, min_fee >= MIN_FEE, Error::ErrMinFee);
+    assert_with_error!(&e, max_fee <= MAX_FEE, Error::ErrMaxFee);
+    assert_with_error!(&e, min_fee <= max_fee, ErrorExt::ErrInvalidDynamicFee);
+
+    let (old_min_fee, old_max_fee) = read_fee_bounds(&e);
+    check_fee_delta(&e, old_min_fee, min_fee);
+    check_fee_delta(&e, old_max_fee, max_fee);
+    record_fee_update(&e);
+
+    write_dynamic_fee(&e, DynamicFee { min_fee, max_fee });
+
+    let event: DynamicFeeEvent = DynamicFeeEvent {
+        caller,
+        min_fee,
+        max_fee,
+    };
+    e.events().publish((POOL, symbol_short!("dyn_fee")), event);
+
+    publish_sync_event(&e);
+}
+
+// Disable the dynamic fee, swaps are charged the fixed swap fee again
+//
+// The fixed swap fee has to be within MAX_FEE_DELTA of both bounds,
+// and MIN_FEE_UPDATE_DELAY ledgers have to pass since the last fee update
+pub fn execute_disable_dynamic_fee(e: Env, caller: Address) {
+    let dynamic_fee = read_dynamic_fee(&e)
+        .unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrInvalidDynamicFee));
+    let swap_fee = read_swap_fee(&e);
+    check_fee_delta(&e, dynamic_fee.min_fee, swap_fee);
+    check_fee_delta(&e, dynamic_fee.max_fee, swap_fee);
+    record_fee_update(&e);
+    remove_dynamic_fee(&e);
+    remove_volatility(&e);
+
+    let event: DynamicFeeEvent = DynamicFeeEvent {
+        caller,
+        min_fee: dynamic_fee.min_fee,
+        max_fee: dynamic_fee.max_fee,
+    };
+    e.events().publish((POOL, symbol_short!("dyn_off")), event);
+
+    publish_sync_event(&e);
+}
@@ -0,0 +1,89 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    assert_with_error, panic_with_error, symbol_short, token, unwrap::UnwrapOptimized, Address,
+    Bytes, Env,
+};
+
+use crate::{
+    c_consts::{EVENT_VERSION, PAUSE_SWAP, STROOP},
+    c_pool::{
+        error::Error,
+        event::FlashLoanEvent,
+        flash_loan_receiver::FlashLoanReceiverClient,
+        metadata::{
+            get_total_shares, read_flash_loan_lock, read_protocol_fees, read_public_swap,
+            read_record, write_flash_loan_lock, write_record,
+        },
+        token_utility::push_underlying,
+    },
+};
+
+use super::{
+    dynamic_fee::read_effective_swap_fee, oracle::update_oracle, pause::check_not_paused,
+    pool::POOL,
+};
+
+// Lend `amount` of `token` to `receiver`, which has to return it plus a fee
+// at the swap fee rate during the `on_flash_loan` callback
+pub fn execute_flash_loan(
+    e: Env,
+    initiator: Address,
+    receiver: Address,
+    token: Address,
+    amount: i128,
+    data: Bytes,
+) {
+    assert_with_error!(&e, !read_flash_loan_lock(&e), Error::ErrReentrancy);
+    assert_with_error!(&e, read_public_swap(&e), Error::ErrSwapNotPublic);
+    check_not_paused(&e, PAUSE_SWAP);
+    assert_with_error!(&e, amount > 0, Error::ErrNegativeOrZero);
+    update_oracle(&e);
+
+    let mut rec =
+        read_record(&e, &token).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    assert_with_error!(&e, amount <= rec.balance, Error::ErrInsufficientBalance);
+
+    // rounds up to benefit the pool
+    let fee = amount
+        .fixed_mul_ceil(read_effective_swap_fee(&e), STROOP)
+        .unwrap_optimized();
+    let token_client = token::Client::new(&e, &token);
+    let balance_before = token_client.balance(&e.current_contract_address());
+
+    write_flash_loan_lock(&e, true);
+    push_underlying(&e, &token, &receiver, amount);
+    FlashLoanReceiverClient::new(&e, &receiver).on_flash_loan(
+        &initiator,
+        &e.current_contract_address(),
+        &token,
+        &amount,
+        &fee,
+        &data,
+    );
+    write_flash_loan_lock(&e, false);
+
+    let balance_after = token_client.balance(&e.current_contract_address());
+    assert_with_error!(
+        &e,
+        balance_after >= balance_before + fee,
+        Error::ErrFlashLoanNotRepaid
+    );
+
+    // accrued protocol fees are held by the pool but are not part of its balance,
+    // any overpayment above the fee is kept by the pool
+    let protocol_fees = read_protocol_fees(&e).get(token.clone()).unwrap_or(0);
+    rec.balance = balance_after - protocol_fees;
+    write_record(&e, &token, &rec);
+
+    let event: FlashLoanEvent = FlashLoanEvent {
+        version: EVENT_VERSION,
+        receiver,
+        token,
+        amount,
+        fee,
+        balance: rec.balance,
+        total_supply: get_total_shares(&e),
+    };
+    e.events()
+        .publish((POOL, symbol_short!("flashloan")), event);
+}
@@ -2,22 +2,20 @@ use soroban_sdk::{unwrap::UnwrapOptimized, Address, Env};
 
 use crate::{
     c_math::calc_spot_price,
-    c_pool::metadata::{read_record, read_swap_fee},
+    c_pool::{call_logic::dynamic_fee::read_effective_swap_fee, metadata::read_record},
 };
 
 // Calculate the spot considering the swap fee
 pub fn execute_get_spot_price(e: Env, token_in: Address, token_out: Address) -> i128 {
-    let record = read_record(&e);
-    let in_record = record.get(token_in).unwrap_optimized();
-    let out_record = record.get(token_out).unwrap_optimized();
-    let swap_fee = read_swap_fee(&e);
+    let in_record = read_record(&e, &token_in).unwrap_optimized();
+    let out_record = read_record(&e, &token_out).unwrap_optimized();
+    let swap_fee = read_effective_swap_fee(&e);
     calc_spot_price(&in_record, &out_record, swap_fee)
 }
 
 // Get the spot price without considering the swap fee
 pub fn execute_get_spot_price_sans_fee(e: Env, token_in: Address, token_out: Address) -> i128 {
-    let record = read_record(&e);
-    let in_record = record.get(token_in).unwrap_optimized();
-    let out_record = record.get(token_out).unwrap_optimized();
+    let in_record = read_record(&e, &token_in).unwrap_optimized();
+    let out_record = read_record(&e, &token_out).unwrap_optimized();
     calc_spot_price(&in_record, &out_record, 0)
 }
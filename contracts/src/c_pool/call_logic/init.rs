@@ -4,15 +4,23 @@ use soroban_sdk::{
 use soroban_token_sdk::metadata::TokenMetadata;
 
 use crate::{
-    c_consts::{INIT_POOL_SUPPLY, MAX_FEE, MAX_WEIGHT, MIN_BALANCE, MIN_FEE, MIN_WEIGHT, STROOP},
+    c_consts::{
+        INIT_POOL_SUPPLY, MAX_FEE, MAX_WEIGHT, MIN_BALANCE, MIN_FEE, MIN_UPGRADE_DELAY, MIN_WEIGHT,
+        STORAGE_VERSION, STROOP,
+    },
     c_pool::{
         error::Error,
-        metadata::{write_controller, write_metadata, write_record, write_swap_fee, write_tokens},
+        metadata::{
+            write_controller, write_finalize, write_metadata, write_public_swap, write_record,
+            write_storage_version, write_swap_fee, write_tokens, write_upgrade_delay,
+        },
         storage_types::{DataKey, Record},
         token_utility::mint_shares,
     },
 };
 
+use super::pool::publish_sync_event;
+
 pub fn execute_init(
     e: &Env,
     controller: Address,
@@ -76,9 +84,52 @@ pub fn execute_init(
     mint_shares(&e, &controller, INIT_POOL_SUPPLY);
     write_swap_fee(&e, swap_fee);
 
-    write_record(e, records);
+    for (token, record) in records.iter() {
+        write_record(e, &token, &record);
+    }
     write_tokens(e, tokens);
+    write_finalize(e, true);
+    write_public_swap(e, true);
+
+    write_lp_token_metadata(e);
+    write_storage_version(e, STORAGE_VERSION);
+    write_upgrade_delay(e, MIN_UPGRADE_DELAY);
+
+    // Store the Controller Address (Pool Admin)
+    write_controller(&e, controller);
+
+    // report the initial state, as no trade event has been published yet
+    publish_sync_event(e);
+}
+
+// Initialize a pool without any tokens bound, where the controller
+// can bind, rebind and unbind tokens until the pool is finalized
+pub fn execute_init_unfinalized(e: &Env, controller: Address, swap_fee: i128) {
+    assert_with_error!(
+        e,
+        !e.storage().instance().has(&DataKey::Controller),
+        Error::AlreadyInitialized
+    );
+    assert_with_error!(
+        e,
+        (MIN_FEE..=MAX_FEE).contains(&swap_fee),
+        Error::ErrSwapFee
+    );
+
+    write_swap_fee(e, swap_fee);
+    write_tokens(e, Vec::<Address>::new(e));
+    write_finalize(e, false);
+    write_public_swap(e, false);
+
+    write_lp_token_metadata(e);
+    write_storage_version(e, STORAGE_VERSION);
+    write_upgrade_delay(e, MIN_UPGRADE_DELAY);
 
+    // Store the Controller Address (Pool Admin)
+    write_controller(e, controller);
+}
+
+fn write_lp_token_metadata(e: &Env) {
     // Name of the LP Token
     let name = String::from_str(&e, "Comet Pool Token");
     // Symbol of the LP Token
@@ -91,7 +142,4 @@ pub fn execute_init(
             decimal: 7u32,
         },
     );
-
-    // Store the Controller Address (Pool Admin)
-    write_controller(&e, controller);
 }
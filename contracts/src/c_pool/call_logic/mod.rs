@@ -1,3 +1,13 @@
+pub mod admin;
+pub mod bind;
+pub mod dynamic_fee;
+pub mod flash_loan;
 pub mod getter;
 pub mod init;
+pub mod oracle;
+pub mod pause;
+pub mod permit;
 pub mod pool;
+pub mod protocol_fee;
+pub mod quote;
+pub mod upgrade;
@@ -0,0 +1,205 @@
+use soroban_fixed_point_math::{FixedPoint, SorobanFixedPoint};
+use soroban_sdk::{
+    assert_with_error, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Vec,
+};
+
+use crate::{
+    c_consts::{OBSERVATION_CARDINALITY, OBSERVATION_INTERVAL, STROOP},
+    c_math::{calc_spot_price, calc_weighted_balance},
+    c_pool::{
+        error::Error,
+        metadata::{
+            read_finalize, read_observation, read_oracle_state, read_price_cumulative, read_record,
+            read_records, read_tokens, read_weight_schedule, read_weighted_balances,
+            write_observation, write_oracle_state, write_price_cumulative, write_weighted_balances,
+        },
+        storage_types::{Observation, OracleState},
+    },
+};
+
+// Index of the accumulator of the spot price of token `j` in token `i`,
+// among the `n * (n - 1)` pairs of `n` tokens
+fn pair_index(n: u32, i: u32, j: u32) -> u32 {
+    i * (n - 1) + if j > i { j - 1 } else { j }
+}
+
+// Calculate the weighted balance of every token from its record
+fn calc_weighted_balances(e: &Env) -> Vec<i128> {
+    let records = read_records(e);
+    let mut weighted_balances = Vec::new(e);
+    for token in read_tokens(e).iter() {
+        weighted_balances.push_back(calc_weighted_balance(&records.get_unchecked(token)));
+    }
+    weighted_balances
+}
+
+// Accumulate the spot prices of every pair for the time elapsed since the last
+// update, and store an observation if OBSERVATION_INTERVAL has passed since the last one
+//
+// The prices come from the weighted balances kept by `write_record`, so only pools
+// with a weight schedule read every record. Must be called before the balances change
+pub fn update_oracle(e: &Env) {
+    if !read_finalize(e) {
+        return;
+    }
+
+    let timestamp = e.ledger().timestamp();
+    let n = read_tokens(e).len();
+    let mut current = match read_price_cumulative(e) {
+        Some(current) if current.timestamp == timestamp => return,
+        Some(current) => current,
+        None => {
+            write_weighted_balances(e, calc_weighted_balances(e));
+            let mut price_cumulatives = Vec::new(e);
+            for _ in 0..n * (n - 1) {
+                price_cumulatives.push_back(0);
+            }
+            Observation {
+                timestamp,
+                price_cumulatives,
+            }
+        }
+    };
+
+    if timestamp > current.timestamp {
+        // scheduled weights move without their records being written
+        let weighted_balances = if read_weight_schedule(e).is_some() {
+            let weighted_balances = calc_weighted_balances(e);
+            write_weighted_balances(e, weighted_balances.clone());
+            weighted_balances
+        } else {
+            read_weighted_balances(e).unwrap_optimized()
+        };
+        let elapsed = (timestamp - current.timestamp) as i128;
+        for i in 0..n {
+            let weighted_balance_in = weighted_balances.get_unchecked(i);
+            for j in (0..n).filter(|j| *j != i) {
+                let price = weighted_balance_in
+                    .fixed_mul_floor(STROOP, weighted_balances.get_unchecked(j))
+                    .unwrap_optimized();
+                let k = pair_index(n, i, j);
+                let cumulative = current.price_cumulatives.get_unchecked(k);
+                current
+                    .price_cumulatives
+                    .set(k, cumulative.wrapping_add(price.wrapping_mul(elapsed)));
+            }
+        }
+        current.timestamp = timestamp;
+    }
+    write_price_cumulative(e, current.clone());
+
+    let mut state = read_oracle_state(e);
+    if state.count == 0 || timestamp >= state.last_timestamp + OBSERVATION_INTERVAL {
+        write_observation(e, state.index, current);
+        state.index = (state.index + 1) % OBSERVATION_CARDINALITY;
+        state.count = (state.count + 1).min(OBSERVATION_CARDINALITY);
+        state.last_timestamp = timestamp;
+        write_oracle_state(e, state);
+    }
+}
+
+// Get the price accumulator at index `k` for the `target` timestamp,
+// interpolating between the surrounding observations
+fn observe_at(
+    e: &Env,
+    current: &Observation,
+    state: &OracleState,
+    k: u32,
+    spot_price: i128,
+    target: u64,
+) -> i128 {
+    // the spot price has not changed since the last update
+    if target >= current.timestamp {
+        let elapsed = (target - current.timestamp) as i128;
+        return current
+            .price_cumulatives
+            .get_unchecked(k)
+            .wrapping_add(spot_price.wrapping_mul(elapsed));
+    }
+
+    // binary search the ring buffer for the last observation at or before `target`
+    let oldest = if state.count < OBSERVATION_CARDINALITY {
+        0
+    } else {
+        state.index
+    };
+    let observation_at =
+        |position: u32| read_observation(e, (oldest + position) % OBSERVATION_CARDINALITY);
+
+    let mut before = observation_at(0);
+    assert_with_error!(e, before.timestamp <= target, Error::ErrObservationTooOld);
+    let mut low: u32 = 0;
+    let mut high: u32 = state.count - 1;
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        let observation = observation_at(mid);
+        if observation.timestamp <= target {
+            low = mid;
+            before = observation;
+        } else {
+            high = mid - 1;
+        }
+    }
+    let after = if low + 1 < state.count {
+        observation_at(low + 1)
+    } else {
+        current.clone()
+    };
+
+    let before_cumulative = before.price_cumulatives.get_unchecked(k);
+    let after_cumulative = after.price_cumulatives.get_unchecked(k);
+    let elapsed = (target - before.timestamp) as i128;
+    let interval = (after.timestamp - before.timestamp) as i128;
+    let delta = after_cumulative.wrapping_sub(before_cumulative);
+    let interpolated = SorobanFixedPoint::fixed_mul_floor(&delta, e, &elapsed, &interval);
+    before_cumulative.wrapping_add(interpolated)
+}
+
+// Get the time weighted price accumulators of `token_out` in `token_in`
+// for each of the given number of seconds ago
+//
+// The accumulators wrap on overflow, so only their `wrapping_sub` differences are meaningful
+pub fn execute_observe(
+    e: Env,
+    token_in: Address,
+    token_out: Address,
+    seconds_ago: Vec<u64>,
+) -> Vec<i128> {
+    let in_record =
+        read_record(&e, &token_in).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let out_record =
+        read_record(&e, &token_out).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let current = read_price_cumulative(&e)
+        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNoObservations));
+    let n = read_tokens(&e).len();
+    let spot_price = calc_spot_price(&in_record, &out_record, 0);
+    let state = read_oracle_state(&e);
+    let timestamp = e.ledger().timestamp();
+
+    let mut price_cumulatives = Vec::new(&e);
+    for ago in seconds_ago.iter() {
+        assert_with_error!(&e, ago <= timestamp, Error::ErrObservationTooOld);
+        let target = timestamp - ago;
+        price_cumulatives.push_back(if in_record.index == out_record.index {
+            // a token is priced at STROOP in itself
+            (target as i128).wrapping_mul(STROOP)
+        } else {
+            let k = pair_index(n, in_record.index, out_record.index);
+            observe_at(&e, &current, &state, k, spot_price, target)
+        });
+    }
+    price_cumulatives
+}
+
+// Get the time weighted average price of `token_out` in `token_in`
+// over the last `window` seconds
+pub fn execute_consult_twap(e: Env, token_in: Address, token_out: Address, window: u64) -> i128 {
+    assert_with_error!(&e, window > 0, Error::ErrNegativeOrZero);
+
+    let seconds_ago = vec![&e, window, 0];
+    let price_cumulatives = execute_observe(e, token_in, token_out, seconds_ago);
+    price_cumulatives
+        .get_unchecked(1)
+        .wrapping_sub(price_cumulatives.get_unchecked(0))
+        / window as i128
+}
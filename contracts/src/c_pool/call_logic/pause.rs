@@ -0,0 +1,107 @@
+use soroban_sdk::{assert_with_error, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    c_consts::{
+        PAUSE_ALL, PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP, PAUSE_WITHDRAW, WITHDRAW_PAUSE_DELAY,
+        WITHDRAW_PAUSE_GRACE,
+    },
+    c_pool::{
+        error::{Error, ErrorExt},
+        event::{PauseEvent, WithdrawPauseEvent},
+        metadata::{
+            read_pause_flags, read_withdraw_pause_eta, remove_withdraw_pause_eta,
+            write_pause_flags, write_withdraw_pause_eta,
+        },
+    },
+};
+
+use super::pool::POOL;
+
+// Operations paused by `set_freeze_status`, everything but withdrawals
+pub(crate) const FREEZE_FLAGS: u32 = PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT;
+
+// Check that the operations covered by `flag` are not paused
+pub fn check_not_paused(e: &Env, flag: u32) {
+    let paused = read_pause_flags(e) & flag != 0;
+    if flag == PAUSE_WITHDRAW {
+        assert_with_error!(e, !paused, ErrorExt::ErrWithdrawPaused);
+    } else {
+        assert_with_error!(e, !paused, Error::ErrFreezeOnlyWithdrawals);
+    }
+}
+
+// Replace the paused operations with `flags`
+//
+// Withdrawals can only be paused once a proposed withdrawal pause has
+// passed its timelock and before it expires WITHDRAW_PAUSE_GRACE later,
+// unpausing takes effect immediately
+pub fn execute_set_pause_flags(e: Env, flags: u32, caller: Address) {
+    assert_with_error!(&e, flags & !PAUSE_ALL == 0, ErrorExt::ErrInvalidPauseFlags);
+
+    let old_flags = read_pause_flags(&e);
+    if flags & PAUSE_WITHDRAW != 0 && old_flags & PAUSE_WITHDRAW == 0 {
+        let eta = read_withdraw_pause_eta(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrWithdrawPauseTimelock));
+        assert_with_error!(
+            &e,
+            (eta..=eta + WITHDRAW_PAUSE_GRACE).contains(&e.ledger().timestamp()),
+            ErrorExt::ErrWithdrawPauseTimelock
+        );
+        remove_withdraw_pause_eta(&e);
+    }
+    write_pause_flags(&e, flags);
+
+    let event: PauseEvent = PauseEvent {
+        caller,
+        old_flags,
+        new_flags: flags,
+    };
+    e.events().publish((POOL, symbol_short!("pause")), event);
+}
+
+// Pause the operations in `flags`, on top of the ones already paused
+//
+// Used by the guardian, which can pause but not unpause operations,
+// and never withdrawals, even once the Pool Admin's proposal has matured
+pub fn execute_guardian_pause(e: Env, flags: u32, guardian: Address) {
+    assert_with_error!(&e, flags & PAUSE_WITHDRAW == 0, ErrorExt::ErrUnauthorized);
+    let old_flags = read_pause_flags(&e);
+    execute_set_pause_flags(e, old_flags | flags, guardian);
+}
+
+// Pause or unpause every operation except withdrawals
+pub fn execute_set_freeze_status(e: Env, val: bool, caller: Address) {
+    let old_flags = read_pause_flags(&e);
+    let new_flags = if val {
+        old_flags | FREEZE_FLAGS
+    } else {
+        old_flags & !FREEZE_FLAGS
+    };
+    write_pause_flags(&e, new_flags);
+
+    let event: PauseEvent = PauseEvent {
+        caller,
+        old_flags,
+        new_flags,
+    };
+    e.events().publish((POOL, symbol_short!("pause")), event);
+}
+
+// Start the timelock after which withdrawals can be paused
+pub fn execute_propose_withdraw_pause(e: Env, caller: Address) {
+    let eta = e.ledger().timestamp() + WITHDRAW_PAUSE_DELAY;
+    write_withdraw_pause_eta(&e, eta);
+
+    let event: WithdrawPauseEvent = WithdrawPauseEvent { caller, eta };
+    e.events().publish((POOL, symbol_short!("wdr_prop")), event);
+}
+
+// Cancel the proposed withdrawal pause
+pub fn execute_cancel_withdraw_pause(e: Env, caller: Address) {
+    let eta = read_withdraw_pause_eta(&e)
+        .unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrNoPendingPause));
+    remove_withdraw_pause_eta(&e);
+
+    let event: WithdrawPauseEvent = WithdrawPauseEvent { caller, eta };
+    e.events().publish((POOL, symbol_short!("wdr_cncl")), event);
+}
@@ -0,0 +1,58 @@
+use soroban_sdk::{panic_with_error, xdr::ToXdr, Address, BytesN, Env};
+use soroban_token_sdk::TokenUtils;
+
+use crate::c_pool::{
+    allowance::{read_nonce, read_permit_key, write_allowance, write_nonce, write_permit_key},
+    error::ErrorExt,
+    token_utility::check_nonnegative_amount,
+};
+
+// Set the ed25519 key whose signatures `owner` accepts for its permits
+pub fn execute_set_permit_key(e: Env, owner: Address, public_key: BytesN<32>) {
+    write_permit_key(&e, owner, &public_key);
+}
+
+// Set the allowance of `spender` over the LP tokens of `owner` with a signature
+// by the permit key of `owner` over the XDR of
+// (pool, owner, spender, amount, expiration_ledger, nonce)
+//
+// The pool address keeps a signature from being used on another pool, and the
+// nonce is the number of permits already used by `owner`, so every signature
+// can only be used once
+pub fn execute_permit(
+    e: Env,
+    owner: Address,
+    spender: Address,
+    amount: i128,
+    expiration_ledger: u32,
+    signature: BytesN<64>,
+) {
+    check_nonnegative_amount(amount);
+    let public_key = read_permit_key(&e, owner.clone())
+        .unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrNoPermitKey));
+
+    let nonce = read_nonce(&e, owner.clone());
+    let payload = (
+        e.current_contract_address(),
+        owner.clone(),
+        spender.clone(),
+        amount,
+        expiration_ledger,
+        nonce,
+    )
+        .to_xdr(&e);
+    e.crypto().ed25519_verify(&public_key, &payload, &signature);
+    write_nonce(&e, owner.clone(), nonce + 1);
+
+    write_allowance(
+        &e,
+        owner.clone(),
+        spender.clone(),
+        amount,
+        expiration_ledger,
+    );
+
+    TokenUtils::new(&e)
+        .events()
+        .approve(owner, spender, amount, expiration_ledger);
+}
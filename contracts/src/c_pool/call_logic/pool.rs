@@ -1,48 +1,101 @@
+use math::I256;
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::I256;
 use soroban_sdk::{
     assert_with_error, panic_with_error, symbol_short, token, unwrap::UnwrapOptimized, Address,
     Env, Symbol, Vec,
 };
 
-use crate::c_consts::STROOP;
+use crate::c_consts::{EVENT_VERSION, STROOP};
 use crate::{
-    c_consts::{MAX_IN_RATIO, MAX_OUT_RATIO},
+    c_consts::{
+        MAX_IN_RATIO, MAX_OUT_RATIO, PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP, PAUSE_WITHDRAW,
+    },
     c_math,
     c_pool::{
-        error::Error,
-        event::{DepositEvent, ExitEvent, JoinEvent, SwapEvent, WithdrawEvent},
+        error::{Error, ErrorExt},
+        event::{DepositEvent, ExitEvent, JoinEvent, SwapEvent, SyncEvent, WithdrawEvent},
         metadata::{
-            get_total_shares, read_freeze, read_record, read_swap_fee, read_tokens, write_record,
+            get_total_shares, read_controller, read_exit_fee, read_finalize, read_protocol_fees,
+            read_public_swap, read_record, read_records, read_tokens, write_record,
+        },
+        token_utility::{
+            burn_shares, mint_shares, pull_shares, pull_underlying, push_shares, push_underlying,
         },
-        token_utility::{burn_shares, mint_shares, pull_shares, pull_underlying, push_underlying},
     },
 };
-const POOL: Symbol = symbol_short!("POOL");
+
+use super::{
+    dynamic_fee::{read_effective_swap_fee, update_volatility, update_volatility_single_sided},
+    oracle::update_oracle,
+    pause::check_not_paused,
+    protocol_fee::{charge_protocol_fee, read_protocol_fee},
+};
+pub(crate) const POOL: Symbol = symbol_short!("POOL");
+
+// Check that the ledger timestamp is not past `deadline`
+pub fn check_deadline(e: &Env, deadline: u64) {
+    assert_with_error!(
+        e,
+        e.ledger().timestamp() <= deadline,
+        ErrorExt::ErrDeadlinePassed
+    );
+}
 
 // Absorbing tokens into the pool directly sent to the current contract
 pub fn execute_gulp(e: Env, t: Address) {
-    let mut records = read_record(&e);
-    let mut rec = records
-        .get(t.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
-
-    rec.balance = token::Client::new(&e, &t).balance(&e.current_contract_address());
-    records.set(t, rec);
-    write_record(&e, records);
+    update_oracle(&e);
+
+    let mut rec = read_record(&e, &t).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+
+    // accrued protocol fees are held by the pool but are not part of its balance
+    let protocol_fees = read_protocol_fees(&e).get(t.clone()).unwrap_or(0);
+    rec.balance = token::Client::new(&e, &t).balance(&e.current_contract_address()) - protocol_fees;
+    write_record(&e, &t, &rec);
+
+    publish_sync_event(&e);
 }
 
-pub fn execute_join_pool(e: Env, pool_amount_out: i128, max_amounts_in: Vec<i128>, user: Address) {
-    assert_with_error!(&e, !read_freeze(&e), Error::ErrFreezeOnlyWithdrawals);
+// Publish the full pool state, used after gulps and admin changes
+pub fn publish_sync_event(e: &Env) {
+    let tokens = read_tokens(e);
+    let records = read_records(e);
+    let mut balances = Vec::new(e);
+    let mut weights = Vec::new(e);
+    for t in tokens.iter() {
+        let rec = records.get_unchecked(t);
+        balances.push_back(rec.balance);
+        weights.push_back(rec.weight);
+    }
+
+    let event: SyncEvent = SyncEvent {
+        version: EVENT_VERSION,
+        tokens,
+        balances,
+        weights,
+        swap_fee: read_effective_swap_fee(e),
+        total_supply: get_total_shares(e),
+    };
+    e.events().publish((POOL, symbol_short!("sync")), event);
+}
+
+pub fn execute_join_pool(
+    e: Env,
+    pool_amount_out: i128,
+    max_amounts_in: Vec<i128>,
+    user: Address,
+    to: Address,
+) {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_JOIN);
     assert_with_error!(&e, pool_amount_out > 0, Error::ErrNegativeOrZero);
+    update_oracle(&e);
 
     let pool_total = get_total_shares(&e);
-    let zero = I256::from_i32(&e, 0);
-    let ratio = c_math::calc_join_ratio(&e, pool_total, pool_amount_out);
-    assert_with_error!(&e, ratio > zero, Error::ErrMathApprox);
+    let ratio = c_math::calc_join_ratio(pool_total, pool_amount_out);
+    assert_with_error!(&e, ratio > I256::ZERO, Error::ErrMathApprox);
 
     let tokens = read_tokens(&e);
-    let mut records = read_record(&e);
+    let records = read_records(&e);
     for i in 0..tokens.len() {
         let t = tokens.get_unchecked(i);
         let mut rec = records.get_unchecked(t.clone());
@@ -53,34 +106,52 @@ pub fn execute_join_pool(e: Env, pool_amount_out: i128, max_amounts_in: Vec<i128
         assert_with_error!(&e, max_amount_in > 0, Error::ErrNegative);
         assert_with_error!(&e, token_amount_in <= max_amount_in, Error::ErrLimitIn);
         rec.balance = rec.balance.checked_add(token_amount_in).unwrap_optimized();
-        records.set(t.clone(), rec);
+        let balance = rec.balance;
+        write_record(&e, &t, &rec);
         let event: JoinEvent = JoinEvent {
+            version: EVENT_VERSION,
             caller: user.clone(),
             token_in: t.clone(),
             token_amount_in,
+            pool_amount_out,
+            balance,
+            total_supply: pool_total + pool_amount_out,
         };
         e.events()
             .publish((POOL, symbol_short!("join_pool")), event);
         pull_underlying(&e, &t, &user, token_amount_in, max_amount_in);
     }
 
-    write_record(&e, records);
-    mint_shares(&e, &user, pool_amount_out);
+    mint_shares(&e, &to, pool_amount_out);
 }
 
 // Helps a user exit the pool
-pub fn execute_exit_pool(e: Env, pool_amount_in: i128, min_amounts_out: Vec<i128>, user: Address) {
+pub fn execute_exit_pool(
+    e: Env,
+    pool_amount_in: i128,
+    min_amounts_out: Vec<i128>,
+    user: Address,
+    to: Address,
+) {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_WITHDRAW);
     assert_with_error!(&e, pool_amount_in > 0, Error::ErrNegativeOrZero);
+    update_oracle(&e);
+
+    // the exit fee rounds up to benefit the pool
+    let exit_fee_amount = pool_amount_in
+        .fixed_mul_ceil(read_exit_fee(&e), STROOP)
+        .unwrap_optimized();
+    let pool_amount_in_after_exit_fee = pool_amount_in - exit_fee_amount;
 
     let pool_total = get_total_shares(&e);
-    let zero = I256::from_i32(&e, 0);
-    let ratio = c_math::calc_exit_ratio(&e, pool_total, pool_amount_in);
-    assert_with_error!(&e, ratio > zero, Error::ErrMathApprox);
-    pull_shares(&e, &user, pool_amount_in);
-    burn_shares(&e, pool_amount_in);
+    let ratio = c_math::calc_exit_ratio(pool_total, pool_amount_in_after_exit_fee);
+    assert_with_error!(&e, ratio > I256::ZERO, Error::ErrMathApprox);
+    burn_exiting_shares(&e, &user, pool_amount_in, exit_fee_amount);
+    let total_supply = get_total_shares(&e);
 
     let tokens = read_tokens(&e);
-    let mut records = read_record(&e);
+    let records = read_records(&e);
     for i in 0..tokens.len() {
         let t = tokens.get_unchecked(i);
         let mut rec = records.get_unchecked(t.clone());
@@ -95,20 +166,25 @@ pub fn execute_exit_pool(e: Env, pool_amount_in: i128, min_amounts_out: Vec<i128
             Error::ErrInsufficientBalance
         );
         rec.balance = rec.balance - token_amount_out;
-        records.set(t.clone(), rec);
+        let balance = rec.balance;
+        write_record(&e, &t, &rec);
         let event: ExitEvent = ExitEvent {
+            version: EVENT_VERSION,
             caller: user.clone(),
             token_out: t.clone(),
             token_amount_out,
+            pool_amount_in,
+            exit_fee_amount,
+            balance,
+            total_supply,
         };
         e.events()
             .publish((POOL, symbol_short!("exit_pool")), event);
-        push_underlying(&e, &t, &user, token_amount_out)
+        push_underlying(&e, &t, &to, token_amount_out)
     }
-
-    write_record(&e, records);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_swap_exact_amount_in(
     e: Env,
     token_in: Address,
@@ -117,20 +193,20 @@ pub fn execute_swap_exact_amount_in(
     min_amount_out: i128,
     max_price: i128,
     user: Address,
+    to: Address,
 ) -> (i128, i128) {
-    assert_with_error!(&e, !read_freeze(&e), Error::ErrFreezeOnlyWithdrawals);
+    assert_with_error!(&e, read_public_swap(&e), Error::ErrSwapNotPublic);
+    check_not_paused(&e, PAUSE_SWAP);
     assert_with_error!(&e, token_amount_in > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, min_amount_out >= 0, Error::ErrNegative);
     assert_with_error!(&e, max_price >= 0, Error::ErrNegative);
+    update_oracle(&e);
 
-    let swap_fee = read_swap_fee(&e);
-    let mut record_map = read_record(&e);
-    let mut in_record = record_map
-        .get(token_in.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
-    let mut out_record = record_map
-        .get(token_out.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let swap_fee = read_effective_swap_fee(&e);
+    let mut in_record =
+        read_record(&e, &token_in).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let mut out_record =
+        read_record(&e, &token_out).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
     assert_with_error!(
         &e,
         token_amount_in
@@ -153,9 +229,10 @@ pub fn execute_swap_exact_amount_in(
     );
     assert_with_error!(&e, token_amount_out >= min_amount_out, Error::ErrLimitOut);
 
+    let protocol_fee_amount = charge_protocol_fee(&e, &token_in, token_amount_in, swap_fee);
     in_record.balance = in_record
         .balance
-        .checked_add(token_amount_in)
+        .checked_add(token_amount_in - protocol_fee_amount)
         .unwrap_optimized();
     assert_with_error!(
         &e,
@@ -180,13 +257,22 @@ pub fn execute_swap_exact_amount_in(
                 .unwrap_optimized(),
         Error::ErrMathApprox
     );
+    update_volatility(&e, spot_price_before, spot_price_after);
 
     let event: SwapEvent = SwapEvent {
+        version: EVENT_VERSION,
         caller: user.clone(),
         token_in: token_in.clone(),
         token_out: token_out.clone(),
         token_amount_in,
         token_amount_out,
+        fee_amount: token_amount_in
+            .fixed_mul_floor(swap_fee, STROOP)
+            .unwrap_optimized(),
+        spot_price_after,
+        balance_in: in_record.balance,
+        balance_out: out_record.balance,
+        total_supply: get_total_shares(&e),
     };
     e.events().publish((POOL, symbol_short!("swap")), event);
 
@@ -197,16 +283,15 @@ pub fn execute_swap_exact_amount_in(
         token_amount_in,
         token_amount_in.clone(),
     );
-    push_underlying(&e, &token_out, &user, token_amount_out);
-
-    record_map.set(token_in, in_record);
-    record_map.set(token_out, out_record);
+    push_underlying(&e, &token_out, &to, token_amount_out);
 
-    write_record(&e, record_map);
+    write_record(&e, &token_in, &in_record);
+    write_record(&e, &token_out, &out_record);
 
     (token_amount_out, spot_price_after)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_swap_exact_amount_out(
     e: Env,
     token_in: Address,
@@ -215,20 +300,20 @@ pub fn execute_swap_exact_amount_out(
     token_amount_out: i128,
     max_price: i128,
     user: Address,
+    to: Address,
 ) -> (i128, i128) {
-    assert_with_error!(&e, !read_freeze(&e), Error::ErrFreezeOnlyWithdrawals);
+    assert_with_error!(&e, read_public_swap(&e), Error::ErrSwapNotPublic);
+    check_not_paused(&e, PAUSE_SWAP);
     assert_with_error!(&e, token_amount_out > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, max_amount_in > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, max_price >= 0, Error::ErrNegative);
+    update_oracle(&e);
 
-    let swap_fee = read_swap_fee(&e);
-    let record_map = read_record(&e);
-    let mut in_record = record_map
-        .get(token_in.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
-    let mut out_record = record_map
-        .get(token_out.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let swap_fee = read_effective_swap_fee(&e);
+    let mut in_record =
+        read_record(&e, &token_in).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let mut out_record =
+        read_record(&e, &token_out).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
     assert_with_error!(
         &e,
         token_amount_out
@@ -252,9 +337,10 @@ pub fn execute_swap_exact_amount_out(
     assert_with_error!(&e, token_amount_in > 0, Error::ErrMathApprox);
     assert_with_error!(&e, token_amount_in <= max_amount_in, Error::ErrLimitIn);
 
+    let protocol_fee_amount = charge_protocol_fee(&e, &token_in, token_amount_in, swap_fee);
     in_record.balance = in_record
         .balance
-        .checked_add(token_amount_in)
+        .checked_add(token_amount_in - protocol_fee_amount)
         .unwrap_optimized();
     assert_with_error!(
         &e,
@@ -279,23 +365,29 @@ pub fn execute_swap_exact_amount_out(
                 .unwrap_optimized(),
         Error::ErrMathApprox
     );
+    update_volatility(&e, spot_price_before, spot_price_after);
 
     let event: SwapEvent = SwapEvent {
+        version: EVENT_VERSION,
         caller: user.clone(),
         token_in: token_in.clone(),
         token_out: token_out.clone(),
         token_amount_in,
         token_amount_out,
+        fee_amount: token_amount_in
+            .fixed_mul_floor(swap_fee, STROOP)
+            .unwrap_optimized(),
+        spot_price_after,
+        balance_in: in_record.balance,
+        balance_out: out_record.balance,
+        total_supply: get_total_shares(&e),
     };
     e.events().publish((POOL, symbol_short!("swap")), event);
     pull_underlying(&e, &token_in, &user, token_amount_in, max_amount_in);
-    push_underlying(&e, &token_out, &user, token_amount_out);
-
-    let mut record_map = read_record(&e);
-    record_map.set(token_in, in_record);
-    record_map.set(token_out, out_record);
+    push_underlying(&e, &token_out, &to, token_amount_out);
 
-    write_record(&e, record_map);
+    write_record(&e, &token_in, &in_record);
+    write_record(&e, &token_out, &out_record);
 
     (token_amount_in, spot_price_after)
 }
@@ -306,16 +398,17 @@ pub fn execute_dep_tokn_amt_in_get_lp_tokns_out(
     token_amount_in: i128,
     min_pool_amount_out: i128,
     user: Address,
+    to: Address,
 ) -> i128 {
-    assert_with_error!(&e, !read_freeze(&e), Error::ErrFreezeOnlyWithdrawals);
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_DEPOSIT);
     assert_with_error!(&e, token_amount_in > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, min_pool_amount_out >= 0, Error::ErrNegative);
+    update_oracle(&e);
 
-    let swap_fee = read_swap_fee(&e);
-    let mut record_map = read_record(&e);
-    let mut in_record = record_map
-        .get(token_in.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let swap_fee = read_effective_swap_fee(&e);
+    let mut in_record =
+        read_record(&e, &token_in).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
     assert_with_error!(
         &e,
         token_amount_in
@@ -340,22 +433,29 @@ pub fn execute_dep_tokn_amt_in_get_lp_tokns_out(
         Error::ErrLimitOut
     );
 
+    let balance_before = in_record.balance;
     in_record.balance = in_record
         .balance
         .checked_add(token_amount_in)
         .unwrap_optimized();
-
-    record_map.set(token_in.clone(), in_record);
-    write_record(&e, record_map);
+    update_volatility_single_sided(&e, balance_before, in_record.balance);
 
     let event: DepositEvent = DepositEvent {
+        version: EVENT_VERSION,
         caller: user.clone(),
         token_in: token_in.clone(),
         token_amount_in,
+        pool_amount_out,
+        fee_amount: calc_deposit_fee(token_amount_in, in_record.weight, swap_fee),
+        balance: in_record.balance,
+        total_supply: total_shares + pool_amount_out,
     };
+
+    write_record(&e, &token_in, &in_record);
+
     e.events().publish((POOL, symbol_short!("deposit")), event);
     pull_underlying(&e, &token_in, &user, token_amount_in, token_amount_in);
-    mint_shares(&e, &user, pool_amount_out);
+    mint_shares(&e, &to, pool_amount_out);
 
     pool_amount_out
 }
@@ -366,17 +466,18 @@ pub fn execute_dep_lp_tokn_amt_out_get_tokn_in(
     pool_amount_out: i128,
     max_amount_in: i128,
     user: Address,
+    to: Address,
 ) -> i128 {
-    assert_with_error!(&e, !read_freeze(&e), Error::ErrFreezeOnlyWithdrawals);
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_DEPOSIT);
     assert_with_error!(&e, pool_amount_out > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, max_amount_in > 0, Error::ErrNegativeOrZero);
+    update_oracle(&e);
 
-    let mut record_map = read_record(&e);
-    let mut in_record = record_map
-        .get(token_in.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let mut in_record =
+        read_record(&e, &token_in).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
 
-    let swap_fee = read_swap_fee(&e);
+    let swap_fee = read_effective_swap_fee(&e);
     let total_shares = get_total_shares(&e);
     let token_amount_in = c_math::calc_token_deposits_in_given_lp_token_amount(
         &e,
@@ -396,22 +497,29 @@ pub fn execute_dep_lp_tokn_amt_out_get_tokn_in(
                 .unwrap_optimized(),
         Error::ErrMaxInRatio
     );
+    let balance_before = in_record.balance;
     in_record.balance = in_record
         .balance
         .checked_add(token_amount_in)
         .unwrap_optimized();
-
-    record_map.set(token_in.clone(), in_record);
-    write_record(&e, record_map);
+    update_volatility_single_sided(&e, balance_before, in_record.balance);
 
     let event: DepositEvent = DepositEvent {
+        version: EVENT_VERSION,
         caller: user.clone(),
         token_in: token_in.clone(),
         token_amount_in,
+        pool_amount_out,
+        fee_amount: calc_deposit_fee(token_amount_in, in_record.weight, swap_fee),
+        balance: in_record.balance,
+        total_supply: total_shares + pool_amount_out,
     };
+
+    write_record(&e, &token_in, &in_record);
+
     e.events().publish((POOL, symbol_short!("deposit")), event);
     pull_underlying(&e, &token_in, &user, token_amount_in, max_amount_in);
-    mint_shares(&e, &user, pool_amount_out);
+    mint_shares(&e, &to, pool_amount_out);
 
     token_amount_in
 }
@@ -422,16 +530,19 @@ pub fn execute_wdr_tokn_amt_in_get_lp_tokns_out(
     pool_amount_in: i128,
     min_amount_out: i128,
     user: Address,
+    to: Address,
 ) -> i128 {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_WITHDRAW);
     assert_with_error!(&e, pool_amount_in > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, min_amount_out >= 0, Error::ErrNegative);
+    update_oracle(&e);
 
-    let mut record_map = read_record(&e);
-    let mut out_record = record_map
-        .get(token_out.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let mut out_record =
+        read_record(&e, &token_out).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
 
-    let swap_fee = read_swap_fee(&e);
+    let swap_fee = read_effective_swap_fee(&e);
+    let exit_fee = read_exit_fee(&e);
     let total_shares = get_total_shares(&e);
     let token_amount_out = c_math::calc_token_withdrawal_amount_given_lp_token_amount(
         &e,
@@ -439,6 +550,7 @@ pub fn execute_wdr_tokn_amt_in_get_lp_tokns_out(
         total_shares,
         pool_amount_in,
         swap_fee,
+        exit_fee,
     );
 
     assert_with_error!(&e, token_amount_out >= min_amount_out, Error::ErrLimitOut);
@@ -456,22 +568,32 @@ pub fn execute_wdr_tokn_amt_in_get_lp_tokns_out(
         token_amount_out <= out_record.balance,
         Error::ErrInsufficientBalance
     );
+    let balance_before = out_record.balance;
     out_record.balance = out_record.balance - token_amount_out;
+    update_volatility_single_sided(&e, balance_before, out_record.balance);
+
+    // the exit fee rounds down, so at least the LP tokens the withdrawal
+    // was priced with are burned
+    let exit_fee_amount = pool_amount_in
+        .fixed_mul_floor(exit_fee, STROOP)
+        .unwrap_optimized();
 
     let event: WithdrawEvent = WithdrawEvent {
+        version: EVENT_VERSION,
         caller: user.clone(),
         token_out: token_out.clone(),
         token_amount_out,
         pool_amount_in,
+        fee_amount: calc_withdraw_fee(token_amount_out, out_record.weight, swap_fee),
+        exit_fee_amount,
+        balance: out_record.balance,
+        total_supply: total_shares - pool_amount_in + exit_fee_amount,
     };
     e.events().publish((POOL, symbol_short!("withdraw")), event);
+    burn_exiting_shares(&e, &user, pool_amount_in, exit_fee_amount);
+    push_underlying(&e, &token_out, &to, token_amount_out);
 
-    pull_shares(&e, &user, pool_amount_in);
-    burn_shares(&e, pool_amount_in);
-    push_underlying(&e, &token_out, &user, token_amount_out);
-
-    record_map.set(token_out, out_record);
-    write_record(&e, record_map);
+    write_record(&e, &token_out, &out_record);
 
     token_amount_out
 }
@@ -482,14 +604,16 @@ pub fn execute_wdr_tokn_amt_out_get_lp_tokns_in(
     token_amount_out: i128,
     max_pool_amount_in: i128,
     user: Address,
+    to: Address,
 ) -> i128 {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_WITHDRAW);
     assert_with_error!(&e, token_amount_out > 0, Error::ErrNegativeOrZero);
     assert_with_error!(&e, max_pool_amount_in > 0, Error::ErrNegativeOrZero);
+    update_oracle(&e);
 
-    let mut record_map = read_record(&e);
-    let mut out_record = record_map
-        .get(token_out.clone())
-        .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
+    let mut out_record =
+        read_record(&e, &token_out).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNotBound));
     assert_with_error!(
         &e,
         token_amount_out
@@ -500,7 +624,8 @@ pub fn execute_wdr_tokn_amt_out_get_lp_tokns_in(
         Error::ErrMaxOutRatio
     );
 
-    let swap_fee = read_swap_fee(&e);
+    let swap_fee = read_effective_swap_fee(&e);
+    let exit_fee = read_exit_fee(&e);
     let total_shares = get_total_shares(&e);
     let pool_amount_in = c_math::calc_lp_token_amount_given_token_withdrawal_amount(
         &e,
@@ -508,6 +633,7 @@ pub fn execute_wdr_tokn_amt_out_get_lp_tokns_in(
         total_shares,
         token_amount_out,
         swap_fee,
+        exit_fee,
     );
 
     assert_with_error!(&e, pool_amount_in != 0, Error::ErrMathApprox);
@@ -517,21 +643,66 @@ pub fn execute_wdr_tokn_amt_out_get_lp_tokns_in(
         token_amount_out <= out_record.balance,
         Error::ErrInsufficientBalance
     );
+    let balance_before = out_record.balance;
     out_record.balance = out_record.balance - token_amount_out;
+    update_volatility_single_sided(&e, balance_before, out_record.balance);
+    // the exit fee rounds down, so at least the LP tokens the withdrawal
+    // was priced with are burned
+    let exit_fee_amount = pool_amount_in
+        .fixed_mul_floor(exit_fee, STROOP)
+        .unwrap_optimized();
+
     let event: WithdrawEvent = WithdrawEvent {
+        version: EVENT_VERSION,
         caller: user.clone(),
         token_out: token_out.clone(),
         token_amount_out,
         pool_amount_in,
+        fee_amount: calc_withdraw_fee(token_amount_out, out_record.weight, swap_fee),
+        exit_fee_amount,
+        balance: out_record.balance,
+        total_supply: total_shares - pool_amount_in + exit_fee_amount,
     };
     e.events().publish((POOL, symbol_short!("withdraw")), event);
+    burn_exiting_shares(&e, &user, pool_amount_in, exit_fee_amount);
+    push_underlying(&e, &token_out, &to, token_amount_out);
 
-    pull_shares(&e, &user, pool_amount_in);
-    burn_shares(&e, pool_amount_in);
-    push_underlying(&e, &token_out, &user, token_amount_out);
-
-    record_map.set(token_out, out_record);
-    write_record(&e, record_map);
+    write_record(&e, &token_out, &out_record);
 
     pool_amount_in
 }
+
+// Pull the LP tokens from the exiting user and burn them, except for the exit fee
+// which is sent to the protocol fee recipient, or to the controller if there is none
+fn burn_exiting_shares(e: &Env, user: &Address, pool_amount_in: i128, exit_fee_amount: i128) {
+    pull_shares(e, user, pool_amount_in);
+    burn_shares(e, pool_amount_in - exit_fee_amount);
+    if exit_fee_amount > 0 {
+        let fee_recipient = match read_protocol_fee(e) {
+            Some(config) => config.fee_recipient,
+            None => read_controller(e),
+        };
+        push_shares(e, &fee_recipient, exit_fee_amount);
+    }
+}
+
+// Swap fee paid on the part of a single-sided deposit of `token_amount_in`
+// that is traded for the other tokens of the pool
+fn calc_deposit_fee(token_amount_in: i128, weight: i128, swap_fee: i128) -> i128 {
+    token_amount_in
+        .fixed_mul_floor(STROOP - weight, STROOP)
+        .unwrap_optimized()
+        .fixed_mul_floor(swap_fee, STROOP)
+        .unwrap_optimized()
+}
+
+// Swap fee paid on the part of a single-sided withdrawal of `token_amount_out`
+// that is traded from the other tokens of the pool
+fn calc_withdraw_fee(token_amount_out: i128, weight: i128, swap_fee: i128) -> i128 {
+    let fee_ratio = (STROOP - weight)
+        .fixed_mul_floor(swap_fee, STROOP)
+        .unwrap_optimized();
+    token_amount_out
+        .fixed_mul_floor(fee_ratio, STROOP - fee_ratio)
+        .unwrap_optimized()
+}
@@ -0,0 +1,121 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    assert_with_error, panic_with_error, symbol_short, unwrap::UnwrapOptimized, Address, BytesN,
+    Env, Map,
+};
+
+use crate::{
+    c_consts::{MAX_PROTOCOL_FEE, STROOP},
+    c_pool::{
+        error::{Error, ErrorExt},
+        event::{CollectEvent, ProtocolFeeEvent},
+        factory_interface::FactoryClient,
+        metadata::{
+            read_factory, read_protocol_fee_settings, read_protocol_fees, write_factory,
+            write_protocol_fee_settings, write_protocol_fees,
+        },
+        storage_types::ProtocolFee,
+        token_utility::push_underlying,
+    },
+};
+
+use super::pool::POOL;
+
+// Set the Factory the protocol fee settings are synced from, can only be set once
+//
+// Only the Factory that deployed the pool with `salt` is accepted, and it passes
+// its current settings since the pool cannot call back into it during the deployment
+pub fn execute_set_factory(
+    e: Env,
+    factory: Address,
+    salt: BytesN<32>,
+    protocol_fee: Option<ProtocolFee>,
+) {
+    assert_with_error!(&e, read_factory(&e).is_none(), Error::AlreadyInitialized);
+    let deployed = e
+        .deployer()
+        .with_address(factory.clone(), salt)
+        .deployed_address();
+    assert_with_error!(
+        &e,
+        deployed == e.current_contract_address(),
+        ErrorExt::ErrUnauthorized
+    );
+    write_factory(&e, factory);
+    write_protocol_fee_settings(&e, protocol_fee);
+}
+
+// Refresh the protocol fee settings cached from the Factory
+pub fn execute_sync_protocol_fee(e: &Env) {
+    let factory = read_factory(e).unwrap_or_else(|| panic_with_error!(e, ErrorExt::ErrNoFactory));
+    write_protocol_fee_settings(e, FactoryClient::new(e, &factory).get_protocol_fee());
+}
+
+// Read the protocol fee settings cached from the Factory, if the pool has one
+//
+// Swaps never call the Factory, so they keep working if it fails
+pub fn read_protocol_fee(e: &Env) -> Option<ProtocolFee> {
+    read_protocol_fee_settings(e)
+}
+
+// Calculate the protocol's share of the swap fee paid on `token_amount_in`
+pub fn calc_protocol_fee(e: &Env, token_amount_in: i128, swap_fee: i128) -> i128 {
+    let protocol_fee = match read_protocol_fee(e) {
+        Some(config) => config.protocol_fee.clamp(0, MAX_PROTOCOL_FEE),
+        None => return 0,
+    };
+    token_amount_in
+        .fixed_mul_floor(swap_fee, STROOP)
+        .unwrap_optimized()
+        .fixed_mul_floor(protocol_fee, STROOP)
+        .unwrap_optimized()
+}
+
+// Set aside the protocol's share of the swap fee paid on `token_amount_in`
+//
+// Returns the amount of `token` that is not added to the pool's balance
+pub fn charge_protocol_fee(
+    e: &Env,
+    token: &Address,
+    token_amount_in: i128,
+    swap_fee: i128,
+) -> i128 {
+    let amount = calc_protocol_fee(e, token_amount_in, swap_fee);
+    if amount > 0 {
+        let mut fees = read_protocol_fees(e);
+        let accrued = fees.get(token.clone()).unwrap_or(0);
+        fees.set(token.clone(), accrued + amount);
+        write_protocol_fees(e, fees);
+
+        let event: ProtocolFeeEvent = ProtocolFeeEvent {
+            token: token.clone(),
+            amount,
+        };
+        e.events()
+            .publish((POOL, symbol_short!("proto_fee")), event);
+    }
+    amount
+}
+
+// Send all accrued protocol fees to the fee recipient currently set on the Factory
+pub fn execute_collect_protocol_fees(e: Env) {
+    if read_factory(&e).is_some() {
+        execute_sync_protocol_fee(&e);
+    }
+    let config =
+        read_protocol_fee(&e).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNoFeeRecipient));
+    let fees = read_protocol_fees(&e);
+    write_protocol_fees(&e, Map::new(&e));
+
+    for (token, amount) in fees.iter() {
+        if amount > 0 {
+            push_underlying(&e, &token, &config.fee_recipient, amount);
+            let event: CollectEvent = CollectEvent {
+                fee_recipient: config.fee_recipient.clone(),
+                token,
+                amount,
+            };
+            e.events().publish((POOL, symbol_short!("collect")), event);
+        }
+    }
+}
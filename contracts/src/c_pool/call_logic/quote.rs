@@ -0,0 +1,292 @@
+use math::I256;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    assert_with_error, panic_with_error, unwrap::UnwrapOptimized, Address, Env, Vec,
+};
+
+use crate::{
+    c_consts::{
+        MAX_IN_RATIO, MAX_OUT_RATIO, PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP, PAUSE_WITHDRAW, STROOP,
+    },
+    c_math,
+    c_pool::{
+        error::Error,
+        metadata::{
+            get_total_shares, read_exit_fee, read_finalize, read_public_swap, read_record,
+            read_records, read_tokens,
+        },
+        storage_types::Record,
+    },
+};
+
+use super::{
+    dynamic_fee::read_effective_swap_fee, pause::check_not_paused, protocol_fee::calc_protocol_fee,
+};
+
+// Read the record of a bound token
+fn read_bound_record(e: &Env, token: Address) -> Record {
+    read_record(e, &token).unwrap_or_else(|| panic_with_error!(e, Error::ErrNotBound))
+}
+
+// Quote the amount of `token_out` received and the spot price after
+// swapping `token_amount_in` of `token_in`
+pub fn execute_quote_swap_exact_amount_in(
+    e: Env,
+    token_in: Address,
+    token_amount_in: i128,
+    token_out: Address,
+) -> (i128, i128) {
+    assert_with_error!(&e, read_public_swap(&e), Error::ErrSwapNotPublic);
+    check_not_paused(&e, PAUSE_SWAP);
+    assert_with_error!(&e, token_amount_in > 0, Error::ErrNegativeOrZero);
+
+    let swap_fee = read_effective_swap_fee(&e);
+    let mut in_record = read_bound_record(&e, token_in);
+    let mut out_record = read_bound_record(&e, token_out);
+    assert_with_error!(
+        &e,
+        token_amount_in
+            <= in_record
+                .balance
+                .fixed_mul_floor(MAX_IN_RATIO, STROOP)
+                .unwrap_optimized(),
+        Error::ErrMaxInRatio
+    );
+
+    let spot_price_before = c_math::calc_spot_price(&in_record, &out_record, swap_fee);
+    let token_amount_out = c_math::calc_token_out_given_token_in(
+        &e,
+        &in_record,
+        &out_record,
+        token_amount_in,
+        swap_fee,
+    );
+    assert_with_error!(
+        &e,
+        out_record.balance >= token_amount_out,
+        Error::ErrInsufficientBalance
+    );
+
+    let protocol_fee_amount = calc_protocol_fee(&e, token_amount_in, swap_fee);
+    in_record.balance += token_amount_in - protocol_fee_amount;
+    out_record.balance -= token_amount_out;
+    let spot_price_after = c_math::calc_spot_price(&in_record, &out_record, swap_fee);
+    assert_with_error!(
+        &e,
+        spot_price_after >= spot_price_before,
+        Error::ErrMathApprox
+    );
+
+    (token_amount_out, spot_price_after)
+}
+
+// Quote the amount of `token_in` required and the spot price after
+// swapping for `token_amount_out` of `token_out`
+pub fn execute_quote_swap_exact_amount_out(
+    e: Env,
+    token_in: Address,
+    token_out: Address,
+    token_amount_out: i128,
+) -> (i128, i128) {
+    assert_with_error!(&e, read_public_swap(&e), Error::ErrSwapNotPublic);
+    check_not_paused(&e, PAUSE_SWAP);
+    assert_with_error!(&e, token_amount_out > 0, Error::ErrNegativeOrZero);
+
+    let swap_fee = read_effective_swap_fee(&e);
+    let mut in_record = read_bound_record(&e, token_in);
+    let mut out_record = read_bound_record(&e, token_out);
+    assert_with_error!(
+        &e,
+        token_amount_out
+            <= out_record
+                .balance
+                .fixed_mul_floor(MAX_OUT_RATIO, STROOP)
+                .unwrap_optimized(),
+        Error::ErrMaxOutRatio
+    );
+
+    let spot_price_before = c_math::calc_spot_price(&in_record, &out_record, swap_fee);
+    let token_amount_in = c_math::calc_token_in_given_token_out(
+        &e,
+        &in_record,
+        &out_record,
+        token_amount_out,
+        swap_fee,
+    );
+    assert_with_error!(&e, token_amount_in > 0, Error::ErrMathApprox);
+
+    let protocol_fee_amount = calc_protocol_fee(&e, token_amount_in, swap_fee);
+    in_record.balance += token_amount_in - protocol_fee_amount;
+    out_record.balance -= token_amount_out;
+    let spot_price_after = c_math::calc_spot_price(&in_record, &out_record, swap_fee);
+    assert_with_error!(
+        &e,
+        spot_price_after >= spot_price_before,
+        Error::ErrMathApprox
+    );
+
+    (token_amount_in, spot_price_after)
+}
+
+// Quote the amount of each token, ordered like `get_tokens`,
+// required to join the pool for `pool_amount_out` LP tokens
+pub fn execute_quote_join_pool(e: Env, pool_amount_out: i128) -> Vec<i128> {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_JOIN);
+    assert_with_error!(&e, pool_amount_out > 0, Error::ErrNegativeOrZero);
+
+    let pool_total = get_total_shares(&e);
+    let ratio = c_math::calc_join_ratio(pool_total, pool_amount_out);
+    assert_with_error!(&e, ratio > I256::ZERO, Error::ErrMathApprox);
+
+    let tokens = read_tokens(&e);
+    let records = read_records(&e);
+    let mut amounts_in = Vec::new(&e);
+    for t in tokens.iter() {
+        let rec = records.get_unchecked(t);
+        let token_amount_in = c_math::calc_join_deposit_amount(&e, &rec, &ratio);
+        assert_with_error!(&e, token_amount_in > 0, Error::ErrMathApprox);
+        amounts_in.push_back(token_amount_in);
+    }
+    amounts_in
+}
+
+// Quote the amount of each token, ordered like `get_tokens`,
+// received for exiting the pool with `pool_amount_in` LP tokens
+pub fn execute_quote_exit_pool(e: Env, pool_amount_in: i128) -> Vec<i128> {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_WITHDRAW);
+    assert_with_error!(&e, pool_amount_in > 0, Error::ErrNegativeOrZero);
+
+    let exit_fee_amount = pool_amount_in
+        .fixed_mul_ceil(read_exit_fee(&e), STROOP)
+        .unwrap_optimized();
+    let pool_total = get_total_shares(&e);
+    let ratio = c_math::calc_exit_ratio(pool_total, pool_amount_in - exit_fee_amount);
+    assert_with_error!(&e, ratio > I256::ZERO, Error::ErrMathApprox);
+
+    let tokens = read_tokens(&e);
+    let records = read_records(&e);
+    let mut amounts_out = Vec::new(&e);
+    for t in tokens.iter() {
+        let rec = records.get_unchecked(t);
+        let token_amount_out = c_math::calc_exit_withdrawal_amount(&e, &rec, &ratio);
+        assert_with_error!(&e, token_amount_out > 0, Error::ErrMathApprox);
+        assert_with_error!(
+            &e,
+            token_amount_out <= rec.balance,
+            Error::ErrInsufficientBalance
+        );
+        amounts_out.push_back(token_amount_out);
+    }
+    amounts_out
+}
+
+// Quote the LP tokens received for depositing `token_amount_in` of `token_in`
+pub fn execute_quote_dep_tokn_amt_in(e: Env, token_in: Address, token_amount_in: i128) -> i128 {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_DEPOSIT);
+    assert_with_error!(&e, token_amount_in > 0, Error::ErrNegativeOrZero);
+
+    let in_record = read_bound_record(&e, token_in);
+    assert_with_error!(
+        &e,
+        token_amount_in
+            <= in_record
+                .balance
+                .fixed_mul_floor(MAX_IN_RATIO, STROOP)
+                .unwrap_optimized(),
+        Error::ErrMaxInRatio
+    );
+
+    c_math::calc_lp_token_amount_given_token_deposits_in(
+        &e,
+        &in_record,
+        get_total_shares(&e),
+        token_amount_in,
+        read_effective_swap_fee(&e),
+    )
+}
+
+// Quote the amount of `token_in` required to receive `pool_amount_out` LP tokens
+pub fn execute_quote_dep_lp_tokn_amt_out(e: Env, token_in: Address, pool_amount_out: i128) -> i128 {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_DEPOSIT);
+    assert_with_error!(&e, pool_amount_out > 0, Error::ErrNegativeOrZero);
+
+    let in_record = read_bound_record(&e, token_in);
+    let token_amount_in = c_math::calc_token_deposits_in_given_lp_token_amount(
+        &e,
+        &in_record,
+        get_total_shares(&e),
+        pool_amount_out,
+        read_effective_swap_fee(&e),
+    );
+    assert_with_error!(&e, token_amount_in != 0, Error::ErrMathApprox);
+    assert_with_error!(
+        &e,
+        token_amount_in
+            <= in_record
+                .balance
+                .fixed_mul_floor(MAX_IN_RATIO, STROOP)
+                .unwrap_optimized(),
+        Error::ErrMaxInRatio
+    );
+    token_amount_in
+}
+
+// Quote the amount of `token_out` received for burning `pool_amount_in` LP tokens
+pub fn execute_quote_wdr_tokn_amt_in(e: Env, token_out: Address, pool_amount_in: i128) -> i128 {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_WITHDRAW);
+    assert_with_error!(&e, pool_amount_in > 0, Error::ErrNegativeOrZero);
+
+    let out_record = read_bound_record(&e, token_out);
+    let token_amount_out = c_math::calc_token_withdrawal_amount_given_lp_token_amount(
+        &e,
+        &out_record,
+        get_total_shares(&e),
+        pool_amount_in,
+        read_effective_swap_fee(&e),
+        read_exit_fee(&e),
+    );
+    assert_with_error!(
+        &e,
+        token_amount_out
+            <= out_record
+                .balance
+                .fixed_mul_floor(MAX_OUT_RATIO, STROOP)
+                .unwrap_optimized(),
+        Error::ErrMaxOutRatio
+    );
+    token_amount_out
+}
+
+// Quote the LP tokens burned to withdraw `token_amount_out` of `token_out`
+pub fn execute_quote_wdr_tokn_amt_out(e: Env, token_out: Address, token_amount_out: i128) -> i128 {
+    assert_with_error!(&e, read_finalize(&e), Error::ErrNotFinalized);
+    check_not_paused(&e, PAUSE_WITHDRAW);
+    assert_with_error!(&e, token_amount_out > 0, Error::ErrNegativeOrZero);
+
+    let out_record = read_bound_record(&e, token_out);
+    assert_with_error!(
+        &e,
+        token_amount_out
+            <= out_record
+                .balance
+                .fixed_mul_floor(MAX_OUT_RATIO, STROOP)
+                .unwrap_optimized(),
+        Error::ErrMaxOutRatio
+    );
+
+    let pool_amount_in = c_math::calc_lp_token_amount_given_token_withdrawal_amount(
+        &e,
+        &out_record,
+        get_total_shares(&e),
+        token_amount_out,
+        read_effective_swap_fee(&e),
+        read_exit_fee(&e),
+    );
+    assert_with_error!(&e, pool_amount_in != 0, Error::ErrMathApprox);
+    pool_amount_in
+}
@@ -0,0 +1,174 @@
+use soroban_sdk::{assert_with_error, panic_with_error, symbol_short, Address, BytesN, Env, Map};
+
+use crate::{
+    c_consts::{MAX_UPGRADE_DELAY, MIN_UPGRADE_DELAY, STORAGE_VERSION},
+    c_pool::{
+        balance::write_history_start,
+        error::ErrorExt,
+        event::{MigrateEvent, UpgradeDelayEvent, UpgradeEvent},
+        metadata::{
+            get_total_shares, read_controller, read_factory, read_pending_upgrade,
+            read_storage_version, read_upgrade_delay, remove_pending_upgrade, write_pause_flags,
+            write_pending_upgrade, write_record, write_storage_version, write_upgrade_delay,
+        },
+        storage_types::{DataKey, PendingUpgrade, Record},
+    },
+};
+
+use super::{pause::FREEZE_FLAGS, pool::POOL};
+
+// Check that `caller` is the controller or the Factory that deployed the pool
+pub fn check_upgrade_caller(e: &Env, caller: &Address) {
+    assert_with_error!(
+        e,
+        *caller == read_controller(e) || Some(caller.clone()) == read_factory(e),
+        ErrorExt::ErrUnauthorized
+    );
+}
+
+// Check that the storage has the layout of the current code, every entrypoint
+// apart from the upgrade ones fails between `upgrade` and `migrate`
+pub fn check_storage_version(e: &Env) {
+    assert_with_error!(
+        e,
+        read_storage_version(e) == STORAGE_VERSION,
+        ErrorExt::ErrNotMigrated
+    );
+}
+
+// Update the delay between proposing and executing an upgrade,
+// the delay can only be increased, from MIN_UPGRADE_DELAY up to MAX_UPGRADE_DELAY
+pub fn execute_set_upgrade_delay(e: Env, delay: u64, caller: Address) {
+    let old_delay = read_upgrade_delay(&e);
+    assert_with_error!(
+        &e,
+        delay >= old_delay && (MIN_UPGRADE_DELAY..=MAX_UPGRADE_DELAY).contains(&delay),
+        ErrorExt::ErrUpgradeDelay
+    );
+    write_upgrade_delay(&e, delay);
+
+    let event: UpgradeDelayEvent = UpgradeDelayEvent {
+        caller,
+        old_delay,
+        new_delay: delay,
+    };
+    e.events()
+        .publish((POOL, symbol_short!("upg_delay")), event);
+}
+
+// Propose an upgrade to `wasm_hash`, which can be executed once the upgrade delay has passed
+pub fn execute_propose_upgrade(e: Env, wasm_hash: BytesN<32>, caller: Address) {
+    let eta = e.ledger().timestamp() + read_upgrade_delay(&e);
+    write_pending_upgrade(
+        &e,
+        PendingUpgrade {
+            wasm_hash: wasm_hash.clone(),
+            eta,
+        },
+    );
+
+    let event: UpgradeEvent = UpgradeEvent {
+        caller,
+        wasm_hash,
+        eta,
+    };
+    e.events().publish((POOL, symbol_short!("upg_prop")), event);
+}
+
+// Cancel the pending upgrade
+pub fn execute_cancel_upgrade(e: Env, caller: Address) {
+    let pending = read_pending_upgrade(&e)
+        .unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrNoPendingUpgrade));
+    remove_pending_upgrade(&e);
+
+    let event: UpgradeEvent = UpgradeEvent {
+        caller,
+        wasm_hash: pending.wasm_hash,
+        eta: pending.eta,
+    };
+    e.events().publish((POOL, symbol_short!("upg_cncl")), event);
+}
+
+// Replace the pool's code with `wasm_hash`
+//
+// `wasm_hash` has to be proposed first and can only be executed once the
+// upgrade delay has passed. The new code runs from the next call, which
+// should be `migrate` if the storage layout changed
+pub fn execute_upgrade(e: Env, wasm_hash: BytesN<32>, caller: Address) {
+    let pending = read_pending_upgrade(&e)
+        .unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrNoPendingUpgrade));
+    assert_with_error!(
+        &e,
+        pending.wasm_hash == wasm_hash,
+        ErrorExt::ErrNoPendingUpgrade
+    );
+    assert_with_error!(
+        &e,
+        e.ledger().timestamp() >= pending.eta,
+        ErrorExt::ErrUpgradeTooEarly
+    );
+    remove_pending_upgrade(&e);
+    e.deployer().update_current_contract_wasm(wasm_hash.clone());
+
+    let event: UpgradeEvent = UpgradeEvent {
+        caller,
+        wasm_hash,
+        eta: e.ledger().timestamp(),
+    };
+    e.events().publish((POOL, symbol_short!("upgrade")), event);
+}
+
+// Migrate the storage to the layout of STORAGE_VERSION, called after an upgrade
+pub fn execute_migrate(e: Env, caller: Address) {
+    let old_version = read_storage_version(&e);
+    assert_with_error!(
+        &e,
+        old_version < STORAGE_VERSION,
+        ErrorExt::ErrAlreadyMigrated
+    );
+
+    for version in old_version..STORAGE_VERSION {
+        migrate_step(&e, version);
+    }
+    write_storage_version(&e, STORAGE_VERSION);
+
+    let event: MigrateEvent = MigrateEvent {
+        caller,
+        old_version,
+        new_version: STORAGE_VERSION,
+    };
+    e.events().publish((POOL, symbol_short!("migrate")), event);
+}
+
+// Move the storage from `version` to `version + 1`
+//
+// Add a case here whenever the layout of `Record` or any other stored type changes
+fn migrate_step(e: &Env, version: u32) {
+    match version {
+        // pools deployed before storage versioning freeze with a flag, keep
+        // every record in a single map and have no checkpoints
+        0 => {
+            let key = DataKey::Freeze;
+            if e.storage().instance().get::<DataKey, bool>(&key) == Some(true) {
+                write_pause_flags(e, FREEZE_FLAGS);
+            }
+            e.storage().instance().remove(&key);
+
+            let key = DataKey::AllRecordData;
+            if let Some(records) = e
+                .storage()
+                .persistent()
+                .get::<DataKey, Map<Address, Record>>(&key)
+            {
+                for (token, record) in records.iter() {
+                    write_record(e, &token, &record);
+                }
+                e.storage().persistent().remove(&key);
+            }
+
+            // LP token balances and total supply are recorded from here on
+            write_history_start(e, get_total_shares(e));
+        }
+        _ => panic_with_error!(e, ErrorExt::ErrAlreadyMigrated),
+    }
+}
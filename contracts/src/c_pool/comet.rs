@@ -1,31 +1,71 @@
 //! Liquidity Pool and Token Implementation
+use crate::c_consts::{PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP, PAUSE_WITHDRAW, STORAGE_VERSION};
 use crate::c_pool::{
-    allowance::{read_allowance, spend_allowance, write_allowance},
-    balance::{read_balance, receive_balance, spend_balance},
+    allowance::{read_allowance, read_nonce, read_permit_key, spend_allowance, write_allowance},
+    balance::{
+        read_balance, read_balance_at, read_total_supply_at, receive_balance, spend_balance,
+    },
     call_logic::{
+        admin::{
+            execute_accept_controller, execute_cancel_controller, execute_propose_controller,
+            execute_set_exit_fee, execute_set_guardian, execute_set_swap_fee,
+            execute_set_weight_schedule,
+        },
+        bind::{execute_bind, execute_finalize, execute_rebind, execute_unbind},
+        dynamic_fee::{
+            execute_disable_dynamic_fee, execute_set_dynamic_fee, read_effective_swap_fee,
+        },
+        flash_loan::execute_flash_loan,
         getter::{execute_get_spot_price, execute_get_spot_price_sans_fee},
-        init::execute_init,
+        init::{execute_init, execute_init_unfinalized},
+        oracle::{execute_consult_twap, execute_observe},
+        pause::{
+            execute_cancel_withdraw_pause, execute_guardian_pause, execute_propose_withdraw_pause,
+            execute_set_freeze_status, execute_set_pause_flags,
+        },
+        permit::{execute_permit, execute_set_permit_key},
         pool::{
-            execute_dep_lp_tokn_amt_out_get_tokn_in, execute_dep_tokn_amt_in_get_lp_tokns_out,
-            execute_exit_pool, execute_gulp, execute_join_pool, execute_swap_exact_amount_in,
-            execute_swap_exact_amount_out, execute_wdr_tokn_amt_in_get_lp_tokns_out,
-            execute_wdr_tokn_amt_out_get_lp_tokns_in,
+            check_deadline, execute_dep_lp_tokn_amt_out_get_tokn_in,
+            execute_dep_tokn_amt_in_get_lp_tokns_out, execute_exit_pool, execute_gulp,
+            execute_join_pool, execute_swap_exact_amount_in, execute_swap_exact_amount_out,
+            execute_wdr_tokn_amt_in_get_lp_tokns_out, execute_wdr_tokn_amt_out_get_lp_tokns_in,
+        },
+        protocol_fee::{
+            execute_collect_protocol_fees, execute_set_factory, execute_sync_protocol_fee,
+            read_protocol_fee,
+        },
+        quote::{
+            execute_quote_dep_lp_tokn_amt_out, execute_quote_dep_tokn_amt_in,
+            execute_quote_exit_pool, execute_quote_join_pool, execute_quote_swap_exact_amount_in,
+            execute_quote_swap_exact_amount_out, execute_quote_wdr_tokn_amt_in,
+            execute_quote_wdr_tokn_amt_out,
+        },
+        upgrade::{
+            check_storage_version, check_upgrade_caller, execute_cancel_upgrade, execute_migrate,
+            execute_propose_upgrade, execute_set_upgrade_delay, execute_upgrade,
         },
     },
+    error::{Error, ErrorExt},
     metadata::{
-        get_total_shares, read_controller, read_decimal, read_name, read_record, read_swap_fee,
-        read_symbol, read_tokens,
+        get_total_shares, read_controller, read_decimal, read_dynamic_fee, read_exit_fee,
+        read_factory, read_finalize, read_guardian, read_name, read_pause_flags,
+        read_pending_controller, read_pending_exit_fee, read_pending_upgrade, read_protocol_fees,
+        read_public_swap, read_record, read_records, read_storage_version, read_symbol,
+        read_tokens, read_upgrade_delay, read_weight_schedule, read_withdraw_pause_eta,
+    },
+    storage_types::{
+        DynamicFee, PendingExitFee, PendingUpgrade, ProtocolFee, WeightSchedule,
+        SHARED_BUMP_AMOUNT, SHARED_LIFETIME_THRESHOLD,
     },
-    storage_types::{SHARED_BUMP_AMOUNT, SHARED_LIFETIME_THRESHOLD},
     token_utility::check_nonnegative_amount,
 };
 use soroban_sdk::{
-    contract, contractimpl, token::TokenInterface, unwrap::UnwrapOptimized, Address, Env, String,
-    Vec,
+    contract, contractimpl, panic_with_error, token::TokenInterface, unwrap::UnwrapOptimized,
+    Address, Bytes, BytesN, Env, String, Vec,
 };
 use soroban_token_sdk::TokenUtils;
 
-use super::metadata::{put_total_shares, write_controller, write_freeze};
+use super::metadata::put_total_shares;
 
 #[contract]
 pub struct CometPoolContract;
@@ -48,31 +88,175 @@ impl CometPoolContract {
         execute_init(&e, controller, tokens, weights, balances, swap_fee);
     }
 
+    // Initialize the Pool and the LP Token without any tokens bound
+    // The controller binds the tokens and then finalizes the pool
+    pub fn init_unfinalized(e: Env, controller: Address, swap_fee: i128) {
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_init_unfinalized(&e, controller, swap_fee);
+    }
+
+    // Only Callable by the Pool Admin before the pool is finalized
+    // Binds a token to the pool, transferring `balance` from the controller
+    pub fn bind(e: Env, token: Address, balance: i128, weight: i128) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_bind(e, token, balance, weight, controller);
+    }
+
+    // Only Callable by the Pool Admin before the pool is finalized
+    // Updates the balance and weight of a bound token
+    pub fn rebind(e: Env, token: Address, balance: i128, weight: i128) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_rebind(e, token, balance, weight, controller);
+    }
+
+    // Only Callable by the Pool Admin before the pool is finalized
+    // Unbinds a token and returns its balance to the controller
+    pub fn unbind(e: Env, token: Address) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_unbind(e, token, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Finalizes the pool, making it public. This cannot be undone.
+    pub fn finalize(e: Env) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_finalize(e, controller);
+    }
+
+    // Sets the Factory the protocol fee settings are synced from, with its current settings
+    // Only Callable by the Factory that deployed the pool with `salt`, and can only be set once
+    pub fn set_factory(
+        e: Env,
+        factory: Address,
+        salt: BytesN<32>,
+        protocol_fee: Option<ProtocolFee>,
+    ) {
+        check_storage_version(&e);
+        factory.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_factory(e, factory, salt, protocol_fee);
+    }
+
+    // Refreshes the protocol fee settings from the Factory, callable by anyone
+    pub fn sync_protocol_fee(e: Env) {
+        check_storage_version(&e);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_sync_protocol_fee(&e);
+    }
+
+    // Sends the accrued protocol fees to the fee recipient set on the Factory
+    pub fn collect_protocol_fees(e: Env) {
+        check_storage_version(&e);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_collect_protocol_fees(e);
+    }
+
     // Absorbing tokens into the pool directly sent to the current contract
     pub fn gulp(e: Env, t: Address) {
+        check_storage_version(&e);
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
         execute_gulp(e, t);
     }
 
+    // Lend `amount` of `token` to `receiver`, which has to return it plus a fee
+    // during the `on_flash_loan` callback
+    // Requires the auth of `initiator`, which is passed to the callback so the
+    // receiver can check who started the loan
+    pub fn flash_loan(
+        e: Env,
+        initiator: Address,
+        receiver: Address,
+        token: Address,
+        amount: i128,
+        data: Bytes,
+    ) {
+        check_storage_version(&e);
+        initiator.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_flash_loan(e, initiator, receiver, token, amount, data);
+    }
+
+    // Only Callable by `owner`
+    // Sets the ed25519 key whose signatures are accepted for the permits of `owner`
+    pub fn set_permit_key(e: Env, owner: Address, public_key: BytesN<32>) {
+        check_storage_version(&e);
+        owner.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_permit_key(e, owner, public_key);
+    }
+
+    // Sets the allowance of `spender` over the LP tokens of `owner` with a signature by
+    // the permit key of `owner` over (pool, owner, spender, amount, expiration_ledger, nonce),
+    // so a relayer can approve and `transfer_from` in one transaction
+    pub fn permit(
+        e: Env,
+        owner: Address,
+        spender: Address,
+        amount: i128,
+        expiration_ledger: u32,
+        signature: BytesN<64>,
+    ) {
+        check_storage_version(&e);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_permit(e, owner, spender, amount, expiration_ledger, signature);
+    }
+
     // Helps a users join the pool
     pub fn join_pool(e: Env, pool_amount_out: i128, max_amounts_in: Vec<i128>, user: Address) {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
 
-        execute_join_pool(e, pool_amount_out, max_amounts_in, user);
+        execute_join_pool(e, pool_amount_out, max_amounts_in, user.clone(), user);
     }
 
     // Helps a user exit the pool
     pub fn exit_pool(e: Env, pool_amount_in: i128, min_amounts_out: Vec<i128>, user: Address) {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
-        execute_exit_pool(e, pool_amount_in, min_amounts_out, user);
+        execute_exit_pool(e, pool_amount_in, min_amounts_out, user.clone(), user);
     }
 
     // User wants to swap X amount of Token A
@@ -86,6 +270,7 @@ impl CometPoolContract {
         max_price: i128,
         user: Address,
     ) -> (i128, i128) {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
@@ -97,6 +282,7 @@ impl CometPoolContract {
             token_out,
             min_amount_out,
             max_price,
+            user.clone(),
             user,
         )
     }
@@ -112,6 +298,7 @@ impl CometPoolContract {
         max_price: i128,
         user: Address,
     ) -> (i128, i128) {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
@@ -123,6 +310,7 @@ impl CometPoolContract {
             token_out,
             token_amount_out,
             max_price,
+            user.clone(),
             user,
         )
     }
@@ -136,6 +324,7 @@ impl CometPoolContract {
         min_pool_amount_out: i128,
         user: Address,
     ) -> i128 {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
@@ -145,6 +334,7 @@ impl CometPoolContract {
             token_in,
             token_amount_in,
             min_pool_amount_out,
+            user.clone(),
             user,
         )
     }
@@ -157,11 +347,19 @@ impl CometPoolContract {
         max_amount_in: i128,
         user: Address,
     ) -> i128 {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
-        execute_dep_lp_tokn_amt_out_get_tokn_in(e, token_in, pool_amount_out, max_amount_in, user)
+        execute_dep_lp_tokn_amt_out_get_tokn_in(
+            e,
+            token_in,
+            pool_amount_out,
+            max_amount_in,
+            user.clone(),
+            user,
+        )
     }
 
     // Burns LP tokens and gives back the deposit tokens
@@ -174,11 +372,19 @@ impl CometPoolContract {
         min_amount_out: i128,
         user: Address,
     ) -> i128 {
+        check_storage_version(&e);
         user.require_auth();
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
-        execute_wdr_tokn_amt_in_get_lp_tokns_out(e, token_out, pool_amount_in, min_amount_out, user)
+        execute_wdr_tokn_amt_in_get_lp_tokns_out(
+            e,
+            token_out,
+            pool_amount_in,
+            min_amount_out,
+            user.clone(),
+            user,
+        )
     }
 
     // Burns LP tokens and gives back the deposit tokens
@@ -191,7 +397,218 @@ impl CometPoolContract {
         max_pool_amount_in: i128,
         user: Address,
     ) -> i128 {
+        check_storage_version(&e);
+        user.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_wdr_tokn_amt_out_get_lp_tokns_in(
+            e,
+            token_out,
+            token_amount_out,
+            max_pool_amount_in,
+            user.clone(),
+            user,
+        )
+    }
+
+    // Helps a user join the pool, minting the LP tokens to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    pub fn join_pool_to(
+        e: Env,
+        pool_amount_out: i128,
+        max_amounts_in: Vec<i128>,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_join_pool(e, pool_amount_out, max_amounts_in, user, to);
+    }
+
+    // Helps a user exit the pool, sending the tokens to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    pub fn exit_pool_to(
+        e: Env,
+        pool_amount_in: i128,
+        min_amounts_out: Vec<i128>,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_exit_pool(e, pool_amount_in, min_amounts_out, user, to);
+    }
+
+    // Same as `swap_exact_amount_in`, sending the tokens out to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_amount_in_to(
+        e: Env,
+        token_in: Address,
+        token_amount_in: i128,
+        token_out: Address,
+        min_amount_out: i128,
+        max_price: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> (i128, i128) {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_swap_exact_amount_in(
+            e,
+            token_in,
+            token_amount_in,
+            token_out,
+            min_amount_out,
+            max_price,
+            user,
+            to,
+        )
+    }
+
+    // Same as `swap_exact_amount_out`, sending the tokens out to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_amount_out_to(
+        e: Env,
+        token_in: Address,
+        max_amount_in: i128,
+        token_out: Address,
+        token_amount_out: i128,
+        max_price: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> (i128, i128) {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_swap_exact_amount_out(
+            e,
+            token_in,
+            max_amount_in,
+            token_out,
+            token_amount_out,
+            max_price,
+            user,
+            to,
+        )
+    }
+
+    // Same as `dep_tokn_amt_in_get_lp_tokns_out`, minting the LP tokens to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    pub fn dep_tokn_amt_in_to(
+        e: Env,
+        token_in: Address,
+        token_amount_in: i128,
+        min_pool_amount_out: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> i128 {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_dep_tokn_amt_in_get_lp_tokns_out(
+            e,
+            token_in,
+            token_amount_in,
+            min_pool_amount_out,
+            user,
+            to,
+        )
+    }
+
+    // Same as `dep_lp_tokn_amt_out_get_tokn_in`, minting the LP tokens to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    pub fn dep_lp_tokn_amt_out_to(
+        e: Env,
+        token_in: Address,
+        pool_amount_out: i128,
+        max_amount_in: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> i128 {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_dep_lp_tokn_amt_out_get_tokn_in(
+            e,
+            token_in,
+            pool_amount_out,
+            max_amount_in,
+            user,
+            to,
+        )
+    }
+
+    // Same as `wdr_tokn_amt_in_get_lp_tokns_out`, sending the tokens out to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    pub fn wdr_tokn_amt_in_to(
+        e: Env,
+        token_out: Address,
+        pool_amount_in: i128,
+        min_amount_out: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> i128 {
+        check_storage_version(&e);
+        user.require_auth();
+        check_deadline(&e, deadline);
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_wdr_tokn_amt_in_get_lp_tokns_out(
+            e,
+            token_out,
+            pool_amount_in,
+            min_amount_out,
+            user,
+            to,
+        )
+    }
+
+    // Same as `wdr_tokn_amt_out_get_lp_tokns_in`, sending the tokens out to `to`
+    // Fails if the ledger timestamp is past `deadline`
+    pub fn wdr_tokn_amt_out_to(
+        e: Env,
+        token_out: Address,
+        token_amount_out: i128,
+        max_pool_amount_in: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> i128 {
+        check_storage_version(&e);
         user.require_auth();
+        check_deadline(&e, deadline);
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
@@ -201,69 +618,537 @@ impl CometPoolContract {
             token_amount_out,
             max_pool_amount_in,
             user,
+            to,
         )
     }
 
-    // Sets the value of the controller address, only can be set by the current controller
-    pub fn set_controller(e: Env, manager: Address) {
-        read_controller(&e).require_auth();
+    // Proposes a new controller address, only can be set by the current controller
+    // The new controller has to call `accept_controller` to take over the role
+    pub fn propose_controller(e: Env, manager: Address) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_propose_controller(e, manager, controller);
+    }
+
+    // Accepts the controller role, only can be called by the pending controller
+    pub fn accept_controller(e: Env) {
+        check_storage_version(&e);
+        let pending_controller = read_pending_controller(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNoPendingController));
+        pending_controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        let controller = read_controller(&e);
+        execute_accept_controller(e, pending_controller, controller);
+    }
+
+    // Cancels the pending controller transfer, only can be called by the current controller
+    pub fn cancel_controller(e: Env) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
-        write_controller(&e, manager);
+        execute_cancel_controller(e, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Sets the Guardian, which can pause operations but not unpause them,
+    // change fees or move funds. Passing None removes the Guardian
+    pub fn set_guardian(e: Env, guardian: Option<Address>) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_guardian(e, guardian, controller);
     }
 
     // Only Callable by the Pool Admin
     // Freezes Functions and only allows withdrawals
     pub fn set_freeze_status(e: Env, val: bool) {
-        read_controller(&e).require_auth();
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_freeze_status(e, val, controller);
+    }
+
+    // Only Callable by the Guardian
+    // Pauses the operations in `flags` on top of the ones already paused,
+    // passing PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT freezes the pool
+    // Only the Pool Admin can unpause operations or pause withdrawals
+    pub fn guardian_pause(e: Env, flags: u32) {
+        check_storage_version(&e);
+        let guardian =
+            read_guardian(&e).unwrap_or_else(|| panic_with_error!(&e, ErrorExt::ErrNoGuardian));
+        guardian.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_guardian_pause(e, flags, guardian);
+    }
+
+    // Only Callable by the Pool Admin
+    // Sets the bitmask of paused operations: swaps, joins, deposits and withdrawals
+    // Withdrawals can only be paused once `propose_withdraw_pause` has passed its timelock
+    pub fn set_pause_flags(e: Env, flags: u32) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_pause_flags(e, flags, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Starts the WITHDRAW_PAUSE_DELAY timelock after which withdrawals can be paused,
+    // the proposal expires WITHDRAW_PAUSE_GRACE after the timelock
+    pub fn propose_withdraw_pause(e: Env) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_propose_withdraw_pause(e, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Cancels the proposed withdrawal pause
+    pub fn cancel_withdraw_pause(e: Env) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_cancel_withdraw_pause(e, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Updates the swap fee, limited to MAX_FEE_DELTA per change
+    // and one change every MIN_FEE_UPDATE_DELAY ledgers
+    pub fn set_swap_fee(e: Env, swap_fee: i128) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_swap_fee(e, swap_fee, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Enables the dynamic fee, the swap fee then moves between `min_fee` and `max_fee`
+    // with the spot price movement of recent swaps, decaying over VOLATILITY_DECAY_LEDGERS
+    pub fn set_dynamic_fee(e: Env, min_fee: i128, max_fee: i128) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_dynamic_fee(e, min_fee, max_fee, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Disables the dynamic fee, swaps are charged the fixed swap fee again
+    pub fn disable_dynamic_fee(e: Env) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_disable_dynamic_fee(e, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Updates the exit fee charged in LP tokens when exiting the pool,
+    // limited to MAX_EXIT_FEE, increases apply after MIN_FEE_UPDATE_DELAY ledgers
+    pub fn set_exit_fee(e: Env, exit_fee: i128) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_exit_fee(e, exit_fee, controller);
+    }
+
+    // Only Callable by the Pool Admin
+    // Linearly shifts the weights from the current weights to `end_weights`
+    // between `start_ledger` and `end_ledger`, replacing any existing schedule
+    pub fn set_weight_schedule(e: Env, end_weights: Vec<i128>, start_ledger: u32, end_ledger: u32) {
+        check_storage_version(&e);
+        let controller = read_controller(&e);
+        controller.require_auth();
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
-        write_freeze(&e, val);
+        execute_set_weight_schedule(e, end_weights, start_ledger, end_ledger, controller);
+    }
+
+    // Only Callable by the Pool Admin or the Factory
+    // Sets the delay in seconds between proposing and executing an upgrade,
+    // the delay can only be increased, from MIN_UPGRADE_DELAY up to MAX_UPGRADE_DELAY
+    pub fn set_upgrade_delay(e: Env, delay: u64, caller: Address) {
+        check_upgrade_caller(&e, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_set_upgrade_delay(e, delay, caller);
+    }
+
+    // Only Callable by the Pool Admin or the Factory
+    // Proposes an upgrade to `new_wasm_hash`, replacing any pending upgrade
+    // The timelock is not optional: every upgrade waits at least MIN_UPGRADE_DELAY,
+    // so LPs always have time to exit before the pool's code changes
+    pub fn propose_upgrade(e: Env, new_wasm_hash: BytesN<32>, caller: Address) {
+        check_upgrade_caller(&e, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_propose_upgrade(e, new_wasm_hash, caller);
+    }
+
+    // Only Callable by the Pool Admin or the Factory
+    // Cancels the pending upgrade
+    pub fn cancel_upgrade(e: Env, caller: Address) {
+        check_upgrade_caller(&e, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_cancel_upgrade(e, caller);
+    }
+
+    // Only Callable by the Pool Admin or the Factory
+    // Replaces the code of the pool with `new_wasm_hash`, the upgrade has to
+    // be proposed and the upgrade delay has to pass first
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>, caller: Address) {
+        check_upgrade_caller(&e, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_upgrade(e, new_wasm_hash, caller);
+    }
+
+    // Only Callable by the Pool Admin or the Factory
+    // Migrates the storage to the layout of the current code after an upgrade
+    // Every other entrypoint fails with ErrNotMigrated until it is called
+    pub fn migrate(e: Env, caller: Address) {
+        check_upgrade_caller(&e, &caller);
+        caller.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_migrate(e, caller);
     }
 
     // GETTER FUNCTIONS
 
     // Get the Controller Address
     pub fn get_total_supply(e: Env) -> i128 {
+        check_storage_version(&e);
         get_total_shares(&e)
     }
 
     // Get the Controller Address
     pub fn get_controller(e: Env) -> Address {
+        check_storage_version(&e);
         read_controller(&e)
     }
 
+    // Check if the pool is finalized
+    pub fn is_finalized(e: Env) -> bool {
+        check_storage_version(&e);
+        read_finalize(&e)
+    }
+
+    // Check if swaps are open to the public
+    pub fn is_public_swap(e: Env) -> bool {
+        check_storage_version(&e);
+        read_public_swap(&e)
+    }
+
+    // Get the bitmask of paused operations
+    pub fn get_pause_flags(e: Env) -> u32 {
+        check_storage_version(&e);
+        read_pause_flags(&e)
+    }
+
+    // Check if swaps and flash loans are paused
+    pub fn is_swap_paused(e: Env) -> bool {
+        check_storage_version(&e);
+        read_pause_flags(&e) & PAUSE_SWAP != 0
+    }
+
+    // Check if proportional joins are paused
+    pub fn is_join_paused(e: Env) -> bool {
+        check_storage_version(&e);
+        read_pause_flags(&e) & PAUSE_JOIN != 0
+    }
+
+    // Check if single-sided deposits are paused
+    pub fn is_deposit_paused(e: Env) -> bool {
+        check_storage_version(&e);
+        read_pause_flags(&e) & PAUSE_DEPOSIT != 0
+    }
+
+    // Check if exits and single-sided withdrawals are paused
+    pub fn is_withdraw_paused(e: Env) -> bool {
+        check_storage_version(&e);
+        read_pause_flags(&e) & PAUSE_WITHDRAW != 0
+    }
+
+    // Get the timestamp from which withdrawals can be paused, if a pause has been proposed
+    pub fn get_withdraw_pause_eta(e: Env) -> Option<u64> {
+        check_storage_version(&e);
+        read_withdraw_pause_eta(&e)
+    }
+
+    // Get the Guardian Address, if one is set
+    pub fn get_guardian(e: Env) -> Option<Address> {
+        check_storage_version(&e);
+        read_guardian(&e)
+    }
+
+    // Get the LP token balance of `id` at the end of `ledger`, for governance voting power
+    // `ledger` has to be before the current ledger, and for pools migrated from before
+    // checkpoints existed, at or after the ledger of the migration
+    pub fn balance_at(e: Env, id: Address, ledger: u32) -> i128 {
+        check_storage_version(&e);
+        read_balance_at(&e, id, ledger)
+    }
+
+    // Get the LP token total supply at the end of `ledger`, before the current ledger
+    // and at or after the ledger of the migration for migrated pools
+    pub fn total_supply_at(e: Env, ledger: u32) -> i128 {
+        check_storage_version(&e);
+        read_total_supply_at(&e, ledger)
+    }
+
+    // Get the nonce the next permit signed by `owner` has to use
+    pub fn get_nonce(e: Env, owner: Address) -> u64 {
+        check_storage_version(&e);
+        read_nonce(&e, owner)
+    }
+
+    // Get the ed25519 key whose signatures are accepted for the permits of `owner`
+    pub fn get_permit_key(e: Env, owner: Address) -> Option<BytesN<32>> {
+        check_storage_version(&e);
+        read_permit_key(&e, owner)
+    }
+
+    // Get the Pending Controller Address, if a transfer has been proposed
+    pub fn get_pending_controller(e: Env) -> Option<Address> {
+        check_storage_version(&e);
+        read_pending_controller(&e)
+    }
+
+    // Get the Factory Address, if the pool has one
+    pub fn get_factory(e: Env) -> Option<Address> {
+        check_storage_version(&e);
+        read_factory(&e)
+    }
+
+    // Get the protocol fee settings cached from the Factory, if a protocol fee is set
+    pub fn get_protocol_fee(e: Env) -> Option<ProtocolFee> {
+        check_storage_version(&e);
+        read_protocol_fee(&e)
+    }
+
+    // Get the protocol fees accrued for the Token that have not been collected
+    pub fn get_protocol_fees(e: Env, token: Address) -> i128 {
+        check_storage_version(&e);
+        read_protocol_fees(&e).get(token).unwrap_or(0)
+    }
+
     // Get the Current Tokens in the Pool
     pub fn get_tokens(e: Env) -> Vec<Address> {
+        check_storage_version(&e);
         read_tokens(&e)
     }
 
     // Get the balance of the Token
     pub fn get_balance(e: Env, token: Address) -> i128 {
-        let val = read_record(&e).get(token).unwrap_optimized();
+        check_storage_version(&e);
+        let val = read_record(&e, &token).unwrap_optimized();
         val.balance
     }
 
     // Get the weight of the token in decimal form with 7 decimals
     pub fn get_normalized_weight(e: Env, token: Address) -> i128 {
-        let val = read_record(&e).get(token).unwrap_optimized();
+        check_storage_version(&e);
+        let val = read_record(&e, &token).unwrap_optimized();
         val.weight
     }
 
+    // Get the current weights of the tokens, ordered like `get_tokens`
+    pub fn get_weights(e: Env) -> Vec<i128> {
+        check_storage_version(&e);
+        let records = read_records(&e);
+        let mut weights = Vec::new(&e);
+        for token in read_tokens(&e).iter() {
+            weights.push_back(records.get_unchecked(token).weight);
+        }
+        weights
+    }
+
+    // Get the weight schedule, if one has been set
+    pub fn get_weight_schedule(e: Env) -> Option<WeightSchedule> {
+        check_storage_version(&e);
+        read_weight_schedule(&e)
+    }
+
     // Calculate the spot considering the swap fee
     pub fn get_spot_price(e: Env, token_in: Address, token_out: Address) -> i128 {
+        check_storage_version(&e);
         execute_get_spot_price(e, token_in, token_out)
     }
 
-    // Get the Swap Fee of the Contract
+    // Get the Swap Fee charged by the next swap, including the dynamic fee if enabled
     pub fn get_swap_fee(e: Env) -> i128 {
-        read_swap_fee(&e)
+        check_storage_version(&e);
+        read_effective_swap_fee(&e)
+    }
+
+    // Get the Dynamic Fee Bounds, if the dynamic fee is enabled
+    pub fn get_dynamic_fee(e: Env) -> Option<DynamicFee> {
+        check_storage_version(&e);
+        read_dynamic_fee(&e)
+    }
+
+    // Get the Exit Fee of the Contract
+    pub fn get_exit_fee(e: Env) -> i128 {
+        check_storage_version(&e);
+        read_exit_fee(&e)
+    }
+
+    // Get the pending Exit Fee increase, if one was set
+    pub fn get_pending_exit_fee(e: Env) -> Option<PendingExitFee> {
+        check_storage_version(&e);
+        read_pending_exit_fee(&e)
+    }
+
+    // Get the storage layout version of the pool
+    pub fn get_storage_version(e: Env) -> u32 {
+        read_storage_version(&e)
+    }
+
+    // Returns true if the storage has the layout of the current code,
+    // false after an upgrade until `migrate` is called
+    pub fn is_migrated(e: Env) -> bool {
+        read_storage_version(&e) == STORAGE_VERSION
+    }
+
+    // Get the delay in seconds between proposing and executing an upgrade
+    pub fn get_upgrade_delay(e: Env) -> u64 {
+        read_upgrade_delay(&e)
+    }
+
+    // Get the pending upgrade, if one has been proposed
+    pub fn get_pending_upgrade(e: Env) -> Option<PendingUpgrade> {
+        read_pending_upgrade(&e)
+    }
+
+    // Get the time weighted price accumulators of `token_out` in `token_in`
+    // for each of the given number of seconds ago, the accumulators wrap on overflow
+    pub fn observe(
+        e: Env,
+        token_in: Address,
+        token_out: Address,
+        seconds_ago: Vec<u64>,
+    ) -> Vec<i128> {
+        check_storage_version(&e);
+        execute_observe(e, token_in, token_out, seconds_ago)
+    }
+
+    // Get the time weighted average price of `token_out` in `token_in`
+    // over the last `window` seconds
+    pub fn consult_twap(e: Env, token_in: Address, token_out: Address, window: u64) -> i128 {
+        check_storage_version(&e);
+        execute_consult_twap(e, token_in, token_out, window)
+    }
+
+    // Quote the amount of `token_out` received and the spot price after
+    // swapping `token_amount_in` of `token_in`
+    pub fn quote_swap_exact_amount_in(
+        e: Env,
+        token_in: Address,
+        token_amount_in: i128,
+        token_out: Address,
+    ) -> (i128, i128) {
+        check_storage_version(&e);
+        execute_quote_swap_exact_amount_in(e, token_in, token_amount_in, token_out)
+    }
+
+    // Quote the amount of `token_in` required and the spot price after
+    // swapping for `token_amount_out` of `token_out`
+    pub fn quote_swap_exact_amount_out(
+        e: Env,
+        token_in: Address,
+        token_out: Address,
+        token_amount_out: i128,
+    ) -> (i128, i128) {
+        check_storage_version(&e);
+        execute_quote_swap_exact_amount_out(e, token_in, token_out, token_amount_out)
+    }
+
+    // Quote the amount of each token required to join the pool for `pool_amount_out`
+    pub fn quote_join_pool(e: Env, pool_amount_out: i128) -> Vec<i128> {
+        check_storage_version(&e);
+        execute_quote_join_pool(e, pool_amount_out)
+    }
+
+    // Quote the amount of each token received for exiting the pool with `pool_amount_in`
+    pub fn quote_exit_pool(e: Env, pool_amount_in: i128) -> Vec<i128> {
+        check_storage_version(&e);
+        execute_quote_exit_pool(e, pool_amount_in)
+    }
+
+    // Quote the LP tokens received for depositing `token_amount_in` of `token_in`
+    pub fn quote_dep_tokn_amt_in(e: Env, token_in: Address, token_amount_in: i128) -> i128 {
+        check_storage_version(&e);
+        execute_quote_dep_tokn_amt_in(e, token_in, token_amount_in)
+    }
+
+    // Quote the amount of `token_in` required to receive `pool_amount_out` LP tokens
+    pub fn quote_dep_lp_tokn_amt_out(e: Env, token_in: Address, pool_amount_out: i128) -> i128 {
+        check_storage_version(&e);
+        execute_quote_dep_lp_tokn_amt_out(e, token_in, pool_amount_out)
+    }
+
+    // Quote the amount of `token_out` received for burning `pool_amount_in` LP tokens
+    pub fn quote_wdr_tokn_amt_in(e: Env, token_out: Address, pool_amount_in: i128) -> i128 {
+        check_storage_version(&e);
+        execute_quote_wdr_tokn_amt_in(e, token_out, pool_amount_in)
+    }
+
+    // Quote the LP tokens burned to withdraw `token_amount_out` of `token_out`
+    pub fn quote_wdr_tokn_amt_out(e: Env, token_out: Address, token_amount_out: i128) -> i128 {
+        check_storage_version(&e);
+        execute_quote_wdr_tokn_amt_out(e, token_out, token_amount_out)
     }
 
     // Get the spot price without considering the swap fee
     pub fn get_spot_price_sans_fee(e: Env, token_in: Address, token_out: Address) -> i128 {
+        check_storage_version(&e);
         execute_get_spot_price_sans_fee(e, token_in, token_out)
     }
 }
@@ -272,6 +1157,7 @@ impl CometPoolContract {
 #[contractimpl]
 impl TokenInterface for CometPoolContract {
     fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        check_storage_version(&e);
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
@@ -279,6 +1165,7 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        check_storage_version(&e);
         from.require_auth();
 
         check_nonnegative_amount(amount);
@@ -295,6 +1182,7 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn balance(e: Env, id: Address) -> i128 {
+        check_storage_version(&e);
         e.storage()
             .instance()
             .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
@@ -302,6 +1190,7 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        check_storage_version(&e);
         from.require_auth();
 
         check_nonnegative_amount(amount);
@@ -316,6 +1205,7 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        check_storage_version(&e);
         spender.require_auth();
 
         check_nonnegative_amount(amount);
@@ -331,6 +1221,7 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn burn(e: Env, from: Address, amount: i128) {
+        check_storage_version(&e);
         from.require_auth();
         let total = get_total_shares(&e);
         check_nonnegative_amount(amount);
@@ -345,6 +1236,7 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+        check_storage_version(&e);
         spender.require_auth();
         let total = get_total_shares(&e);
         check_nonnegative_amount(amount);
@@ -360,14 +1252,17 @@ impl TokenInterface for CometPoolContract {
     }
 
     fn decimals(e: Env) -> u32 {
+        check_storage_version(&e);
         read_decimal(&e)
     }
 
     fn name(e: Env) -> String {
+        check_storage_version(&e);
         read_name(&e)
     }
 
     fn symbol(e: Env) -> String {
+        check_storage_version(&e);
         read_symbol(&e)
     }
 }
@@ -42,4 +42,52 @@ pub enum Error {
     ErrInvalidExpirationLedger = 36,
     ErrNegativeOrZero = 37,
     ErrTokenInvalid = 38,
+    ErrMaxFeeDelta = 39,
+    ErrFeeUpdateTooSoon = 40,
+    ErrInvalidSchedule = 41,
+    ErrNotFinalized = 42,
+    ErrSwapNotPublic = 43,
+    ErrNoPendingController = 44,
+    ErrNoFeeRecipient = 45,
+    ErrMaxExitFee = 46,
+    ErrNoObservations = 47,
+    ErrObservationTooOld = 48,
+    ErrFlashLoanNotRepaid = 49,
+    ErrReentrancy = 50,
+}
+
+// Error codes continue here, as `Error` holds the maximum number of
+// cases allowed in a contract error type
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum ErrorExt {
+    ErrDeadlinePassed = 51,
+    ErrUnauthorized = 52,
+    ErrNoPendingUpgrade = 53,
+    ErrUpgradeTooEarly = 54,
+    ErrUpgradeDelay = 55,
+    ErrAlreadyMigrated = 56,
+    ErrWithdrawPaused = 57,
+    ErrInvalidPauseFlags = 58,
+    ErrWithdrawPauseTimelock = 59,
+    ErrNoPendingPause = 60,
+    ErrNoGuardian = 61,
+    ErrInvalidDynamicFee = 62,
+    ErrNoPermitKey = 63,
+    ErrNoFactory = 64,
+    ErrNotMigrated = 65,
+    ErrLedgerNotFinal = 66,
+    ErrHistoryUnavailable = 67,
+}
+
+impl From<math::error::Error> for Error {
+    fn from(err: math::error::Error) -> Self {
+        match err {
+            math::error::Error::ErrMathApprox => Error::ErrMathApprox,
+            math::error::Error::ErrSubUnderflow => Error::ErrSubUnderflow,
+            math::error::Error::ErrCPowBaseTooLow => Error::ErrCPowBaseTooLow,
+            math::error::Error::ErrCPowBaseTooHigh => Error::ErrCPowBaseTooHigh,
+        }
+    }
 }
@@ -1,50 +1,242 @@
 //! Definition of the Events used in the contract
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, BytesN, Vec};
+
+// The trade events carry a `version` field set to EVENT_VERSION, fields are only
+// ever added, and balances and total supply are the values after the operation
 
 // Swap Token Event, emitted when tokens are swapped
+// `fee_amount` is the swap fee paid in `token_in`
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SwapEvent {
+    pub version: u32,
     pub caller: Address,
     pub token_in: Address,
     pub token_out: Address,
     pub token_amount_in: i128,
     pub token_amount_out: i128,
+    pub fee_amount: i128,
+    pub spot_price_after: i128,
+    pub balance_in: i128,
+    pub balance_out: i128,
+    pub total_supply: i128,
 }
 
-// Join Pool Event, emitted a when a user joins the pool
+// Join Pool Event, emitted a when a user joins the pool, once per token
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct JoinEvent {
+    pub version: u32,
     pub caller: Address,
     pub token_in: Address,
     pub token_amount_in: i128,
+    pub pool_amount_out: i128,
+    pub balance: i128,
+    pub total_supply: i128,
 }
 
-// Exit Pool Event, emitted a when a user exits the pool
+// Exit Pool Event, emitted a when a user exits the pool, once per token
+// `exit_fee_amount` is the exit fee paid in LP tokens
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ExitEvent {
+    pub version: u32,
     pub caller: Address,
     pub token_out: Address,
     pub token_amount_out: i128,
+    pub pool_amount_in: i128,
+    pub exit_fee_amount: i128,
+    pub balance: i128,
+    pub total_supply: i128,
 }
 
-// Join Pool Event, emitted a when a user joins the pool
+// Deposit Event, emitted a when a user deposits a single token
+// `fee_amount` is the swap fee paid in `token_in`
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DepositEvent {
+    pub version: u32,
     pub caller: Address,
     pub token_in: Address,
     pub token_amount_in: i128,
+    pub pool_amount_out: i128,
+    pub fee_amount: i128,
+    pub balance: i128,
+    pub total_supply: i128,
 }
 
-// Exit Pool Event, emitted a when a user exits the pool
+// Withdraw Event, emitted a when a user withdraws a single token
+// `fee_amount` is the swap fee paid in `token_out`,
+// `exit_fee_amount` is the exit fee paid in LP tokens
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct WithdrawEvent {
+    pub version: u32,
     pub caller: Address,
     pub token_out: Address,
     pub token_amount_out: i128,
     pub pool_amount_in: i128,
+    pub fee_amount: i128,
+    pub exit_fee_amount: i128,
+    pub balance: i128,
+    pub total_supply: i128,
+}
+
+// Sync Event, emitted with the full pool state after gulps and admin changes,
+// tokens, balances and weights are ordered like `AllTokenVec`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncEvent {
+    pub version: u32,
+    pub tokens: Vec<Address>,
+    pub balances: Vec<i128>,
+    pub weights: Vec<i128>,
+    pub swap_fee: i128,
+    pub total_supply: i128,
+}
+
+// Fee Change Event, emitted when the controller updates the swap fee
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeChangeEvent {
+    pub caller: Address,
+    pub old_fee: i128,
+    pub new_fee: i128,
+}
+
+// Weight Schedule Event, emitted when the controller schedules a weight change
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeightScheduleEvent {
+    pub caller: Address,
+    pub start_ledger: u32,
+    pub end_ledger: u32,
+    pub start_weights: Vec<i128>,
+    pub end_weights: Vec<i128>,
+}
+
+// Bind Event, emitted when the controller binds or rebinds a token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BindEvent {
+    pub caller: Address,
+    pub token: Address,
+    pub balance: i128,
+    pub weight: i128,
+}
+
+// Unbind Event, emitted when the controller unbinds a token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnbindEvent {
+    pub caller: Address,
+    pub token: Address,
+    pub token_amount_out: i128,
+}
+
+// Finalize Event, emitted when the controller finalizes the pool
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalizeEvent {
+    pub caller: Address,
+    pub pool_amount_out: i128,
+}
+
+// Controller Event, emitted when a controller transfer is proposed, accepted or cancelled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ControllerEvent {
+    pub controller: Address,
+    pub pending_controller: Address,
+}
+
+// Guardian Event, emitted when the controller sets or removes the guardian
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianEvent {
+    pub controller: Address,
+    pub guardian: Address,
+}
+
+// Protocol Fee Event, emitted when a swap accrues a protocol fee
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolFeeEvent {
+    pub token: Address,
+    pub amount: i128,
+}
+
+// Collect Event, emitted when accrued protocol fees are sent to the fee recipient
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CollectEvent {
+    pub fee_recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+// Flash Loan Event, emitted when a flash loan is repaid
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlashLoanEvent {
+    pub version: u32,
+    pub receiver: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub fee: i128,
+    pub balance: i128,
+    pub total_supply: i128,
+}
+
+// Upgrade Event, emitted when an upgrade is proposed, cancelled or executed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeEvent {
+    pub caller: Address,
+    pub wasm_hash: BytesN<32>,
+    pub eta: u64,
+}
+
+// Upgrade Delay Event, emitted when the upgrade timelock is changed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeDelayEvent {
+    pub caller: Address,
+    pub old_delay: u64,
+    pub new_delay: u64,
+}
+
+// Migrate Event, emitted when the storage is migrated to a new layout version
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MigrateEvent {
+    pub caller: Address,
+    pub old_version: u32,
+    pub new_version: u32,
+}
+
+// Pause Event, emitted when the paused operations change
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PauseEvent {
+    pub caller: Address,
+    pub old_flags: u32,
+    pub new_flags: u32,
+}
+
+// Withdraw Pause Event, emitted when a withdrawal pause is proposed or cancelled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawPauseEvent {
+    pub caller: Address,
+    pub eta: u64,
+}
+
+// Dynamic Fee Event, emitted when the dynamic fee bounds are set or the dynamic fee is disabled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DynamicFeeEvent {
+    pub caller: Address,
+    pub min_fee: i128,
+    pub max_fee: i128,
 }
@@ -0,0 +1,10 @@
+//! Interface of the Factory Contract used by the Pool
+use soroban_sdk::{contractclient, Env};
+
+use super::storage_types::ProtocolFee;
+
+#[contractclient(name = "FactoryClient")]
+pub trait FactoryInterface {
+    // Get the protocol fee settings, if a protocol fee is set
+    fn get_protocol_fee(e: Env) -> Option<ProtocolFee>;
+}
@@ -0,0 +1,20 @@
+//! Interface of the Flash Loan Receiver Contract called by the Pool
+use soroban_sdk::{contractclient, Address, Bytes, Env};
+
+#[contractclient(name = "FlashLoanReceiverClient")]
+pub trait FlashLoanReceiver {
+    // Called after `amount` of `token` is sent to the receiver, which has to
+    // send back `amount + fee` to the pool before returning
+    //
+    // Anyone can start a loan to any receiver, so receivers should only act
+    // for an `initiator` they trust
+    fn on_flash_loan(
+        e: Env,
+        initiator: Address,
+        pool: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        data: Bytes,
+    );
+}
@@ -1,10 +1,17 @@
 //! Utilities to read and write contract's storage
 
-use crate::c_pool::storage_types::DataKey;
+use crate::{
+    c_consts::MIN_UPGRADE_DELAY,
+    c_math::{calc_scheduled_weights, calc_weighted_balance},
+    c_pool::{balance::write_supply_checkpoint, storage_types::DataKey},
+};
 use soroban_sdk::{unwrap::UnwrapOptimized, Address, Env, Map, String, Vec};
 use soroban_token_sdk::{metadata::TokenMetadata, TokenUtils};
 
-use super::storage_types::{Record, SHARED_BUMP_AMOUNT, SHARED_LIFETIME_THRESHOLD};
+use super::storage_types::{
+    DynamicFee, Observation, OracleState, PendingExitFee, PendingUpgrade, ProtocolFee, Record,
+    Volatility, WeightSchedule, SHARED_BUMP_AMOUNT, SHARED_LIFETIME_THRESHOLD,
+};
 
 // Read all Token Addresses in the pool
 pub fn read_tokens(e: &Env) -> Vec<Address> {
@@ -27,36 +34,167 @@ pub fn write_tokens(e: &Env, new: Vec<Address>) {
         .extend_ttl(&key, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
 }
 
-// Read Record
-pub fn read_record(e: &Env) -> Map<Address, Record> {
-    let key_rec = DataKey::AllRecordData;
+// Read the Record of `token`, or None if the token is not bound
+//
+// If a weight schedule exists, the weight of the record is
+// the interpolated weight for the current ledger
+pub fn read_record(e: &Env, token: &Address) -> Option<Record> {
+    let mut record = read_stored_record(e, token)?;
+    if let Some(schedule) = read_weight_schedule(e) {
+        let weights = calc_scheduled_weights(e, &schedule, e.ledger().sequence());
+        record.weight = weights.get_unchecked(record.index);
+    }
+    Some(record)
+}
+
+// Read the Records of every bound token, with the weights of the weight schedule
+pub fn read_records(e: &Env) -> Map<Address, Record> {
+    let weights = read_weight_schedule(e)
+        .map(|schedule| calc_scheduled_weights(e, &schedule, e.ledger().sequence()));
+    let mut records = Map::new(e);
+    for token in read_tokens(e).iter() {
+        let mut record = read_stored_record(e, &token).unwrap_optimized();
+        if let Some(weights) = &weights {
+            record.weight = weights.get_unchecked(record.index);
+        }
+        records.set(token, record);
+    }
+    records
+}
+
+fn read_stored_record(e: &Env, token: &Address) -> Option<Record> {
+    let key = DataKey::Record(token.clone());
+    let record = e.storage().persistent().get::<DataKey, Record>(&key)?;
     e.storage()
         .persistent()
-        .extend_ttl(&key_rec, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        .extend_ttl(&key, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+    Some(record)
+}
+
+// Write the Record of `token`, and its weighted balance once the price oracle has started
+pub fn write_record(e: &Env, token: &Address, record: &Record) {
+    let key = DataKey::Record(token.clone());
+    e.storage().persistent().set(&key, record);
     e.storage()
         .persistent()
-        .get::<DataKey, Map<Address, Record>>(&key_rec)
-        .unwrap_optimized()
+        .extend_ttl(&key, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+    if let Some(mut weighted_balances) = read_weighted_balances(e) {
+        weighted_balances.set(record.index, calc_weighted_balance(record));
+        write_weighted_balances(e, weighted_balances);
+    }
 }
 
-// Write Record
-pub fn write_record(e: &Env, new_map: Map<Address, Record>) {
-    let key_rec = DataKey::AllRecordData;
-    e.storage().persistent().set(&key_rec, &new_map);
+// Remove the Record of an unbound token
+pub fn remove_record(e: &Env, token: &Address) {
     e.storage()
         .persistent()
-        .extend_ttl(&key_rec, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        .remove(&DataKey::Record(token.clone()));
 }
 
-// Read Factory
-pub fn read_factory(e: &Env) -> Address {
-    let key = DataKey::Factory;
+// Read Protocol Fees accrued per token
+pub fn read_protocol_fees(e: &Env) -> Map<Address, i128> {
+    let key = DataKey::ProtocolFees;
+    if let Some(fees) = e
+        .storage()
+        .persistent()
+        .get::<DataKey, Map<Address, i128>>(&key)
+    {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        fees
+    } else {
+        Map::new(e)
+    }
+}
+
+// Write Protocol Fees accrued per token
+pub fn write_protocol_fees(e: &Env, fees: Map<Address, i128>) {
+    let key = DataKey::ProtocolFees;
+    e.storage().persistent().set(&key, &fees);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+}
+
+// Read the protocol fee settings cached from the Factory
+pub fn read_protocol_fee_settings(e: &Env) -> Option<ProtocolFee> {
+    let key = DataKey::ProtocolFee;
+    e.storage().instance().get::<DataKey, ProtocolFee>(&key)
+}
+
+// Write the protocol fee settings cached from the Factory
+pub fn write_protocol_fee_settings(e: &Env, settings: Option<ProtocolFee>) {
+    let key = DataKey::ProtocolFee;
+    match settings {
+        Some(settings) => e.storage().instance().set(&key, &settings),
+        None => e.storage().instance().remove(&key),
+    }
+}
+
+// Read the latest Price Accumulators
+pub fn read_price_cumulative(e: &Env) -> Option<Observation> {
+    let key = DataKey::PriceCumulative;
+    e.storage().instance().get::<DataKey, Observation>(&key)
+}
+
+// Write the latest Price Accumulators
+pub fn write_price_cumulative(e: &Env, d: Observation) {
+    let key = DataKey::PriceCumulative;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read the weighted balances the price oracle accumulates prices from
+pub fn read_weighted_balances(e: &Env) -> Option<Vec<i128>> {
+    let key = DataKey::WeightedBalances;
+    e.storage().instance().get::<DataKey, Vec<i128>>(&key)
+}
+
+// Write the weighted balances the price oracle accumulates prices from
+pub fn write_weighted_balances(e: &Env, d: Vec<i128>) {
+    let key = DataKey::WeightedBalances;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read the position of the Observation Ring Buffer
+pub fn read_oracle_state(e: &Env) -> OracleState {
+    let key = DataKey::OracleState;
     e.storage()
         .instance()
-        .get::<DataKey, Address>(&key)
+        .get::<DataKey, OracleState>(&key)
+        .unwrap_or_default()
+}
+
+// Write the position of the Observation Ring Buffer
+pub fn write_oracle_state(e: &Env, d: OracleState) {
+    let key = DataKey::OracleState;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read an Observation from the Ring Buffer
+pub fn read_observation(e: &Env, index: u32) -> Observation {
+    let key = DataKey::Observation(index);
+    e.storage()
+        .persistent()
+        .get::<DataKey, Observation>(&key)
         .unwrap_optimized()
 }
 
+// Write an Observation to the Ring Buffer
+pub fn write_observation(e: &Env, index: u32, d: Observation) {
+    let key = DataKey::Observation(index);
+    e.storage().persistent().set(&key, &d);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+}
+
+// Read Factory
+pub fn read_factory(e: &Env) -> Option<Address> {
+    let key = DataKey::Factory;
+    e.storage().instance().get::<DataKey, Address>(&key)
+}
+
 // Write Factory
 pub fn write_factory(e: &Env, d: Address) {
     let key = DataKey::Factory;
@@ -78,6 +216,42 @@ pub fn write_controller(e: &Env, d: Address) {
     e.storage().instance().set(&key, &d);
 }
 
+// Read Guardian, if one is set
+pub fn read_guardian(e: &Env) -> Option<Address> {
+    let key = DataKey::Guardian;
+    e.storage().instance().get::<DataKey, Address>(&key)
+}
+
+// Write Guardian
+pub fn write_guardian(e: &Env, d: Address) {
+    let key = DataKey::Guardian;
+    e.storage().instance().set(&key, &d);
+}
+
+// Remove Guardian
+pub fn remove_guardian(e: &Env) {
+    let key = DataKey::Guardian;
+    e.storage().instance().remove(&key);
+}
+
+// Read Pending Controller
+pub fn read_pending_controller(e: &Env) -> Option<Address> {
+    let key = DataKey::PendingController;
+    e.storage().instance().get::<DataKey, Address>(&key)
+}
+
+// Write Pending Controller
+pub fn write_pending_controller(e: &Env, d: Address) {
+    let key = DataKey::PendingController;
+    e.storage().instance().set(&key, &d);
+}
+
+// Remove Pending Controller
+pub fn remove_pending_controller(e: &Env) {
+    let key = DataKey::PendingController;
+    e.storage().instance().remove(&key);
+}
+
 // Read Swap Fee
 pub fn read_swap_fee(e: &Env) -> i128 {
     let key = DataKey::SwapFee;
@@ -93,6 +267,102 @@ pub fn write_swap_fee(e: &Env, d: i128) {
     e.storage().instance().set(&key, &d)
 }
 
+// Read Exit Fee
+pub fn read_exit_fee(e: &Env) -> i128 {
+    if let Some(pending) = read_pending_exit_fee(e) {
+        if e.ledger().sequence() >= pending.ledger {
+            return pending.exit_fee;
+        }
+    }
+    let key = DataKey::ExitFee;
+    e.storage()
+        .instance()
+        .get::<DataKey, i128>(&key)
+        .unwrap_or(0)
+}
+
+// Write Exit Fee, replacing any pending increase
+pub fn write_exit_fee(e: &Env, d: i128) {
+    let key = DataKey::ExitFee;
+    e.storage().instance().set(&key, &d);
+    e.storage().instance().remove(&DataKey::PendingExitFee);
+}
+
+// Read the pending Exit Fee increase, if one was set
+pub fn read_pending_exit_fee(e: &Env) -> Option<PendingExitFee> {
+    let key = DataKey::PendingExitFee;
+    e.storage().instance().get::<DataKey, PendingExitFee>(&key)
+}
+
+// Write the pending Exit Fee increase
+pub fn write_pending_exit_fee(e: &Env, d: PendingExitFee) {
+    let key = DataKey::PendingExitFee;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read the ledger sequence of the last swap fee update
+pub fn read_swap_fee_ledger(e: &Env) -> Option<u32> {
+    let key = DataKey::SwapFeeLedger;
+    e.storage().instance().get::<DataKey, u32>(&key)
+}
+
+// Write the ledger sequence of the last swap fee update
+pub fn write_swap_fee_ledger(e: &Env, d: u32) {
+    let key = DataKey::SwapFeeLedger;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read Dynamic Fee Bounds, if the dynamic fee is enabled
+pub fn read_dynamic_fee(e: &Env) -> Option<DynamicFee> {
+    let key = DataKey::DynamicFee;
+    e.storage().instance().get::<DataKey, DynamicFee>(&key)
+}
+
+// Write Dynamic Fee Bounds
+pub fn write_dynamic_fee(e: &Env, d: DynamicFee) {
+    let key = DataKey::DynamicFee;
+    e.storage().instance().set(&key, &d)
+}
+
+// Remove Dynamic Fee Bounds
+pub fn remove_dynamic_fee(e: &Env) {
+    let key = DataKey::DynamicFee;
+    e.storage().instance().remove(&key);
+}
+
+// Read Volatility
+pub fn read_volatility(e: &Env) -> Volatility {
+    let key = DataKey::Volatility;
+    e.storage()
+        .instance()
+        .get::<DataKey, Volatility>(&key)
+        .unwrap_or_default()
+}
+
+// Write Volatility
+pub fn write_volatility(e: &Env, d: Volatility) {
+    let key = DataKey::Volatility;
+    e.storage().instance().set(&key, &d)
+}
+
+// Remove Volatility
+pub fn remove_volatility(e: &Env) {
+    let key = DataKey::Volatility;
+    e.storage().instance().remove(&key);
+}
+
+// Read Weight Schedule
+pub fn read_weight_schedule(e: &Env) -> Option<WeightSchedule> {
+    let key = DataKey::WeightSchedule;
+    e.storage().instance().get::<DataKey, WeightSchedule>(&key)
+}
+
+// Write Weight Schedule
+pub fn write_weight_schedule(e: &Env, d: WeightSchedule) {
+    let key = DataKey::WeightSchedule;
+    e.storage().instance().set(&key, &d)
+}
+
 // Read Total Shares
 pub fn get_total_shares(e: &Env) -> i128 {
     let key = DataKey::TotalShares;
@@ -114,14 +384,17 @@ pub fn put_total_shares(e: &Env, amount: i128) {
         SHARED_LIFETIME_THRESHOLD,
         SHARED_BUMP_AMOUNT,
     );
+    write_supply_checkpoint(e, amount);
 }
 
 // Read Finalize
+//
+// Pools initialized with all of their tokens are always finalized
 pub fn read_finalize(e: &Env) -> bool {
     e.storage()
         .instance()
         .get::<DataKey, bool>(&DataKey::Finalize)
-        .unwrap_optimized()
+        .unwrap_or(true)
 }
 
 // Write Finalize
@@ -134,7 +407,7 @@ pub fn read_public_swap(e: &Env) -> bool {
     e.storage()
         .instance()
         .get::<DataKey, bool>(&DataKey::PublicSwap)
-        .unwrap_optimized()
+        .unwrap_or(true)
 }
 
 // Write Public Swap
@@ -142,21 +415,105 @@ pub fn write_public_swap(e: &Env, val: bool) {
     e.storage().instance().set(&DataKey::PublicSwap, &val)
 }
 
-// Read status of the pool
-pub fn read_freeze(e: &Env) -> bool {
-    let key = DataKey::Freeze;
+// Read whether a flash loan is in progress
+pub fn read_flash_loan_lock(e: &Env) -> bool {
+    let key = DataKey::FlashLoanLock;
     e.storage()
         .instance()
         .get::<DataKey, bool>(&key)
         .unwrap_or(false)
 }
 
-// Write status of the pool
-pub fn write_freeze(e: &Env, d: bool) {
-    let key = DataKey::Freeze;
+// Write whether a flash loan is in progress
+pub fn write_flash_loan_lock(e: &Env, d: bool) {
+    let key = DataKey::FlashLoanLock;
     e.storage().instance().set(&key, &d)
 }
 
+// Read the storage layout version, pools deployed before
+// storage versioning have version 0
+pub fn read_storage_version(e: &Env) -> u32 {
+    let key = DataKey::StorageVersion;
+    e.storage()
+        .instance()
+        .get::<DataKey, u32>(&key)
+        .unwrap_or(0)
+}
+
+// Write the storage layout version
+pub fn write_storage_version(e: &Env, d: u32) {
+    let key = DataKey::StorageVersion;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read the delay in seconds between proposing and executing an upgrade,
+// never less than MIN_UPGRADE_DELAY
+pub fn read_upgrade_delay(e: &Env) -> u64 {
+    let key = DataKey::UpgradeDelay;
+    e.storage()
+        .instance()
+        .get::<DataKey, u64>(&key)
+        .unwrap_or(0)
+        .max(MIN_UPGRADE_DELAY)
+}
+
+// Write the upgrade delay
+pub fn write_upgrade_delay(e: &Env, d: u64) {
+    let key = DataKey::UpgradeDelay;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read the pending upgrade, if one has been proposed
+pub fn read_pending_upgrade(e: &Env) -> Option<PendingUpgrade> {
+    let key = DataKey::PendingUpgrade;
+    e.storage().instance().get::<DataKey, PendingUpgrade>(&key)
+}
+
+// Write the pending upgrade
+pub fn write_pending_upgrade(e: &Env, d: PendingUpgrade) {
+    let key = DataKey::PendingUpgrade;
+    e.storage().instance().set(&key, &d)
+}
+
+// Remove the pending upgrade
+pub fn remove_pending_upgrade(e: &Env) {
+    let key = DataKey::PendingUpgrade;
+    e.storage().instance().remove(&key)
+}
+
+// Read the bitmask of paused operations
+pub fn read_pause_flags(e: &Env) -> u32 {
+    let key = DataKey::PauseFlags;
+    e.storage()
+        .instance()
+        .get::<DataKey, u32>(&key)
+        .unwrap_or(0)
+}
+
+// Write the bitmask of paused operations
+pub fn write_pause_flags(e: &Env, d: u32) {
+    let key = DataKey::PauseFlags;
+    e.storage().instance().set(&key, &d)
+}
+
+// Read the timestamp from which withdrawals can be paused, if a pause has been proposed
+pub fn read_withdraw_pause_eta(e: &Env) -> Option<u64> {
+    let key = DataKey::WithdrawPauseEta;
+    e.storage().instance().get::<DataKey, u64>(&key)
+}
+
+// Write the timestamp from which withdrawals can be paused
+pub fn write_withdraw_pause_eta(e: &Env, d: u64) {
+    let key = DataKey::WithdrawPauseEta;
+    e.storage().instance().set(&key, &d)
+}
+
+// Remove the proposed withdrawal pause
+pub fn remove_withdraw_pause_eta(e: &Env) {
+    let key = DataKey::WithdrawPauseEta;
+    e.storage().instance().remove(&key)
+}
+
 pub fn read_decimal(e: &Env) -> u32 {
     let util = TokenUtils::new(e);
     util.metadata().get_metadata().decimal
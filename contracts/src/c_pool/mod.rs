@@ -8,6 +8,10 @@ pub mod balance;
 pub mod comet;
 // Events Module
 pub mod event;
+// Factory Contract Interface
+pub mod factory_interface;
+// Flash Loan Receiver Interface
+pub mod flash_loan_receiver;
 // Contract Storage Read and Write Module
 pub mod metadata;
 // Definition of the DataKeys for the Contract's Storage
@@ -1,5 +1,5 @@
 //! Declaration of the Storage Keys
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, BytesN, Vec};
 
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
 
@@ -19,20 +19,114 @@ pub struct Record {
     pub index: u32,
 }
 
+// Linear Weight Change Schedule, weights are ordered like `AllTokenVec`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeightSchedule {
+    pub start_ledger: u32,
+    pub end_ledger: u32,
+    pub start_weights: Vec<i128>,
+    pub end_weights: Vec<i128>,
+}
+
+// Protocol Fee Settings, cached from the Factory Contract
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolFee {
+    pub fee_recipient: Address,
+    pub protocol_fee: i128,
+}
+
+// Time Weighted Price Accumulators, `price_cumulatives` holds the spot price
+// of token `j` in token `i` summed over time for every pair `i != j`, at the
+// index given by `pair_index`, with tokens ordered like `AllTokenVec`. The sums
+// wrap on overflow like their differences
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Observation {
+    pub timestamp: u64,
+    pub price_cumulatives: Vec<i128>,
+}
+
+// Position of the Observation Ring Buffer
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OracleState {
+    pub index: u32,
+    pub count: u32,
+    pub last_timestamp: u64,
+}
+
+// Upgrade Proposal, the pool can be upgraded to `wasm_hash`
+// once the ledger timestamp reaches `eta`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub eta: u64,
+}
+
+// Dynamic Swap Fee Bounds, the effective swap fee moves from `min_fee`
+// to `max_fee` as the recent volatility grows
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DynamicFee {
+    pub min_fee: i128,
+    pub max_fee: i128,
+}
+
+// Accumulated relative spot price movement of recent swaps,
+// decaying linearly to zero over VOLATILITY_DECAY_LEDGERS
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Volatility {
+    pub volatility: i128,
+    pub last_ledger: u32,
+}
+
+// Exit Fee Increase, `exit_fee` is charged from ledger `ledger` on
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingExitFee {
+    pub exit_fee: i128,
+    pub ledger: u32,
+}
+
 // Data Keys for Pool' Storage Data
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
-    Factory,       // Address of the Factory Contract
-    Controller,    // Address of the Controller Account
-    SwapFee,       // i128
-    AllTokenVec,   // Vec<Address>
-    AllRecordData, // Map<Address, Record>
-    TokenShare,    // Address
-    TotalShares,   // i128
-    PublicSwap,    // bool
-    Finalize,      // bool
-    Freeze,        // bool
+    Factory,           // Address of the Factory Contract
+    Controller,        // Address of the Controller Account
+    SwapFee,           // i128
+    AllTokenVec,       // Vec<Address>
+    AllRecordData,     // Map<Address, Record>, replaced by Record in storage version 1
+    TokenShare,        // Address
+    TotalShares,       // i128
+    PublicSwap,        // bool
+    Finalize,          // bool
+    Freeze,            // bool, replaced by PauseFlags in storage version 1
+    SwapFeeLedger,     // u32
+    WeightSchedule,    // WeightSchedule
+    PendingController, // Address
+    ProtocolFees,      // Map<Address, i128>
+    ExitFee,           // i128
+    PriceCumulative,   // Observation
+    OracleState,       // OracleState
+    Observation(u32),  // Observation
+    FlashLoanLock,     // bool
+    StorageVersion,    // u32
+    UpgradeDelay,      // u64
+    PendingUpgrade,    // PendingUpgrade
+    PauseFlags,        // u32
+    WithdrawPauseEta,  // u64
+    Guardian,          // Address of the Guardian Account
+    DynamicFee,        // DynamicFee
+    Volatility,        // Volatility
+    Record(Address),   // Record
+    ProtocolFee,       // ProtocolFee
+    PendingExitFee,    // PendingExitFee
+    WeightedBalances,  // Vec<i128>, balance per unit of weight ordered like `AllTokenVec`
 }
 
 // Data Keys for the LP Token
@@ -44,6 +138,12 @@ pub enum DataKeyToken {
     Nonce(Address),
     State(Address),
     Admin,
+    CheckpointCount(Address),
+    Checkpoint(Address, u32),
+    SupplyCheckpointCount,
+    SupplyCheckpoint(u32),
+    PermitKey(Address),
+    HistoryStart,
 }
 
 #[derive(Clone)]
@@ -58,3 +158,11 @@ pub struct AllowanceValue {
     pub amount: i128,
     pub expiration_ledger: u32,
 }
+
+// LP Token Balance of an Account, or Total Supply, from `ledger` on
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpoint {
+    pub ledger: u32,
+    pub amount: i128,
+}
@@ -46,6 +46,17 @@ pub fn pull_shares(e: &Env, from: &Address, amount: i128) {
         .transfer(from.clone(), contract_address, amount);
 }
 
+// Transfer the LP Tokens from the contract Address to the given 'to' Address
+pub fn push_shares(e: &Env, to: &Address, amount: i128) {
+    let contract_address = e.current_contract_address();
+    check_nonnegative_amount(amount);
+    spend_balance(e, contract_address.clone(), amount);
+    receive_balance(e, to.clone(), amount);
+    TokenUtils::new(e)
+        .events()
+        .transfer(contract_address, to.clone(), amount);
+}
+
 // Burn the LP Tokens
 pub fn burn_shares(e: &Env, amount: i128) {
     let total = get_total_shares(e);
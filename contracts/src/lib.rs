@@ -2,7 +2,6 @@
 
 pub mod c_consts;
 pub mod c_math;
-pub mod c_num;
 pub mod c_pool;
 
 #[cfg(test)]
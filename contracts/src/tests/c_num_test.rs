@@ -1,31 +1,23 @@
 #![cfg(test)]
 extern crate std;
+use math::{c_consts::BONE, c_num::c_pow, I256};
 use soroban_sdk::Env;
-use soroban_sdk::I256;
 
-use crate::c_consts::BONE;
-use crate::c_num::c_pow;
+use crate::c_math::unwrap_math;
 
 #[test]
 #[should_panic = "Error(Contract, #34)"]
 fn test_c_pow_low() {
     let env: Env = Env::default();
-    c_pow(
-        &env,
-        &I256::from_i32(&env, 0),
-        &I256::from_i32(&env, 2),
-        false,
-    );
+    unwrap_math(&env, c_pow(&I256::from_i128(0), &I256::from_i128(2), false));
 }
 
 #[test]
 #[should_panic = "Error(Contract, #35)"]
 fn test_c_pow_high() {
     let env: Env = Env::default();
-    c_pow(
+    unwrap_math(
         &env,
-        &I256::from_i128(&env, 2 * BONE),
-        &I256::from_i32(&env, 2),
-        false,
+        c_pow(&I256::from_i128(2 * BONE), &I256::from_i128(2), false),
     );
 }
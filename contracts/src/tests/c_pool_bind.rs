@@ -0,0 +1,153 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Error};
+
+use crate::{
+    c_consts::STROOP,
+    c_pool::{
+        comet::{CometPoolContract, CometPoolContractClient},
+        error::Error as CometError,
+    },
+    tests::utils::create_stellar_token,
+};
+
+#[test]
+fn test_bind_rebind_unbind_finalize() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_3 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    let token_3_client = MockTokenClient::new(&env, &token_3);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_3_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = env.register_contract(None, CometPoolContract);
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.init_unfinalized(&controller, &0_0030000);
+    assert!(!comet.is_finalized());
+    assert!(!comet.is_public_swap());
+    assert_eq!(comet.get_tokens().len(), 0);
+    assert_eq!(comet.get_total_supply(), 0);
+
+    // bind tokens
+    comet.bind(&token_1, &(10 * STROOP), &0_3000000);
+    comet.bind(&token_2, &(20 * STROOP), &0_3000000);
+    comet.bind(&token_3, &(30 * STROOP), &0_2000000);
+    assert_eq!(
+        comet.get_tokens(),
+        vec![&env, token_1.clone(), token_2.clone(), token_3.clone()]
+    );
+    assert_eq!(token_3_client.balance(&comet_id), 30 * STROOP);
+    assert_eq!(token_3_client.balance(&controller), 70 * STROOP);
+
+    let result = comet.try_bind(&token_1, &(10 * STROOP), &0_1000000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrIsBound as u32
+        )))
+    );
+
+    // validates total weight does not exceed 1
+    let result = comet.try_rebind(&token_3, &(30 * STROOP), &0_4000001);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrTotalWeight as u32
+        )))
+    );
+
+    // pool is not usable until it is finalized
+    let result = comet.try_swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrSwapNotPublic as u32
+        )))
+    );
+    let result = comet.try_join_pool(&STROOP, &vec![&env, i128::MAX, i128::MAX, i128::MAX], &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNotFinalized as u32
+        )))
+    );
+    let result = comet.try_finalize();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrTotalWeight as u32
+        )))
+    );
+
+    // rebind settles the balance difference with the controller
+    comet.rebind(&token_2, &(5 * STROOP), &0_4000000);
+    assert_eq!(comet.get_balance(&token_2), 5 * STROOP);
+    assert_eq!(comet.get_normalized_weight(&token_2), 0_4000000);
+    assert_eq!(token_2_client.balance(&controller), 95 * STROOP);
+    comet.rebind(&token_2, &(25 * STROOP), &0_4000000);
+    assert_eq!(token_2_client.balance(&controller), 75 * STROOP);
+
+    // unbind moves the last token into the unbound index
+    comet.unbind(&token_1);
+    assert_eq!(
+        comet.get_tokens(),
+        vec![&env, token_3.clone(), token_2.clone()]
+    );
+    assert_eq!(token_1_client.balance(&controller), 100 * STROOP);
+    assert_eq!(token_1_client.balance(&comet_id), 0);
+    let result = comet.try_unbind(&token_1);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNotBound as u32
+        )))
+    );
+    comet.rebind(&token_3, &(30 * STROOP), &0_6000000);
+    assert_eq!(comet.get_weights(), vec![&env, 0_6000000, 0_4000000]);
+
+    // finalize
+    comet.finalize();
+    assert!(comet.is_finalized());
+    assert!(comet.is_public_swap());
+    assert_eq!(comet.get_total_supply(), 100 * STROOP);
+    assert_eq!(comet.balance(&controller), 100 * STROOP);
+
+    let result = comet.try_finalize();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFinalized as u32
+        )))
+    );
+    let result = comet.try_bind(&token_1, &(10 * STROOP), &0_1000000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFinalized as u32
+        )))
+    );
+    let result = comet.try_unbind(&token_2);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFinalized as u32
+        )))
+    );
+
+    // pool is usable after it is finalized
+    comet.exit_pool(&(10 * STROOP), &vec![&env, 0, 0], &controller);
+    assert_eq!(comet.get_balance(&token_3), 27 * STROOP);
+    assert_eq!(comet.get_balance(&token_2), 22_5000000);
+    assert_eq!(token_2_client.balance(&controller), 77_5000000);
+}
@@ -0,0 +1,96 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::{INIT_POOL_SUPPLY, STROOP},
+    c_pool::{comet::CometPoolContractClient, error::ErrorExt},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_balance_checkpoints() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let user_1 = Address::generate(&env);
+    let user_2 = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user_1, &(100 * STROOP));
+    token_2_client.mint(&user_1, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    // ledger 110: user_1 joins twice, only the last balance of the ledger is kept
+    env.ledger().with_mut(|li| li.sequence_number = 110);
+    let max_in = vec![&env, i128::MAX, i128::MAX];
+    comet.join_pool(&(5 * STROOP), &max_in, &user_1);
+    comet.join_pool(&(5 * STROOP), &max_in, &user_1);
+
+    // ledger 120: user_1 sends part of their shares to user_2
+    env.ledger().with_mut(|li| li.sequence_number = 120);
+    comet.transfer(&user_1, &user_2, &(4 * STROOP));
+
+    // ledger 130: user_2 exits the pool
+    env.ledger().with_mut(|li| li.sequence_number = 130);
+    comet.exit_pool(&(4 * STROOP), &vec![&env, 0, 0], &user_2);
+
+    // the current ledger can still change, so only closed ledgers can be read
+    let result = comet.try_balance_at(&user_2, &130);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrLedgerNotFinal as u32
+        )))
+    );
+    let result = comet.try_total_supply_at(&131);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrLedgerNotFinal as u32
+        )))
+    );
+
+    env.ledger().with_mut(|li| li.sequence_number = 1001);
+    assert_eq!(comet.balance_at(&user_1, &99), 0);
+    assert_eq!(comet.balance_at(&user_1, &109), 0);
+    assert_eq!(comet.balance_at(&user_1, &110), 10 * STROOP);
+    assert_eq!(comet.balance_at(&user_1, &119), 10 * STROOP);
+    assert_eq!(comet.balance_at(&user_1, &120), 6 * STROOP);
+    assert_eq!(comet.balance_at(&user_1, &1000), 6 * STROOP);
+
+    assert_eq!(comet.balance_at(&user_2, &119), 0);
+    assert_eq!(comet.balance_at(&user_2, &120), 4 * STROOP);
+    assert_eq!(comet.balance_at(&user_2, &129), 4 * STROOP);
+    assert_eq!(comet.balance_at(&user_2, &130), 0);
+
+    assert_eq!(comet.balance_at(&controller, &100), INIT_POOL_SUPPLY);
+    assert_eq!(comet.balance_at(&controller, &130), INIT_POOL_SUPPLY);
+
+    assert_eq!(comet.total_supply_at(&99), 0);
+    assert_eq!(comet.total_supply_at(&100), INIT_POOL_SUPPLY);
+    assert_eq!(comet.total_supply_at(&110), INIT_POOL_SUPPLY + 10 * STROOP);
+    assert_eq!(comet.total_supply_at(&129), INIT_POOL_SUPPLY + 10 * STROOP);
+    assert_eq!(comet.total_supply_at(&130), INIT_POOL_SUPPLY + 6 * STROOP);
+    assert_eq!(comet.total_supply_at(&130), comet.get_total_supply());
+}
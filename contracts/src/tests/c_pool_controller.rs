@@ -0,0 +1,101 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, Error, IntoVal, Symbol,
+};
+
+use crate::{
+    c_consts::STROOP,
+    c_pool::{comet::CometPoolContractClient, error::Error as CometError},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_controller_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let new_controller = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    MockTokenClient::new(&env, &token_1).mint(&controller, &STROOP);
+    MockTokenClient::new(&env, &token_2).mint(&controller, &STROOP);
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, STROOP, STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    assert_eq!(comet.get_pending_controller(), None);
+
+    // nothing to accept or cancel
+    let result = comet.try_accept_controller();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNoPendingController as u32
+        )))
+    );
+    let result = comet.try_cancel_controller();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNoPendingController as u32
+        )))
+    );
+
+    // propose and cancel
+    comet.propose_controller(&new_controller);
+    assert_eq!(
+        env.auths()[0],
+        (
+            controller.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    comet_id.clone(),
+                    Symbol::new(&env, "propose_controller"),
+                    vec![&env, new_controller.into_val(&env)],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+    assert_eq!(comet.get_pending_controller(), Some(new_controller.clone()));
+    assert_eq!(comet.get_controller(), controller);
+
+    comet.cancel_controller();
+    assert_eq!(comet.get_pending_controller(), None);
+    assert_eq!(comet.get_controller(), controller);
+
+    // propose and accept
+    comet.propose_controller(&new_controller);
+    comet.accept_controller();
+    assert_eq!(
+        env.auths()[0],
+        (
+            new_controller.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    comet_id.clone(),
+                    Symbol::new(&env, "accept_controller"),
+                    vec![&env],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+    assert_eq!(comet.get_pending_controller(), None);
+    assert_eq!(comet.get_controller(), new_controller);
+
+    // the new controller holds the admin role
+    comet.set_freeze_status(&true);
+    assert_eq!(env.auths()[0].0, new_controller);
+}
@@ -0,0 +1,209 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::{MAX_FEE, MIN_FEE_UPDATE_DELAY, STROOP, VOLATILITY_DECAY_LEDGERS},
+    c_pool::{
+        comet::CometPoolContractClient,
+        error::{Error as CometError, ErrorExt},
+        storage_types::DynamicFee,
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_dynamic_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+    token_2_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let result = comet.try_set_dynamic_fee(&0_0100000, &0_0050000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrInvalidDynamicFee as u32
+        )))
+    );
+    let result = comet.try_set_dynamic_fee(&0_0010000, &(MAX_FEE + 1));
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(CometError::ErrMaxFee as u32)))
+    );
+    // the bounds start from the fixed swap fee and move by at most MAX_FEE_DELTA
+    let result = comet.try_set_dynamic_fee(&0_0010000, &0_0140000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxFeeDelta as u32
+        )))
+    );
+
+    // without recent swaps the floor is charged
+    comet.set_dynamic_fee(&0_0010000, &0_0100000);
+    assert_eq!(env.auths()[0].0, controller);
+    assert_eq!(
+        comet.get_dynamic_fee(),
+        Some(DynamicFee {
+            min_fee: 0_0010000,
+            max_fee: 0_0100000,
+        })
+    );
+    assert_eq!(comet.get_swap_fee(), 0_0010000);
+    let result = comet.try_set_dynamic_fee(&0_0010000, &0_0200000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFeeUpdateTooSoon as u32
+        )))
+    );
+    let quote = comet.quote_swap_exact_amount_in(&token_1, &STROOP, &token_2);
+
+    // the swap is charged the fee from before it moved the price
+    let (amount_out, _) =
+        comet.swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(amount_out, quote.0);
+
+    // a large swap raises the fee towards the ceiling
+    let fee_after_small_swap = comet.get_swap_fee();
+    assert!(fee_after_small_swap > 0_0010000);
+    comet.swap_exact_amount_in(&token_1, &(10 * STROOP), &token_2, &0, &i128::MAX, &user);
+    let fee_after_large_swap = comet.get_swap_fee();
+    assert!(fee_after_large_swap > fee_after_small_swap);
+    assert!(fee_after_large_swap <= 0_0100000);
+    let quote = comet.quote_swap_exact_amount_in(&token_2, &STROOP, &token_1);
+    let (amount_out, _) =
+        comet.swap_exact_amount_in(&token_2, &STROOP, &token_1, &0, &i128::MAX, &user);
+    assert_eq!(amount_out, quote.0);
+
+    // and decays back to the floor
+    let fee = comet.get_swap_fee();
+    env.ledger()
+        .with_mut(|li| li.sequence_number = 100 + VOLATILITY_DECAY_LEDGERS / 2);
+    let half_decayed_fee = comet.get_swap_fee();
+    assert!(half_decayed_fee < fee);
+    assert!(half_decayed_fee > 0_0010000);
+    env.ledger()
+        .with_mut(|li| li.sequence_number = 100 + VOLATILITY_DECAY_LEDGERS);
+    assert_eq!(comet.get_swap_fee(), 0_0010000);
+
+    // disabling it charges the fixed swap fee again
+    comet.swap_exact_amount_in(&token_1, &(10 * STROOP), &token_2, &0, &i128::MAX, &user);
+    comet.disable_dynamic_fee();
+    assert_eq!(comet.get_dynamic_fee(), None);
+    assert_eq!(comet.get_swap_fee(), 0_0030000);
+    let result = comet.try_disable_dynamic_fee();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrInvalidDynamicFee as u32
+        )))
+    );
+
+    // re-enabling starts without the old volatility
+    let result = comet.try_set_dynamic_fee(&0_0010000, &0_0100000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFeeUpdateTooSoon as u32
+        )))
+    );
+    env.ledger()
+        .with_mut(|li| li.sequence_number = 100 + VOLATILITY_DECAY_LEDGERS + MIN_FEE_UPDATE_DELAY);
+    comet.set_dynamic_fee(&0_0010000, &0_0100000);
+    assert_eq!(comet.get_swap_fee(), 0_0010000);
+
+    // the fixed swap fee has to be close to both bounds to disable it
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 100 + VOLATILITY_DECAY_LEDGERS + 2 * MIN_FEE_UPDATE_DELAY
+    });
+    comet.set_dynamic_fee(&0_0010000, &0_0200000);
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 100 + VOLATILITY_DECAY_LEDGERS + 3 * MIN_FEE_UPDATE_DELAY
+    });
+    let result = comet.try_disable_dynamic_fee();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxFeeDelta as u32
+        )))
+    );
+}
+
+#[test]
+fn test_dynamic_fee_single_sided() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.set_dynamic_fee(&0_0010000, &0_0100000);
+    assert_eq!(comet.get_swap_fee(), 0_0010000);
+
+    // a single-sided deposit moves the price like a swap into the pool
+    let pool_amount_out =
+        comet.dep_tokn_amt_in_get_lp_tokns_out(&token_1, &(10 * STROOP), &0, &user);
+    let fee_after_deposit = comet.get_swap_fee();
+    assert!(fee_after_deposit > 0_0010000);
+    assert!(fee_after_deposit <= 0_0100000);
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number = 100 + VOLATILITY_DECAY_LEDGERS);
+    assert_eq!(comet.get_swap_fee(), 0_0010000);
+
+    // and a single-sided withdrawal like a swap out of it
+    comet.wdr_tokn_amt_in_get_lp_tokns_out(&token_1, &pool_amount_out, &0, &user);
+    let fee_after_withdrawal = comet.get_swap_fee();
+    assert!(fee_after_withdrawal > 0_0010000);
+    assert!(fee_after_withdrawal <= 0_0100000);
+
+    comet.dep_lp_tokn_amt_out_get_tokn_in(&token_1, &pool_amount_out, &i128::MAX, &user);
+    assert!(comet.get_swap_fee() > fee_after_withdrawal);
+    comet.wdr_tokn_amt_out_get_lp_tokns_in(&token_1, &(5 * STROOP), &i128::MAX, &user);
+    assert!(comet.get_swap_fee() > fee_after_withdrawal);
+}
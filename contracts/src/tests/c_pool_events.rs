@@ -0,0 +1,123 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events},
+    vec, Address, Env, Symbol, TryFromVal, Val,
+};
+
+use crate::{
+    c_consts::{EVENT_VERSION, STROOP},
+    c_pool::{
+        comet::CometPoolContractClient,
+        event::{DepositEvent, JoinEvent, SwapEvent, SyncEvent, WithdrawEvent},
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+// Get the data of the last event published by `pool` with `topic`
+fn last_event<T: TryFromVal<Env, Val>>(env: &Env, pool: &Address, topic: Symbol) -> T {
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract, topics, _)| {
+            contract == pool
+                && Symbol::try_from_val(env, &topics.get_unchecked(1)) == Ok(topic.clone())
+        })
+        .last()
+        .unwrap();
+    T::try_from_val(env, &data).unwrap()
+}
+
+#[test]
+fn test_events_carry_pool_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+    token_2_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    // the initial state is reported by the pool
+    let event: SyncEvent = last_event(&env, &comet_id, symbol_short!("sync"));
+    assert_eq!(event.balances, vec![&env, 100 * STROOP, 100 * STROOP]);
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    comet.join_pool(&STROOP, &vec![&env, i128::MAX, i128::MAX], &user);
+    let event: JoinEvent = last_event(&env, &comet_id, symbol_short!("join_pool"));
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.token_in, token_2);
+    assert_eq!(event.pool_amount_out, STROOP);
+    assert_eq!(event.balance, comet.get_balance(&token_2));
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    let (amount_out, spot_price_after) =
+        comet.swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    let event: SwapEvent = last_event(&env, &comet_id, symbol_short!("swap"));
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.token_amount_out, amount_out);
+    assert_eq!(event.fee_amount, STROOP * 30 / 10000);
+    assert_eq!(event.spot_price_after, spot_price_after);
+    assert_eq!(event.balance_in, comet.get_balance(&token_1));
+    assert_eq!(event.balance_out, comet.get_balance(&token_2));
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    let pool_amount_out = comet.dep_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    let event: DepositEvent = last_event(&env, &comet_id, symbol_short!("deposit"));
+    assert_eq!(event.pool_amount_out, pool_amount_out);
+    // half of the deposit is traded for token_2
+    assert_eq!(event.fee_amount, STROOP / 2 * 30 / 10000);
+    assert_eq!(event.balance, comet.get_balance(&token_1));
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    let amount_out = comet.wdr_tokn_amt_in_get_lp_tokns_out(&token_2, &STROOP, &0, &user);
+    let event: WithdrawEvent = last_event(&env, &comet_id, symbol_short!("withdraw"));
+    assert_eq!(event.token_amount_out, amount_out);
+    assert!(event.fee_amount > 0);
+    assert_eq!(event.exit_fee_amount, 0);
+    assert_eq!(event.balance, comet.get_balance(&token_2));
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    // tokens sent to the pool are reported once gulped
+    token_1_client.mint(&comet_id, &STROOP);
+    comet.gulp(&token_1);
+    let event: SyncEvent = last_event(&env, &comet_id, symbol_short!("sync"));
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.tokens, vec![&env, token_1.clone(), token_2.clone()]);
+    assert_eq!(
+        event.balances,
+        vec![
+            &env,
+            comet.get_balance(&token_1),
+            comet.get_balance(&token_2)
+        ]
+    );
+    assert_eq!(event.weights, vec![&env, 0_5000000, 0_5000000]);
+    assert_eq!(event.swap_fee, 0_0030000);
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    // and after admin changes
+    comet.set_dynamic_fee(&0_0010000, &0_0100000);
+    let event: SyncEvent = last_event(&env, &comet_id, symbol_short!("sync"));
+    assert_eq!(event.swap_fee, 0_0010000);
+}
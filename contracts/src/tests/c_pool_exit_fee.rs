@@ -0,0 +1,139 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::{MIN_FEE_UPDATE_DELAY, STROOP, STROOP_SCALAR},
+    c_math,
+    c_pool::{
+        comet::CometPoolContractClient,
+        error::Error as CometError,
+        storage_types::{PendingExitFee, Record},
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_exit_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+
+    let swap_fee = 0_0030000;
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        swap_fee,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.transfer(&controller, &user, &(20 * STROOP));
+    assert_eq!(comet.get_exit_fee(), 0);
+
+    // validates the exit fee
+    let result = comet.try_set_exit_fee(&-1);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNegative as u32
+        )))
+    );
+    let result = comet.try_set_exit_fee(&0_0100001);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxExitFee as u32
+        )))
+    );
+
+    // increases only apply after the update delay
+    let exit_fee = 0_0100000;
+    comet.set_exit_fee(&exit_fee);
+    assert_eq!(env.auths()[0].0, controller);
+    assert_eq!(comet.get_exit_fee(), 0);
+    assert_eq!(
+        comet.get_pending_exit_fee(),
+        Some(PendingExitFee {
+            exit_fee,
+            ledger: MIN_FEE_UPDATE_DELAY,
+        })
+    );
+    env.ledger()
+        .with_mut(|li| li.sequence_number = MIN_FEE_UPDATE_DELAY - 1);
+    assert_eq!(comet.get_exit_fee(), 0);
+    env.ledger()
+        .with_mut(|li| li.sequence_number = MIN_FEE_UPDATE_DELAY);
+    assert_eq!(comet.get_exit_fee(), exit_fee);
+
+    // exit pool keeps 1% of the LP tokens and burns the rest
+    comet.exit_pool(&(10 * STROOP), &vec![&env, 0, 0], &user);
+    assert_eq!(comet.balance(&user), 10 * STROOP);
+    assert_eq!(comet.balance(&controller), 80_1000000);
+    assert_eq!(comet.get_total_supply(), 90_1000000);
+    assert_eq!(token_1_client.balance(&user), 9_9000000);
+    assert_eq!(token_2_client.balance(&user), 9_9000000);
+    assert_eq!(comet.get_balance(&token_1), 90_1000000);
+
+    // single sided withdrawal given the LP tokens burned
+    let record = Record {
+        balance: comet.get_balance(&token_1),
+        weight: 0_5000000,
+        scalar: STROOP_SCALAR,
+        index: 0,
+    };
+    let supply = comet.get_total_supply();
+    let expected_amount_out = c_math::calc_token_withdrawal_amount_given_lp_token_amount(
+        &env, &record, supply, 0_9900000, swap_fee, 0,
+    );
+    let token_amount_out = comet.wdr_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    assert_eq!(token_amount_out, expected_amount_out);
+    assert_eq!(comet.balance(&user), 9 * STROOP);
+    assert_eq!(comet.balance(&controller), 80_1100000);
+    assert_eq!(comet.get_total_supply(), supply - 0_9900000);
+
+    // single sided withdrawal given the tokens withdrawn
+    let record = Record {
+        balance: comet.get_balance(&token_2),
+        weight: 0_5000000,
+        scalar: STROOP_SCALAR,
+        index: 1,
+    };
+    let supply = comet.get_total_supply();
+    let pool_amount_in_sans_exit_fee = c_math::calc_lp_token_amount_given_token_withdrawal_amount(
+        &env, &record, supply, STROOP, swap_fee, 0,
+    );
+    let pool_amount_in =
+        comet.wdr_tokn_amt_out_get_lp_tokns_in(&token_2, &STROOP, &i128::MAX, &user);
+    let exit_fee_amount = pool_amount_in * exit_fee / STROOP;
+    assert!(pool_amount_in > pool_amount_in_sans_exit_fee);
+    assert!(pool_amount_in - exit_fee_amount >= pool_amount_in_sans_exit_fee);
+    assert_eq!(comet.balance(&user), 9 * STROOP - pool_amount_in);
+    assert_eq!(comet.balance(&controller), 80_1100000 + exit_fee_amount);
+    assert_eq!(
+        comet.get_total_supply(),
+        supply - pool_amount_in + exit_fee_amount
+    );
+
+    // removing the exit fee applies at once and burns all exiting LP tokens
+    comet.set_exit_fee(&0);
+    assert_eq!(comet.get_exit_fee(), 0);
+    assert_eq!(comet.get_pending_exit_fee(), None);
+    let supply = comet.get_total_supply();
+    comet.exit_pool(&STROOP, &vec![&env, 0, 0], &user);
+    assert_eq!(comet.get_total_supply(), supply - STROOP);
+}
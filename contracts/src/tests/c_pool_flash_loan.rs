@@ -0,0 +1,192 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, Events, MockAuth, MockAuthInvoke},
+    token::TokenClient,
+    vec, Address, Bytes, Env, Error, IntoVal, Symbol, TryFromVal,
+};
+
+use crate::{
+    c_consts::{EVENT_VERSION, STROOP},
+    c_pool::{comet::CometPoolContractClient, error::Error as CometError, event::FlashLoanEvent},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[contract]
+pub struct FlashLoanReceiverContract;
+
+#[contractimpl]
+impl FlashLoanReceiverContract {
+    // Repays the loan and the fee, or only the loan if `data` is not empty
+    pub fn on_flash_loan(
+        e: Env,
+        _initiator: Address,
+        pool: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        data: Bytes,
+    ) {
+        let repay = if data.is_empty() {
+            amount + fee
+        } else {
+            amount
+        };
+        TokenClient::new(&e, &token).transfer(&e.current_contract_address(), &pool, &repay);
+    }
+}
+
+#[test]
+fn test_flash_loan() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let initiator = Address::generate(&env);
+    let receiver = env.register_contract(None, FlashLoanReceiverContract);
+    token_1_client.mint(&receiver, &STROOP);
+
+    // the fee is added to the pool balance
+    comet.flash_loan(
+        &initiator,
+        &receiver,
+        &token_1,
+        &(50 * STROOP),
+        &Bytes::new(&env),
+    );
+    assert_eq!(env.auths()[0].0, initiator);
+    assert_eq!(comet.get_balance(&token_1), 100_1500000);
+    assert_eq!(token_1_client.balance(&comet_id), 100_1500000);
+    assert_eq!(token_1_client.balance(&receiver), 0_8500000);
+    assert_eq!(comet.get_balance(&token_2), 100 * STROOP);
+
+    // tokens sent to the pool before the loan are kept in its balance,
+    // and the event reports the balance after the loan
+    token_1_client.mint(&comet_id, &STROOP);
+    comet.flash_loan(&initiator, &receiver, &token_1, &STROOP, &Bytes::new(&env));
+    assert_eq!(comet.get_balance(&token_1), 101_1530000);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(
+        Symbol::try_from_val(&env, &topics.get_unchecked(1)),
+        Ok(symbol_short!("flashloan"))
+    );
+    let event = FlashLoanEvent::try_from_val(&env, &data).unwrap();
+    assert_eq!(event.version, EVENT_VERSION);
+    assert_eq!(event.fee, 0_0030000);
+    assert_eq!(event.balance, comet.get_balance(&token_1));
+    assert_eq!(event.total_supply, comet.get_total_supply());
+
+    // the loan has to be repaid with the fee
+    let result = comet.try_flash_loan(
+        &initiator,
+        &receiver,
+        &token_1,
+        &(50 * STROOP),
+        &Bytes::from_array(&env, &[1]),
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFlashLoanNotRepaid as u32
+        )))
+    );
+
+    let result = comet.try_flash_loan(
+        &initiator,
+        &receiver,
+        &token_2,
+        &(101 * STROOP),
+        &Bytes::new(&env),
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrInsufficientBalance as u32
+        )))
+    );
+
+    comet.set_freeze_status(&true);
+    let result = comet.try_flash_loan(&initiator, &receiver, &token_1, &STROOP, &Bytes::new(&env));
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+}
+
+#[test]
+fn test_flash_loan_contract_receiver() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let initiator = Address::generate(&env);
+    let receiver = env.register_contract(None, FlashLoanReceiverContract);
+    token_1_client.mint(&receiver, &STROOP);
+
+    // only the initiator signs, the receiver repays as an ordinary contract
+    let data = Bytes::new(&env);
+    env.mock_auths(&[MockAuth {
+        address: &initiator,
+        invoke: &MockAuthInvoke {
+            contract: &comet_id,
+            fn_name: "flash_loan",
+            args: (
+                initiator.clone(),
+                receiver.clone(),
+                token_1.clone(),
+                50 * STROOP,
+                data.clone(),
+            )
+                .into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    comet.flash_loan(&initiator, &receiver, &token_1, &(50 * STROOP), &data);
+    assert_eq!(comet.get_balance(&token_1), 100_1500000);
+    assert_eq!(token_1_client.balance(&comet_id), 100_1500000);
+    assert_eq!(token_1_client.balance(&receiver), 0_8500000);
+
+    // the initiator has to authorize the loan
+    let result = comet.try_flash_loan(&initiator, &receiver, &token_1, &(50 * STROOP), &data);
+    assert!(result.is_err());
+}
@@ -0,0 +1,147 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
+    vec, Address, Env, Error, IntoVal,
+};
+
+use crate::{
+    c_consts::{
+        PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP, PAUSE_WITHDRAW, STROOP, WITHDRAW_PAUSE_DELAY,
+    },
+    c_pool::{
+        comet::CometPoolContractClient,
+        error::{Error as CometError, ErrorExt},
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_guardian() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let guardian = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let result = comet.try_guardian_pause(&PAUSE_SWAP);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoGuardian as u32
+        )))
+    );
+
+    comet.set_guardian(&Some(guardian.clone()));
+    assert_eq!(env.auths()[0].0, controller);
+    assert_eq!(comet.get_guardian(), Some(guardian.clone()));
+
+    // the guardian can freeze the pool
+    comet.guardian_pause(&PAUSE_SWAP);
+    assert_eq!(env.auths()[0].0, guardian);
+    comet.guardian_pause(&(PAUSE_JOIN | PAUSE_DEPOSIT));
+    assert_eq!(
+        comet.get_pause_flags(),
+        PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT
+    );
+    let result = comet.try_swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+
+    // withdrawals can only be paused by the controller
+    let result = comet.try_guardian_pause(&PAUSE_WITHDRAW);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUnauthorized as u32
+        )))
+    );
+
+    // even once the controller's proposal has matured
+    comet.propose_withdraw_pause();
+    env.ledger()
+        .with_mut(|li| li.timestamp += WITHDRAW_PAUSE_DELAY);
+    let result = comet.try_guardian_pause(&PAUSE_WITHDRAW);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUnauthorized as u32
+        )))
+    );
+    assert_eq!(
+        comet.get_pause_flags(),
+        PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT
+    );
+    comet.cancel_withdraw_pause();
+
+    // but cannot unfreeze it or change the fees
+    let result = comet
+        .mock_auths(&[MockAuth {
+            address: &guardian,
+            invoke: &MockAuthInvoke {
+                contract: &comet_id,
+                fn_name: "set_freeze_status",
+                args: (false,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_freeze_status(&false);
+    assert!(result.is_err());
+    let result = comet
+        .mock_auths(&[MockAuth {
+            address: &guardian,
+            invoke: &MockAuthInvoke {
+                contract: &comet_id,
+                fn_name: "set_swap_fee",
+                args: (0_0040000_i128,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_swap_fee(&0_0040000);
+    assert!(result.is_err());
+
+    comet.set_freeze_status(&false);
+    assert_eq!(env.auths()[0].0, controller);
+    assert_eq!(comet.get_pause_flags(), 0);
+
+    comet.set_guardian(&None);
+    assert_eq!(comet.get_guardian(), None);
+    let result = comet.try_guardian_pause(&PAUSE_SWAP);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoGuardian as u32
+        )))
+    );
+    let result = comet.try_set_guardian(&None);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoGuardian as u32
+        )))
+    );
+}
@@ -0,0 +1,189 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::STROOP,
+    c_pool::{
+        comet::CometPoolContractClient,
+        error::Error as CometError,
+        storage_types::{DataKey, Observation},
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_twap_oracle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+    token_2_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let result = comet.try_consult_twap(&token_1, &token_2, &60);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNoObservations as u32
+        )))
+    );
+
+    // the first balance change starts the accumulators
+    comet.join_pool(&STROOP, &vec![&env, i128::MAX, i128::MAX], &user);
+    assert_eq!(
+        comet.observe(&token_1, &token_2, &vec![&env, 0]),
+        vec![&env, 0]
+    );
+
+    // the price is accumulated over time until the next balance change
+    env.ledger().with_mut(|li| li.timestamp = 1600);
+    assert_eq!(
+        comet.observe(&token_1, &token_2, &vec![&env, 600, 300, 0]),
+        vec![&env, 0, 300 * STROOP, 600 * STROOP]
+    );
+    // a token is priced at STROOP in itself
+    assert_eq!(
+        comet.observe(&token_2, &token_2, &vec![&env, 600, 0]),
+        vec![&env, 1000 * STROOP, 1600 * STROOP]
+    );
+    comet.swap_exact_amount_in(&token_1, &(10 * STROOP), &token_2, &0, &i128::MAX, &user);
+    let price_after_swap = comet.get_spot_price_sans_fee(&token_1, &token_2);
+    assert!(price_after_swap > STROOP);
+
+    env.ledger().with_mut(|li| li.timestamp = 2200);
+    assert_eq!(
+        comet.consult_twap(&token_1, &token_2, &1200),
+        (600 * STROOP + 600 * price_after_swap) / 1200
+    );
+    assert_eq!(
+        comet.consult_twap(&token_1, &token_2, &600),
+        price_after_swap
+    );
+    // the inverse pair has an accumulator of its own
+    assert_eq!(
+        comet.consult_twap(&token_2, &token_1, &600),
+        comet.get_spot_price_sans_fee(&token_2, &token_1)
+    );
+
+    // observations are interpolated between stored observations
+    comet.exit_pool(&STROOP, &vec![&env, 0, 0], &user);
+    env.ledger().with_mut(|li| li.timestamp = 2800);
+    assert_eq!(
+        comet.observe(&token_1, &token_2, &vec![&env, 1500, 900]),
+        vec![&env, 300 * STROOP, 600 * STROOP + 300 * price_after_swap]
+    );
+
+    let result = comet.try_observe(&token_1, &token_2, &vec![&env, 1801]);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrObservationTooOld as u32
+        )))
+    );
+    let result = comet.try_consult_twap(&token_1, &token_2, &0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNegativeOrZero as u32
+        )))
+    );
+}
+
+#[test]
+fn test_twap_oracle_wraps() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_3 = create_stellar_token(&env, &controller);
+    for token in [&token_1, &token_2, &token_3] {
+        MockTokenClient::new(&env, token).mint(&controller, &(100 * STROOP));
+        MockTokenClient::new(&env, token).mint(&user, &(100 * STROOP));
+    }
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone(), token_3.clone()],
+        &vec![&env, 0_4000000, 0_4000000, 0_2000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP, 50 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    // the prices of every ordered pair are accumulated
+    comet.join_pool(&STROOP, &vec![&env, i128::MAX, i128::MAX, i128::MAX], &user);
+    let current = env.as_contract(&comet_id, || {
+        env.storage()
+            .instance()
+            .get::<DataKey, Observation>(&DataKey::PriceCumulative)
+            .unwrap()
+    });
+    assert_eq!(current.price_cumulatives, vec![&env, 0, 0, 0, 0, 0, 0]);
+
+    // the accumulators wrap instead of overflowing
+    env.as_contract(&comet_id, || {
+        let current = Observation {
+            timestamp: 1000,
+            price_cumulatives: vec![&env, i128::MAX - 100 * STROOP, i128::MAX, 0, 0, 0, 0],
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Observation(0), &current);
+        env.storage()
+            .instance()
+            .set(&DataKey::PriceCumulative, &current);
+    });
+    env.ledger().with_mut(|li| li.timestamp = 1600);
+    comet.swap_exact_amount_in(&token_2, &STROOP, &token_3, &0, &i128::MAX, &user);
+    let observed = comet.observe(&token_1, &token_2, &vec![&env, 600, 0]);
+    assert_eq!(
+        observed,
+        vec![&env, i128::MAX - 100 * STROOP, i128::MIN + 500 * STROOP - 1]
+    );
+    let price_after_swap = comet.get_spot_price_sans_fee(&token_1, &token_2);
+    env.ledger().with_mut(|li| li.timestamp = 2200);
+    assert_eq!(
+        comet.consult_twap(&token_1, &token_2, &1200),
+        (600 * STROOP + 600 * price_after_swap) / 1200
+    );
+
+    // pairs without the first token are exact too
+    assert_eq!(
+        comet.consult_twap(&token_2, &token_3, &600),
+        comet.get_spot_price_sans_fee(&token_2, &token_3)
+    );
+    assert_eq!(
+        comet.consult_twap(&token_3, &token_2, &600),
+        comet.get_spot_price_sans_fee(&token_3, &token_2)
+    );
+}
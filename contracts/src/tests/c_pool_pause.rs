@@ -0,0 +1,222 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::{
+        PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP, PAUSE_WITHDRAW, STROOP, WITHDRAW_PAUSE_DELAY,
+        WITHDRAW_PAUSE_GRACE,
+    },
+    c_pool::{
+        comet::CometPoolContractClient,
+        error::{Error as CometError, ErrorExt},
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_pause_flags() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+    token_2_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.join_pool(&(10 * STROOP), &vec![&env, i128::MAX, i128::MAX], &user);
+
+    // swaps can be paused while single-sided deposits stay open
+    comet.set_pause_flags(&PAUSE_SWAP);
+    assert_eq!(env.auths()[0].0, controller);
+    assert!(comet.is_swap_paused());
+    assert!(!comet.is_join_paused());
+    assert!(!comet.is_deposit_paused());
+    assert!(!comet.is_withdraw_paused());
+    let result = comet.try_swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+    comet.dep_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    comet.join_pool(&STROOP, &vec![&env, i128::MAX, i128::MAX], &user);
+
+    // and the other way round
+    comet.set_pause_flags(&(PAUSE_JOIN | PAUSE_DEPOSIT));
+    assert_eq!(comet.get_pause_flags(), PAUSE_JOIN | PAUSE_DEPOSIT);
+    comet.swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    let result = comet.try_dep_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+    let result = comet.try_join_pool(&STROOP, &vec![&env, i128::MAX, i128::MAX], &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+
+    // freezing pauses everything but withdrawals
+    comet.set_pause_flags(&0);
+    comet.set_freeze_status(&true);
+    assert_eq!(
+        comet.get_pause_flags(),
+        PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT
+    );
+    comet.exit_pool(&STROOP, &vec![&env, 0, 0], &user);
+    comet.set_freeze_status(&false);
+    assert_eq!(comet.get_pause_flags(), 0);
+
+    let result = comet.try_set_pause_flags(&(1 << 4));
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrInvalidPauseFlags as u32
+        )))
+    );
+}
+
+#[test]
+fn test_withdraw_pause_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+    token_2_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.join_pool(&(10 * STROOP), &vec![&env, i128::MAX, i128::MAX], &user);
+
+    // withdrawals cannot be paused without waiting for the timelock
+    let result = comet.try_set_pause_flags(&PAUSE_WITHDRAW);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPauseTimelock as u32
+        )))
+    );
+    let result = comet.try_cancel_withdraw_pause();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoPendingPause as u32
+        )))
+    );
+
+    comet.propose_withdraw_pause();
+    assert_eq!(env.auths()[0].0, controller);
+    let eta = 1000 + WITHDRAW_PAUSE_DELAY;
+    assert_eq!(comet.get_withdraw_pause_eta(), Some(eta));
+    env.ledger().with_mut(|li| li.timestamp = eta - 1);
+    let result = comet.try_set_pause_flags(&PAUSE_WITHDRAW);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPauseTimelock as u32
+        )))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = eta);
+    comet.set_pause_flags(&PAUSE_WITHDRAW);
+    assert!(comet.is_withdraw_paused());
+    assert_eq!(comet.get_withdraw_pause_eta(), None);
+    let result = comet.try_exit_pool(&STROOP, &vec![&env, 0, 0], &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPaused as u32
+        )))
+    );
+    let result = comet.try_wdr_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPaused as u32
+        )))
+    );
+    let result = comet.try_quote_exit_pool(&STROOP);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPaused as u32
+        )))
+    );
+    // other operations are unaffected
+    comet.swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+
+    // unpausing is immediate, and pausing again needs a new proposal
+    comet.set_pause_flags(&0);
+    comet.exit_pool(&STROOP, &vec![&env, 0, 0], &user);
+    let result = comet.try_set_pause_flags(&PAUSE_WITHDRAW);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPauseTimelock as u32
+        )))
+    );
+    comet.propose_withdraw_pause();
+    comet.cancel_withdraw_pause();
+    assert_eq!(comet.get_withdraw_pause_eta(), None);
+
+    // a matured proposal expires after the grace window
+    comet.propose_withdraw_pause();
+    let eta = eta + WITHDRAW_PAUSE_DELAY;
+    env.ledger()
+        .with_mut(|li| li.timestamp = eta + WITHDRAW_PAUSE_GRACE + 1);
+    let result = comet.try_set_pause_flags(&PAUSE_WITHDRAW);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrWithdrawPauseTimelock as u32
+        )))
+    );
+    env.ledger()
+        .with_mut(|li| li.timestamp = eta + WITHDRAW_PAUSE_GRACE);
+    comet.set_pause_flags(&PAUSE_WITHDRAW);
+    assert!(comet.is_withdraw_paused());
+}
@@ -0,0 +1,133 @@
+#![cfg(test)]
+extern crate std;
+
+use ed25519_dalek::{Signer, SigningKey};
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec,
+    xdr::ToXdr,
+    Address, BytesN, Env, Error,
+};
+
+use crate::{
+    c_consts::STROOP,
+    c_pool::{comet::CometPoolContractClient, error::ErrorExt},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[allow(clippy::too_many_arguments)]
+fn sign_permit(
+    env: &Env,
+    key: &SigningKey,
+    pool: &Address,
+    owner: &Address,
+    spender: &Address,
+    amount: i128,
+    expiration_ledger: u32,
+    nonce: u64,
+) -> BytesN<64> {
+    let payload = (
+        pool.clone(),
+        owner.clone(),
+        spender.clone(),
+        amount,
+        expiration_ledger,
+        nonce,
+    )
+        .to_xdr(env);
+    let payload: std::vec::Vec<u8> = payload.iter().collect();
+    BytesN::from_array(env, &key.sign(&payload).to_bytes())
+}
+
+#[test]
+fn test_permit() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    env.mock_all_auths();
+    MockTokenClient::new(&env, &token_1).mint(&controller, &(100 * STROOP));
+    MockTokenClient::new(&env, &token_2).mint(&controller, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.transfer(&controller, &owner, &(10 * STROOP));
+
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+
+    // permits need a key registered by the owner
+    let signature = sign_permit(&env, &key, &comet_id, &owner, &relayer, 5 * STROOP, 200, 0);
+    let result = comet.try_permit(&owner, &relayer, &(5 * STROOP), &200, &signature);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoPermitKey as u32
+        )))
+    );
+    comet.set_permit_key(&owner, &public_key);
+    assert_eq!(env.auths()[0].0, owner);
+    assert_eq!(comet.get_permit_key(&owner), Some(public_key));
+    env.set_auths(&[]);
+
+    // the relayer submits the permit and spends the allowance without the owner's auth
+    assert_eq!(comet.get_nonce(&owner), 0);
+    comet.permit(&owner, &relayer, &(5 * STROOP), &200, &signature);
+    assert!(env.auths().is_empty());
+    assert_eq!(comet.allowance(&owner, &relayer), 5 * STROOP);
+    assert_eq!(comet.get_nonce(&owner), 1);
+
+    env.mock_all_auths();
+    comet.transfer_from(&relayer, &owner, &relayer, &(2 * STROOP));
+    assert_eq!(comet.balance(&relayer), 2 * STROOP);
+    assert_eq!(comet.allowance(&owner, &relayer), 3 * STROOP);
+
+    // a signature cannot be replayed once the nonce is used
+    let result = comet.try_permit(&owner, &relayer, &(5 * STROOP), &200, &signature);
+    assert!(result.is_err());
+    assert_eq!(comet.allowance(&owner, &relayer), 3 * STROOP);
+
+    // nor changed to another amount
+    let signature = sign_permit(&env, &key, &comet_id, &owner, &relayer, STROOP, 200, 1);
+    let result = comet.try_permit(&owner, &relayer, &(5 * STROOP), &200, &signature);
+    assert!(result.is_err());
+
+    // nor used on another pool
+    let other_pool = Address::generate(&env);
+    let other_signature = sign_permit(&env, &key, &other_pool, &owner, &relayer, STROOP, 200, 1);
+    let result = comet.try_permit(&owner, &relayer, &STROOP, &200, &other_signature);
+    assert!(result.is_err());
+    comet.permit(&owner, &relayer, &STROOP, &200, &signature);
+    assert_eq!(comet.allowance(&owner, &relayer), STROOP);
+
+    // and the key has to be the one of the owner
+    let signature = sign_permit(&env, &key, &comet_id, &controller, &relayer, STROOP, 200, 0);
+    let result = comet.try_permit(&controller, &relayer, &STROOP, &200, &signature);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoPermitKey as u32
+        )))
+    );
+    let other_key = SigningKey::from_bytes(&[8; 32]);
+    comet.set_permit_key(
+        &controller,
+        &BytesN::from_array(&env, &other_key.verifying_key().to_bytes()),
+    );
+    let result = comet.try_permit(&controller, &relayer, &STROOP, &200, &signature);
+    assert!(result.is_err());
+    assert_eq!(comet.allowance(&controller, &relayer), 0);
+}
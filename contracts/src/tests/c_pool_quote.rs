@@ -0,0 +1,194 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::{MIN_FEE_UPDATE_DELAY, STROOP},
+    c_pool::{comet::CometPoolContractClient, error::Error as CometError},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_quotes_match_execution() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(50 * STROOP));
+    token_1_client.mint(&user, &(1000 * STROOP));
+    token_2_client.mint(&user, &(1000 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_8000000, 0_2000000],
+        &vec![&env, 100 * STROOP, 50 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    comet.set_exit_fee(&0_0050000);
+    env.ledger()
+        .with_mut(|li| li.sequence_number += MIN_FEE_UPDATE_DELAY);
+
+    // swaps
+    let quote = comet.quote_swap_exact_amount_in(&token_1, &(3 * STROOP), &token_2);
+    let result =
+        comet.swap_exact_amount_in(&token_1, &(3 * STROOP), &token_2, &0, &i128::MAX, &user);
+    assert_eq!(quote, result);
+
+    let quote = comet.quote_swap_exact_amount_out(&token_1, &token_2, &(2 * STROOP));
+    let result = comet.swap_exact_amount_out(
+        &token_1,
+        &i128::MAX,
+        &token_2,
+        &(2 * STROOP),
+        &i128::MAX,
+        &user,
+    );
+    assert_eq!(quote, result);
+
+    // join and exit
+    let quote = comet.quote_join_pool(&(10 * STROOP));
+    let balance_1 = token_1_client.balance(&user);
+    let balance_2 = token_2_client.balance(&user);
+    comet.join_pool(&(10 * STROOP), &vec![&env, i128::MAX, i128::MAX], &user);
+    assert_eq!(
+        quote,
+        vec![
+            &env,
+            balance_1 - token_1_client.balance(&user),
+            balance_2 - token_2_client.balance(&user)
+        ]
+    );
+
+    let quote = comet.quote_exit_pool(&(5 * STROOP));
+    let balance_1 = token_1_client.balance(&user);
+    let balance_2 = token_2_client.balance(&user);
+    comet.exit_pool(&(5 * STROOP), &vec![&env, 0, 0], &user);
+    assert_eq!(
+        quote,
+        vec![
+            &env,
+            token_1_client.balance(&user) - balance_1,
+            token_2_client.balance(&user) - balance_2
+        ]
+    );
+
+    // single sided deposits and withdrawals
+    let quote = comet.quote_dep_tokn_amt_in(&token_1, &STROOP);
+    let result = comet.dep_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    assert_eq!(quote, result);
+
+    let quote = comet.quote_dep_lp_tokn_amt_out(&token_2, &STROOP);
+    let result = comet.dep_lp_tokn_amt_out_get_tokn_in(&token_2, &STROOP, &i128::MAX, &user);
+    assert_eq!(quote, result);
+
+    let quote = comet.quote_wdr_tokn_amt_in(&token_1, &STROOP);
+    let result = comet.wdr_tokn_amt_in_get_lp_tokns_out(&token_1, &STROOP, &0, &user);
+    assert_eq!(quote, result);
+
+    let quote = comet.quote_wdr_tokn_amt_out(&token_2, &STROOP);
+    let result = comet.wdr_tokn_amt_out_get_lp_tokns_in(&token_2, &STROOP, &i128::MAX, &user);
+    assert_eq!(quote, result);
+}
+
+#[test]
+fn test_quote_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let controller = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_3 = create_stellar_token(&env, &controller);
+    MockTokenClient::new(&env, &token_1).mint(&controller, &(100 * STROOP));
+    MockTokenClient::new(&env, &token_2).mint(&controller, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let result = comet.try_quote_swap_exact_amount_in(&token_1, &(34 * STROOP), &token_2);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxInRatio as u32
+        )))
+    );
+    let result = comet.try_quote_swap_exact_amount_out(&token_1, &token_2, &(34 * STROOP));
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxOutRatio as u32
+        )))
+    );
+    let result = comet.try_quote_dep_tokn_amt_in(&token_3, &STROOP);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNotBound as u32
+        )))
+    );
+    let result = comet.try_quote_wdr_tokn_amt_in(&token_1, &(40 * STROOP));
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxOutRatio as u32
+        )))
+    );
+    let result = comet.try_quote_join_pool(&0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrNegativeOrZero as u32
+        )))
+    );
+
+    // frozen pools only quote withdrawals
+    comet.set_freeze_status(&true);
+    let result = comet.try_quote_swap_exact_amount_in(&token_1, &STROOP, &token_2);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+    let result = comet.try_quote_join_pool(&STROOP);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+    let result = comet.try_quote_dep_lp_tokn_amt_out(&token_1, &STROOP);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFreezeOnlyWithdrawals as u32
+        )))
+    );
+    assert_eq!(
+        comet.quote_exit_pool(&(10 * STROOP)),
+        vec![&env, 10 * STROOP, 10 * STROOP]
+    );
+    assert!(comet.quote_wdr_tokn_amt_out(&token_2, &STROOP) > 0);
+}
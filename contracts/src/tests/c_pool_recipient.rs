@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::STROOP,
+    c_pool::{comet::CometPoolContractClient, error::ErrorExt},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_recipient_and_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+    token_2_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    // calls past the deadline fail
+    let result = comet.try_swap_exact_amount_in_to(
+        &token_1,
+        &STROOP,
+        &token_2,
+        &0,
+        &i128::MAX,
+        &user,
+        &to,
+        &999,
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrDeadlinePassed as u32
+        )))
+    );
+    let result =
+        comet.try_join_pool_to(&STROOP, &vec![&env, i128::MAX, i128::MAX], &user, &to, &999);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrDeadlinePassed as u32
+        )))
+    );
+
+    // swaps pay the tokens out to the recipient
+    let (amount_out, _) = comet.swap_exact_amount_in_to(
+        &token_1,
+        &STROOP,
+        &token_2,
+        &0,
+        &i128::MAX,
+        &user,
+        &to,
+        &1000,
+    );
+    assert_eq!(token_1_client.balance(&user), 99 * STROOP);
+    assert_eq!(token_2_client.balance(&user), 100 * STROOP);
+    assert_eq!(token_2_client.balance(&to), amount_out);
+
+    let (amount_in, _) = comet.swap_exact_amount_out_to(
+        &token_1,
+        &i128::MAX,
+        &token_2,
+        &STROOP,
+        &i128::MAX,
+        &user,
+        &to,
+        &1000,
+    );
+    assert_eq!(token_1_client.balance(&user), 99 * STROOP - amount_in);
+    assert_eq!(token_2_client.balance(&to), amount_out + STROOP);
+
+    // joins and deposits mint the LP tokens to the recipient
+    comet.join_pool_to(
+        &(10 * STROOP),
+        &vec![&env, i128::MAX, i128::MAX],
+        &user,
+        &to,
+        &1000,
+    );
+    assert_eq!(comet.balance(&user), 0);
+    assert_eq!(comet.balance(&to), 10 * STROOP);
+
+    let pool_amount_out = comet.dep_tokn_amt_in_to(&token_1, &STROOP, &0, &user, &to, &1000);
+    assert_eq!(comet.balance(&to), 10 * STROOP + pool_amount_out);
+    comet.dep_lp_tokn_amt_out_to(&token_2, &STROOP, &i128::MAX, &user, &to, &1000);
+    assert_eq!(comet.balance(&to), 11 * STROOP + pool_amount_out);
+    assert_eq!(comet.balance(&user), 0);
+
+    // exits and withdrawals pay the tokens out to the recipient
+    let balance_1 = token_1_client.balance(&to);
+    let balance_2 = token_2_client.balance(&to);
+    comet.transfer(&to, &user, &(11 * STROOP));
+    comet.exit_pool_to(&(5 * STROOP), &vec![&env, 0, 0], &user, &to, &1000);
+    assert_eq!(comet.balance(&user), 6 * STROOP);
+    assert!(token_1_client.balance(&to) > balance_1);
+    assert!(token_2_client.balance(&to) > balance_2);
+
+    let balance_1 = token_1_client.balance(&to);
+    let amount_out = comet.wdr_tokn_amt_in_to(&token_1, &STROOP, &0, &user, &to, &1000);
+    assert_eq!(token_1_client.balance(&to), balance_1 + amount_out);
+    let balance_2 = token_2_client.balance(&to);
+    let pool_amount_in =
+        comet.wdr_tokn_amt_out_to(&token_2, &STROOP, &i128::MAX, &user, &to, &1000);
+    assert_eq!(token_2_client.balance(&to), balance_2 + STROOP);
+    assert_eq!(comet.balance(&user), 5 * STROOP - pool_amount_in);
+}
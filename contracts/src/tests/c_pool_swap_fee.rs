@@ -0,0 +1,91 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger},
+    vec, Address, Env, Error, IntoVal, Symbol,
+};
+
+use crate::{
+    c_consts::{MAX_FEE, MIN_FEE_UPDATE_DELAY, STROOP},
+    c_pool::{comet::CometPoolContractClient, error::Error as CometError},
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_set_swap_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    MockTokenClient::new(&env, &token_1).mint(&controller, &STROOP);
+    MockTokenClient::new(&env, &token_2).mint(&controller, &STROOP);
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, STROOP, STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    // validates bounds
+    let result = comet.try_set_swap_fee(&0_0000009);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(CometError::ErrMinFee as u32)))
+    );
+    let result = comet.try_set_swap_fee(&(MAX_FEE + 1));
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(CometError::ErrMaxFee as u32)))
+    );
+
+    // validates step size
+    let result = comet.try_set_swap_fee(&0_0130001);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMaxFeeDelta as u32
+        )))
+    );
+
+    // first update is allowed immediately and requires the controller
+    comet.set_swap_fee(&0_0130000);
+    assert_eq!(
+        env.auths()[0],
+        (
+            controller.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    comet_id.clone(),
+                    Symbol::new(&env, "set_swap_fee"),
+                    vec![&env, 0_0130000i128.into_val(&env)],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+    assert_eq!(comet.get_swap_fee(), 0_0130000);
+
+    // validates update delay
+    env.ledger()
+        .with_mut(|li| li.sequence_number += MIN_FEE_UPDATE_DELAY - 1);
+    let result = comet.try_set_swap_fee(&0_0100000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrFeeUpdateTooSoon as u32
+        )))
+    );
+
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+    comet.set_swap_fee(&0_0030000);
+    assert_eq!(comet.get_swap_fee(), 0_0030000);
+}
@@ -0,0 +1,260 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, BytesN, Env, Error, Map,
+};
+
+use crate::{
+    c_consts::{
+        MAX_UPGRADE_DELAY, MIN_UPGRADE_DELAY, PAUSE_DEPOSIT, PAUSE_JOIN, PAUSE_SWAP,
+        STORAGE_VERSION, STROOP,
+    },
+    c_pool::{
+        comet::CometPoolContractClient,
+        error::ErrorExt,
+        storage_types::{DataKey, DataKeyToken, PendingUpgrade, Record},
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_upgrade_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let controller = Address::generate(&env);
+    let factory = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    MockTokenClient::new(&env, &token_1).mint(&controller, &(100 * STROOP));
+    MockTokenClient::new(&env, &token_2).mint(&controller, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    assert_eq!(comet.get_storage_version(), STORAGE_VERSION);
+    assert_eq!(comet.get_upgrade_delay(), MIN_UPGRADE_DELAY);
+
+    // only the factory that deployed the pool can be set
+    let result = comet.try_set_factory(&factory, &BytesN::from_array(&env, &[0; 32]), &None);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUnauthorized as u32
+        )))
+    );
+    env.as_contract(&comet_id, || {
+        env.storage().instance().set(&DataKey::Factory, &factory);
+    });
+
+    let wasm_hash = BytesN::from_array(&env, &[1; 32]);
+    let result = comet.try_propose_upgrade(&wasm_hash, &user);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUnauthorized as u32
+        )))
+    );
+
+    // the delay can only be increased
+    comet.set_upgrade_delay(&(2 * 86400), &controller);
+    assert_eq!(env.auths()[0].0, controller);
+    assert_eq!(comet.get_upgrade_delay(), 2 * 86400);
+    let result = comet.try_set_upgrade_delay(&86400, &controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUpgradeDelay as u32
+        )))
+    );
+    let result = comet.try_set_upgrade_delay(&(MAX_UPGRADE_DELAY + 1), &controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUpgradeDelay as u32
+        )))
+    );
+
+    // upgrades have to be proposed and wait for the delay
+    let result = comet.try_upgrade(&wasm_hash, &controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoPendingUpgrade as u32
+        )))
+    );
+    comet.propose_upgrade(&wasm_hash, &factory);
+    assert_eq!(env.auths()[0].0, factory);
+    assert_eq!(
+        comet.get_pending_upgrade(),
+        Some(PendingUpgrade {
+            wasm_hash: wasm_hash.clone(),
+            eta: 1000 + 2 * 86400,
+        })
+    );
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1000 + 2 * 86400 - 1);
+    let result = comet.try_upgrade(&wasm_hash, &controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrUpgradeTooEarly as u32
+        )))
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1000 + 2 * 86400);
+    let result = comet.try_upgrade(&BytesN::from_array(&env, &[2; 32]), &controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoPendingUpgrade as u32
+        )))
+    );
+
+    comet.cancel_upgrade(&controller);
+    assert_eq!(comet.get_pending_upgrade(), None);
+    let result = comet.try_cancel_upgrade(&controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNoPendingUpgrade as u32
+        )))
+    );
+}
+
+#[test]
+fn test_migrate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    MockTokenClient::new(&env, &token_1).mint(&controller, &(100 * STROOP));
+    MockTokenClient::new(&env, &token_2).mint(&controller, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+
+    let result = comet.try_migrate(&controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrAlreadyMigrated as u32
+        )))
+    );
+
+    // pools deployed before storage versioning freeze with a flag, keep every
+    // record in a single map and have no checkpoints
+    env.as_contract(&comet_id, || {
+        let storage = env.storage().persistent();
+        let count_key = DataKeyToken::CheckpointCount(controller.clone());
+        let count: u32 = storage.get(&count_key).unwrap();
+        for i in 0..count {
+            storage.remove(&DataKeyToken::Checkpoint(controller.clone(), i));
+        }
+        storage.remove(&count_key);
+        let count: u32 = storage.get(&DataKeyToken::SupplyCheckpointCount).unwrap();
+        for i in 0..count {
+            storage.remove(&DataKeyToken::SupplyCheckpoint(i));
+        }
+        storage.remove(&DataKeyToken::SupplyCheckpointCount);
+        env.storage().instance().remove(&DataKey::StorageVersion);
+        env.storage().instance().set(&DataKey::Freeze, &true);
+        let mut records = Map::<Address, Record>::new(&env);
+        for token in [&token_1, &token_2] {
+            let key = DataKey::Record(token.clone());
+            let record: Record = env.storage().persistent().get(&key).unwrap();
+            records.set(token.clone(), record);
+            env.storage().persistent().remove(&key);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::AllRecordData, &records);
+    });
+    assert_eq!(comet.get_storage_version(), 0);
+    assert!(!comet.is_migrated());
+    let result = comet.try_get_balance(&token_1);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrNotMigrated as u32
+        )))
+    );
+
+    // a frozen pool keeps swaps, joins and deposits paused
+    env.ledger().with_mut(|li| li.sequence_number = 200);
+    comet.migrate(&controller);
+    assert!(comet.is_migrated());
+    assert_eq!(comet.get_storage_version(), STORAGE_VERSION);
+    assert_eq!(
+        comet.get_pause_flags(),
+        PAUSE_SWAP | PAUSE_JOIN | PAUSE_DEPOSIT
+    );
+    assert!(!comet.is_withdraw_paused());
+
+    // and every record gets an entry of its own
+    env.as_contract(&comet_id, || {
+        assert!(!env.storage().instance().has(&DataKey::Freeze));
+        assert!(!env.storage().persistent().has(&DataKey::AllRecordData));
+        assert!(env
+            .storage()
+            .persistent()
+            .has(&DataKey::Record(token_2.clone())));
+    });
+    assert_eq!(comet.get_balance(&token_1), 100 * STROOP);
+    assert_eq!(comet.get_balance(&token_2), 100 * STROOP);
+    assert_eq!(comet.get_normalized_weight(&token_2), 0_5000000);
+    assert_eq!(comet.get_spot_price_sans_fee(&token_1, &token_2), STROOP);
+
+    // checkpoints are recorded from the ledger of the migration on, balances
+    // from before it are kept until they first change
+    let supply = comet.get_total_supply();
+    let balance = comet.balance(&controller);
+    env.ledger().with_mut(|li| li.sequence_number = 250);
+    comet.transfer(&controller, &user, &STROOP);
+    env.ledger().with_mut(|li| li.sequence_number = 300);
+    assert_eq!(comet.total_supply_at(&200), supply);
+    assert_eq!(comet.balance_at(&controller, &200), balance);
+    assert_eq!(comet.balance_at(&controller, &249), balance);
+    assert_eq!(comet.balance_at(&controller, &250), balance - STROOP);
+    assert_eq!(comet.balance_at(&user, &249), 0);
+    assert_eq!(comet.balance_at(&user, &250), STROOP);
+    let other = Address::generate(&env);
+    assert_eq!(comet.balance_at(&other, &250), 0);
+
+    let result = comet.try_balance_at(&controller, &199);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrHistoryUnavailable as u32
+        )))
+    );
+    let result = comet.try_total_supply_at(&199);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            ErrorExt::ErrHistoryUnavailable as u32
+        )))
+    );
+}
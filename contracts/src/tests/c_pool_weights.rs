@@ -0,0 +1,119 @@
+#![cfg(test)]
+
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Error,
+};
+
+use crate::{
+    c_consts::STROOP,
+    c_pool::{
+        comet::CometPoolContractClient, error::Error as CometError, storage_types::WeightSchedule,
+    },
+    tests::utils::{create_comet_pool, create_stellar_token},
+};
+
+#[test]
+fn test_weight_schedule() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = create_stellar_token(&env, &controller);
+    let token_2 = create_stellar_token(&env, &controller);
+    let token_1_client = MockTokenClient::new(&env, &token_1);
+    let token_2_client = MockTokenClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &(100 * STROOP));
+    token_2_client.mint(&controller, &(100 * STROOP));
+    token_1_client.mint(&user, &(100 * STROOP));
+
+    let comet_id = create_comet_pool(
+        &env,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_9000000, 0_1000000],
+        &vec![&env, 100 * STROOP, 100 * STROOP],
+        0_0030000,
+    );
+    let comet = CometPoolContractClient::new(&env, &comet_id);
+    assert_eq!(comet.get_weight_schedule(), None);
+    assert_eq!(comet.get_weights(), vec![&env, 0_9000000, 0_1000000]);
+
+    // validates schedule
+    let result = comet.try_set_weight_schedule(&vec![&env, 0_1000000, 0_9000000], &99, &200);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrInvalidSchedule as u32
+        )))
+    );
+    let result = comet.try_set_weight_schedule(&vec![&env, 0_1000000, 0_9000000], &200, &200);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrInvalidSchedule as u32
+        )))
+    );
+    let result = comet.try_set_weight_schedule(&vec![&env, 0_1000000], &100, &200);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrInvalidVectorLen as u32
+        )))
+    );
+    let result = comet.try_set_weight_schedule(&vec![&env, 0_0900000, 0_9100000], &100, &200);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrMinWeight as u32
+        )))
+    );
+    let result = comet.try_set_weight_schedule(&vec![&env, 0_1000000, 0_8000000], &100, &200);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            CometError::ErrTotalWeight as u32
+        )))
+    );
+
+    // schedule a shift starting in the future
+    comet.set_weight_schedule(&vec![&env, 0_1000000, 0_9000000], &200, &1000);
+    assert_eq!(
+        comet.get_weight_schedule(),
+        Some(WeightSchedule {
+            start_ledger: 200,
+            end_ledger: 1000,
+            start_weights: vec![&env, 0_9000000, 0_1000000],
+            end_weights: vec![&env, 0_1000000, 0_9000000],
+        })
+    );
+    assert_eq!(comet.get_weights(), vec![&env, 0_9000000, 0_1000000]);
+    let spot_price_start = comet.get_spot_price_sans_fee(&token_1, &token_2);
+    assert_eq!(spot_price_start, 0_1111111);
+
+    // halfway through the weights are equal
+    env.ledger().with_mut(|li| li.sequence_number = 600);
+    assert_eq!(comet.get_weights(), vec![&env, 0_5000000, 0_5000000]);
+    assert_eq!(comet.get_normalized_weight(&token_1), 0_5000000);
+    assert_eq!(comet.get_spot_price_sans_fee(&token_1, &token_2), STROOP);
+
+    // swaps use the interpolated weights
+    let (amount_out, _) =
+        comet.swap_exact_amount_in(&token_1, &STROOP, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(amount_out, 0_9871580);
+    assert_eq!(comet.get_balance(&token_1), 101 * STROOP);
+
+    // re-scheduling starts from the current weights
+    env.ledger().with_mut(|li| li.sequence_number = 800);
+    assert_eq!(comet.get_weights(), vec![&env, 0_3000000, 0_7000000]);
+    comet.set_weight_schedule(&vec![&env, 0_5000000, 0_5000000], &800, &900);
+    assert_eq!(comet.get_weights(), vec![&env, 0_3000000, 0_7000000]);
+
+    // weights stay at the end weights once the schedule is complete
+    env.ledger().with_mut(|li| li.sequence_number = 2000);
+    assert_eq!(comet.get_weights(), vec![&env, 0_5000000, 0_5000000]);
+}
@@ -3,9 +3,25 @@ mod utils;
 
 pub mod c_num_test;
 pub mod c_pool_all;
+pub mod c_pool_bind;
+pub mod c_pool_checkpoints;
+pub mod c_pool_controller;
 pub mod c_pool_dif_decimals;
+pub mod c_pool_dynamic_fee;
+pub mod c_pool_events;
+pub mod c_pool_exit_fee;
+pub mod c_pool_flash_loan;
+pub mod c_pool_guardian;
 pub mod c_pool_init;
 pub mod c_pool_join_exit;
+pub mod c_pool_oracle;
+pub mod c_pool_pause;
+pub mod c_pool_permit;
+pub mod c_pool_quote;
+pub mod c_pool_recipient;
 pub mod c_pool_single_sided;
 pub mod c_pool_swap;
+pub mod c_pool_swap_fee;
 pub mod c_pool_test;
+pub mod c_pool_upgrade;
+pub mod c_pool_weights;
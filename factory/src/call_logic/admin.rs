@@ -0,0 +1,151 @@
+use math::c_consts::MAX_PROTOCOL_FEE;
+use soroban_sdk::{assert_with_error, panic_with_error, symbol_short, Address, BytesN, Env};
+
+use crate::{
+    error::Error, AdminEvent, DataKeyFactory, ProtocolFee, ProtocolFeeEvent, VersionEvent,
+};
+
+use super::factory::{
+    read_default_version, read_version_count, read_version_deprecated, read_wasm_hash,
+    write_wasm_hash, LARGE_BUMP_AMOUNT, LARGE_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT,
+    SHARED_LIFETIME_THRESHOLD,
+};
+
+// Read the Admin Address
+pub fn read_admin(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<DataKeyFactory, Address>(&DataKeyFactory::Admin)
+        .unwrap_or_else(|| panic_with_error!(e, Error::ErrNotController))
+}
+
+// Read the pending Admin Address
+pub fn read_pending_admin(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<DataKeyFactory, Address>(&DataKeyFactory::PendingAdmin)
+}
+
+// Read the protocol fee settings
+pub fn read_protocol_fee(e: &Env) -> Option<ProtocolFee> {
+    e.storage()
+        .instance()
+        .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+    e.storage()
+        .instance()
+        .get::<DataKeyFactory, ProtocolFee>(&DataKeyFactory::ProtocolFee)
+}
+
+pub fn execute_propose_admin(e: Env, pending_admin: Address, admin: Address) {
+    e.storage()
+        .instance()
+        .set(&DataKeyFactory::PendingAdmin, &pending_admin);
+    let event: AdminEvent = AdminEvent {
+        admin,
+        pending_admin,
+    };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("ADM_PROP")), event);
+}
+
+pub fn execute_accept_admin(e: Env, pending_admin: Address, admin: Address) {
+    e.storage().instance().remove(&DataKeyFactory::PendingAdmin);
+    e.storage()
+        .instance()
+        .set(&DataKeyFactory::Admin, &pending_admin);
+    let event: AdminEvent = AdminEvent {
+        admin,
+        pending_admin,
+    };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("ADM_ACPT")), event);
+}
+
+pub fn execute_cancel_admin(e: Env, admin: Address) {
+    let pending_admin =
+        read_pending_admin(&e).unwrap_or_else(|| panic_with_error!(&e, Error::ErrNoPendingAdmin));
+    e.storage().instance().remove(&DataKeyFactory::PendingAdmin);
+    let event: AdminEvent = AdminEvent {
+        admin,
+        pending_admin,
+    };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("ADM_CNCL")), event);
+}
+
+pub fn execute_set_protocol_fee(e: Env, fee_recipient: Address, protocol_fee: i128) {
+    assert_with_error!(
+        &e,
+        (0..=MAX_PROTOCOL_FEE).contains(&protocol_fee),
+        Error::ErrInvalidProtocolFee
+    );
+    e.storage()
+        .instance()
+        .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+    e.storage().instance().set(
+        &DataKeyFactory::ProtocolFee,
+        &ProtocolFee {
+            fee_recipient: fee_recipient.clone(),
+            protocol_fee,
+        },
+    );
+    let event: ProtocolFeeEvent = ProtocolFeeEvent {
+        fee_recipient,
+        protocol_fee,
+    };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("PROTO_FEE")), event);
+}
+
+// Register `wasm_hash` as the next pool version
+pub fn execute_add_pool_version(e: Env, wasm_hash: BytesN<32>) -> u32 {
+    e.storage()
+        .instance()
+        .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+    let version = read_version_count(&e);
+    write_wasm_hash(&e, version, &wasm_hash);
+    e.storage()
+        .instance()
+        .set(&DataKeyFactory::WasmVersionCount, &(version + 1));
+    let event: VersionEvent = VersionEvent { version, wasm_hash };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("NEW_VER")), event);
+    version
+}
+
+// Make `version` the pool version used by `new_c_pool`
+pub fn execute_set_default_version(e: Env, version: u32) {
+    let wasm_hash = read_wasm_hash(&e, version);
+    assert_with_error!(
+        &e,
+        !read_version_deprecated(&e, version),
+        Error::ErrDeprecatedVersion
+    );
+    e.storage()
+        .instance()
+        .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+    e.storage()
+        .instance()
+        .set(&DataKeyFactory::DefaultVersion, &version);
+    let event: VersionEvent = VersionEvent { version, wasm_hash };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("DEF_VER")), event);
+}
+
+// Deprecate `version`, which can't be the default version
+pub fn execute_deprecate_pool_version(e: Env, version: u32) {
+    let wasm_hash = read_wasm_hash(&e, version);
+    assert_with_error!(
+        &e,
+        version != read_default_version(&e),
+        Error::ErrDeprecatedVersion
+    );
+    let key = DataKeyFactory::DeprecatedVersion(version);
+    e.storage().persistent().set(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+    let event: VersionEvent = VersionEvent { version, wasm_hash };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("DEP_VER")), event);
+}
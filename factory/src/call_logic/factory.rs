@@ -1,8 +1,11 @@
 use soroban_sdk::{
-    symbol_short, unwrap::UnwrapOptimized, vec, Address, Bytes, BytesN, Env, IntoVal, Val, Vec,
+    assert_with_error, panic_with_error, symbol_short, unwrap::UnwrapOptimized, vec, Address,
+    Bytes, BytesN, Env, IntoVal, Symbol, Val, Vec,
 };
 
-use crate::{DataKeyFactory, NewPoolEvent};
+use crate::{error::Error, DataKeyFactory, NewPoolEvent};
+
+use super::admin::read_protocol_fee;
 
 pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
 
@@ -12,16 +15,158 @@ pub(crate) const SHARED_LIFETIME_THRESHOLD: u32 = SHARED_BUMP_AMOUNT - DAY_IN_LE
 pub(crate) const LARGE_BUMP_AMOUNT: u32 = 120 * DAY_IN_LEDGERS;
 pub(crate) const LARGE_LIFETIME_THRESHOLD: u32 = LARGE_BUMP_AMOUNT - 20 * DAY_IN_LEDGERS;
 
-pub fn execute_init(e: Env, pool_wasm_hash: BytesN<32>) {
+// Maximum number of Pools returned by `get_pools`
+pub(crate) const MAX_POOLS_PER_PAGE: u32 = 100;
+
+pub fn execute_init(e: Env, pool_wasm_hash: BytesN<32>, admin: Address) {
+    write_wasm_hash(&e, 0, &pool_wasm_hash);
     e.storage()
         .instance()
-        .set(&DataKeyFactory::WasmHash, &pool_wasm_hash);
+        .set(&DataKeyFactory::WasmVersionCount, &1u32);
+    e.storage()
+        .instance()
+        .set(&DataKeyFactory::DefaultVersion, &0u32);
+    e.storage().instance().set(&DataKeyFactory::Admin, &admin);
+}
+
+// Get the number of pool versions registered
+pub fn read_version_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<DataKeyFactory, u32>(&DataKeyFactory::WasmVersionCount)
+        .unwrap_optimized()
+}
+
+// Get the pool version used by `new_c_pool`
+pub fn read_default_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<DataKeyFactory, u32>(&DataKeyFactory::DefaultVersion)
+        .unwrap_optimized()
+}
+
+// Get the WASM hash of a pool version
+pub fn read_wasm_hash(e: &Env, version: u32) -> BytesN<32> {
+    let key = DataKeyFactory::WasmHash(version);
+    let wasm_hash = e
+        .storage()
+        .persistent()
+        .get::<DataKeyFactory, BytesN<32>>(&key)
+        .unwrap_or_else(|| panic_with_error!(e, Error::ErrInvalidVersion));
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+    wasm_hash
 }
 
+pub(crate) fn write_wasm_hash(e: &Env, version: u32, wasm_hash: &BytesN<32>) {
+    let key = DataKeyFactory::WasmHash(version);
+    e.storage().persistent().set(&key, wasm_hash);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+}
+
+// Returns true if `version` has been deprecated
+pub fn read_version_deprecated(e: &Env, version: u32) -> bool {
+    let key = DataKeyFactory::DeprecatedVersion(version);
+    if let Some(deprecated) = e.storage().persistent().get::<DataKeyFactory, bool>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+        deprecated
+    } else {
+        false
+    }
+}
+
+// Get the WASM hash of a pool version that can still be deployed
+fn read_active_wasm_hash(e: &Env, version: u32) -> BytesN<32> {
+    let wasm_hash = read_wasm_hash(e, version);
+    assert_with_error!(
+        e,
+        !read_version_deprecated(e, version),
+        Error::ErrDeprecatedVersion
+    );
+    wasm_hash
+}
+
+// Get the version a Pool was deployed from or last upgraded to by the Factory,
+// if it was created by the Factory
+pub fn read_pool_version(e: &Env, pool: &Address) -> Option<u32> {
+    let key = DataKeyFactory::PoolVersion(pool.clone());
+    e.storage().persistent().get::<DataKeyFactory, u32>(&key)
+}
+
+fn write_pool_version(e: &Env, pool: &Address, version: u32) {
+    let key = DataKeyFactory::PoolVersion(pool.clone());
+    e.storage().persistent().set(&key, &version);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+}
+
+// Propose an upgrade of `pool` to the WASM of `version`, which `upgrade_pool`
+// can execute once the upgrade delay of the pool has passed
+pub fn execute_propose_pool_upgrade(e: Env, pool: Address, version: u32) {
+    assert_with_error!(
+        &e,
+        execute_is_c_pool(e.clone(), pool.clone()),
+        Error::ErrNotCPool
+    );
+    let upgrade_args: Vec<Val> = vec![
+        &e,
+        read_active_wasm_hash(&e, version).into_val(&e),
+        e.current_contract_address().into_val(&e),
+    ];
+    e.invoke_contract::<()>(&pool, &Symbol::new(&e, "propose_upgrade"), upgrade_args);
+}
+
+// Upgrade `pool` to the WASM of `version` proposed with `propose_pool_upgrade`,
+// and migrate its storage in the same call if the layout changed
+pub fn execute_upgrade_pool(e: Env, pool: Address, version: u32) {
+    assert_with_error!(
+        &e,
+        execute_is_c_pool(e.clone(), pool.clone()),
+        Error::ErrNotCPool
+    );
+    let upgrade_args: Vec<Val> = vec![
+        &e,
+        read_active_wasm_hash(&e, version).into_val(&e),
+        e.current_contract_address().into_val(&e),
+    ];
+    e.invoke_contract::<()>(&pool, &symbol_short!("upgrade"), upgrade_args);
+
+    // the new code runs from this call on
+    if !e.invoke_contract::<bool>(&pool, &Symbol::new(&e, "is_migrated"), vec![&e]) {
+        let migrate_args: Vec<Val> = vec![&e, e.current_contract_address().into_val(&e)];
+        e.invoke_contract::<()>(&pool, &symbol_short!("migrate"), migrate_args);
+    }
+    write_pool_version(&e, &pool, version);
+}
+
+// Build the deployment salt derived from the controller and the provided salt
+fn pool_salt(e: &Env, salt: BytesN<32>, controller: &Address) -> BytesN<32> {
+    let mut as_u8s: [u8; 56] = [0; 56];
+    controller.to_string().copy_into_slice(&mut as_u8s);
+    let mut salt_as_bytes: Bytes = salt.into_val(e);
+    salt_as_bytes.extend_from_array(&as_u8s);
+    e.crypto().keccak256(&salt_as_bytes)
+}
+
+// Returns the Address `new_c_pool` will deploy the Pool to for `salt` and `controller`
+pub fn execute_predict_pool_address(e: Env, salt: BytesN<32>, controller: Address) -> Address {
+    e.deployer()
+        .with_current_contract(pool_salt(&e, salt, &controller))
+        .deployed_address()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn execute_new_c_pool(
     e: Env,
     salt: BytesN<32>,
     controller: Address,
+    version: u32,
     tokens: Vec<Address>,
     weights: Vec<i128>,
     balances: Vec<i128>,
@@ -30,45 +175,52 @@ pub fn execute_new_c_pool(
     e.storage()
         .instance()
         .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
-    let wasm_hash = e
-        .storage()
-        .instance()
-        .get::<DataKeyFactory, BytesN<32>>(&DataKeyFactory::WasmHash)
-        .unwrap_optimized();
-
-    // build salt dervied from user and provided salt to
-    let mut as_u8s: [u8; 56] = [0; 56];
-    controller.to_string().copy_into_slice(&mut as_u8s);
-    let mut salt_as_bytes: Bytes = salt.into_val(&e);
-    salt_as_bytes.extend_from_array(&as_u8s);
-    let new_salt = e.crypto().keccak256(&salt_as_bytes);
+    let wasm_hash = read_active_wasm_hash(&e, version);
 
+    let salt = pool_salt(&e, salt, &controller);
     let id = e
         .deployer()
-        .with_current_contract(new_salt)
+        .with_current_contract(salt.clone())
         .deploy(wasm_hash);
 
+    // announce the pool before its own events, so indexers trusting this
+    // factory also pick up the state the pool reports on init
+    let event: NewPoolEvent = NewPoolEvent {
+        caller: controller.clone(),
+        pool: id.clone(),
+    };
+    e.events()
+        .publish((symbol_short!("LOG"), symbol_short!("NEW_POOL")), event);
+
     let init_args: Vec<Val> = vec![
         &e,
         controller.into_val(&e),
-        tokens.into_val(&e),
+        tokens.clone().into_val(&e),
         weights.into_val(&e),
         balances.into_val(&e),
         swap_fee.into_val(&e),
     ];
     e.invoke_contract::<()>(&id, &symbol_short!("init"), init_args);
 
+    // let the pool sync the protocol fee settings from this factory, starting from the
+    // current ones, the salt proves to the pool that this factory deployed it
+    let factory_args: Vec<Val> = vec![
+        &e,
+        e.current_contract_address().into_val(&e),
+        salt.into_val(&e),
+        read_protocol_fee(&e).into_val(&e),
+    ];
+    e.invoke_contract::<()>(&id, &Symbol::new(&e, "set_factory"), factory_args);
+
     let key = DataKeyFactory::IsCpool(id.clone());
     e.storage().persistent().set(&key, &true);
     e.storage()
         .persistent()
         .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
-    let event: NewPoolEvent = NewPoolEvent {
-        caller: controller,
-        pool: id.clone(),
-    };
-    e.events()
-        .publish((symbol_short!("LOG"), symbol_short!("NEW_POOL")), event);
+    register_pool(&e, &id, &tokens);
+
+    write_pool_version(&e, &id, version);
+
     id
 }
 
@@ -84,3 +236,165 @@ pub fn execute_is_c_pool(e: Env, addr: Address) -> bool {
         false
     }
 }
+
+// Add the Pool to the ordered list of Pools, to the index of each of its tokens
+// and to the index of each pair of its tokens
+fn register_pool(e: &Env, pool: &Address, tokens: &Vec<Address>) {
+    let count = read_pool_count(e);
+    let key = DataKeyFactory::Pool(count);
+    e.storage().persistent().set(&key, pool);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+    e.storage()
+        .instance()
+        .set(&DataKeyFactory::PoolCount, &(count + 1));
+
+    for (i, token) in tokens.iter().enumerate() {
+        let count_key = DataKeyFactory::TokenPoolCount(token.clone());
+        let count = read_indexed_count(e, &count_key);
+        write_indexed_pool(
+            e,
+            &count_key,
+            &DataKeyFactory::TokenPool(token.clone(), count),
+            count,
+            pool,
+        );
+        for other in tokens.slice(i as u32 + 1..).iter() {
+            let (token_a, token_b) = sort_pair(token.clone(), other);
+            let count_key = DataKeyFactory::PairPoolCount(token_a.clone(), token_b.clone());
+            let count = read_indexed_count(e, &count_key);
+            write_indexed_pool(
+                e,
+                &count_key,
+                &DataKeyFactory::PairPool(token_a, token_b, count),
+                count,
+                pool,
+            );
+        }
+    }
+}
+
+// Order the tokens of a pair, so both orders share the same index
+fn sort_pair(token_a: Address, token_b: Address) -> (Address, Address) {
+    if token_a < token_b {
+        (token_a, token_b)
+    } else {
+        (token_b, token_a)
+    }
+}
+
+// Read the number of Pools stored under an index
+fn read_indexed_count(e: &Env, count_key: &DataKeyFactory) -> u32 {
+    if let Some(count) = e
+        .storage()
+        .persistent()
+        .get::<DataKeyFactory, u32>(count_key)
+    {
+        e.storage()
+            .persistent()
+            .extend_ttl(count_key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+        count
+    } else {
+        0
+    }
+}
+
+// Store the Pool at position `count` of an index
+fn write_indexed_pool(
+    e: &Env,
+    count_key: &DataKeyFactory,
+    key: &DataKeyFactory,
+    count: u32,
+    pool: &Address,
+) {
+    e.storage().persistent().set(key, pool);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+    e.storage().persistent().set(count_key, &(count + 1));
+    e.storage()
+        .persistent()
+        .extend_ttl(count_key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+}
+
+// Read up to `limit` Pools of an index, starting at position `start`
+// At most MAX_POOLS_PER_PAGE Pools are returned
+fn read_indexed_pools(
+    e: &Env,
+    count: u32,
+    start: u32,
+    limit: u32,
+    key: impl Fn(u32) -> DataKeyFactory,
+) -> Vec<Address> {
+    let end = start
+        .saturating_add(limit.min(MAX_POOLS_PER_PAGE))
+        .min(count);
+    let mut pools = Vec::new(e);
+    for i in start..end {
+        let key = key(i);
+        pools.push_back(
+            e.storage()
+                .persistent()
+                .get::<DataKeyFactory, Address>(&key)
+                .unwrap_optimized(),
+        );
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LARGE_LIFETIME_THRESHOLD, LARGE_BUMP_AMOUNT);
+    }
+    pools
+}
+
+// Get the number of Pools created by the Factory
+pub fn read_pool_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<DataKeyFactory, u32>(&DataKeyFactory::PoolCount)
+        .unwrap_or(0)
+}
+
+// Get up to `limit` Pools in order of creation, starting at index `start`
+// At most MAX_POOLS_PER_PAGE Pools are returned
+pub fn execute_get_pools(e: Env, start: u32, limit: u32) -> Vec<Address> {
+    read_indexed_pools(&e, read_pool_count(&e), start, limit, DataKeyFactory::Pool)
+}
+
+// Get the number of Pools holding `token`
+pub fn read_token_pool_count(e: &Env, token: &Address) -> u32 {
+    read_indexed_count(e, &DataKeyFactory::TokenPoolCount(token.clone()))
+}
+
+// Get up to `limit` Pools holding `token` in order of creation, starting at index `start`
+// At most MAX_POOLS_PER_PAGE Pools are returned
+pub fn execute_get_pools_for_token(e: Env, token: Address, start: u32, limit: u32) -> Vec<Address> {
+    let count = read_token_pool_count(&e, &token);
+    read_indexed_pools(&e, count, start, limit, |i| {
+        DataKeyFactory::TokenPool(token.clone(), i)
+    })
+}
+
+// Get the number of Pools holding both `token_a` and `token_b`
+pub fn read_pair_pool_count(e: &Env, token_a: Address, token_b: Address) -> u32 {
+    if token_a == token_b {
+        return 0;
+    }
+    let (token_a, token_b) = sort_pair(token_a, token_b);
+    read_indexed_count(e, &DataKeyFactory::PairPoolCount(token_a, token_b))
+}
+
+// Get up to `limit` Pools holding both `token_a` and `token_b` in order of creation,
+// starting at index `start`. At most MAX_POOLS_PER_PAGE Pools are returned
+pub fn execute_get_pools_for_pair(
+    e: Env,
+    token_a: Address,
+    token_b: Address,
+    start: u32,
+    limit: u32,
+) -> Vec<Address> {
+    let count = read_pair_pool_count(&e, token_a.clone(), token_b.clone());
+    let (token_a, token_b) = sort_pair(token_a, token_b);
+    read_indexed_pools(&e, count, start, limit, |i| {
+        DataKeyFactory::PairPool(token_a.clone(), token_b.clone(), i)
+    })
+}
@@ -8,4 +8,8 @@ pub enum Error {
     ErrNotCPool = 1,
     ErrNotController = 5,
     AlreadyInitialized = 7,
+    ErrNoPendingAdmin = 100,
+    ErrInvalidProtocolFee = 101,
+    ErrInvalidVersion = 102,
+    ErrDeprecatedVersion = 103,
 }
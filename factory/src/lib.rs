@@ -1,8 +1,22 @@
 #![no_std]
 
-use call_logic::factory::{execute_is_c_pool, execute_new_c_pool};
+use call_logic::{
+    admin::{
+        execute_accept_admin, execute_add_pool_version, execute_cancel_admin,
+        execute_deprecate_pool_version, execute_propose_admin, execute_set_default_version,
+        execute_set_protocol_fee, read_admin, read_pending_admin, read_protocol_fee,
+    },
+    factory::{
+        execute_get_pools, execute_get_pools_for_pair, execute_get_pools_for_token,
+        execute_is_c_pool, execute_new_c_pool, execute_predict_pool_address,
+        execute_propose_pool_upgrade, execute_upgrade_pool, read_default_version,
+        read_pair_pool_count, read_pool_count, read_pool_version, read_token_pool_count,
+        read_version_count, read_version_deprecated, read_wasm_hash,
+    },
+};
 use soroban_sdk::{
-    assert_with_error, contract, contractimpl, contracttype, Address, BytesN, Env, Vec,
+    assert_with_error, contract, contractimpl, contracttype, panic_with_error, Address, BytesN,
+    Env, Vec,
 };
 
 // Errors Listed
@@ -15,7 +29,29 @@ use crate::{call_logic::factory::execute_init, error::Error};
 #[contracttype]
 pub enum DataKeyFactory {
     IsCpool(Address),
-    WasmHash,
+    WasmHash(u32),
+    WasmVersionCount,
+    DefaultVersion,
+    Admin,
+    PendingAdmin,
+    ProtocolFee,
+    PoolCount,
+    Pool(u32),
+    TokenPoolCount(Address),
+    TokenPool(Address, u32),
+    PairPoolCount(Address, Address),
+    PairPool(Address, Address, u32),
+    PoolVersion(Address),
+    DeprecatedVersion(u32),
+}
+
+// Protocol Fee Settings, cached by the pools through `sync_protocol_fee`
+// `protocol_fee` is the share of the swap fee sent to the `fee_recipient`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolFee {
+    pub fee_recipient: Address,
+    pub protocol_fee: i128,
 }
 
 // Event to signal a new pool has been created
@@ -26,22 +62,113 @@ pub struct NewPoolEvent {
     pub pool: Address,
 }
 
+// Event to signal a pool WASM version was added or made the default
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionEvent {
+    pub version: u32,
+    pub wasm_hash: BytesN<32>,
+}
+
+// Event to signal a factory admin transfer was proposed, accepted or cancelled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminEvent {
+    pub admin: Address,
+    pub pending_admin: Address,
+}
+
+// Event to signal the protocol fee settings have changed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolFeeEvent {
+    pub fee_recipient: Address,
+    pub protocol_fee: i128,
+}
+
 #[contract]
 pub struct Factory;
 
 #[contractimpl]
 impl Factory {
-    // Initialize the Admin for the Factory Contract
-    pub fn init(e: Env, pool_wasm_hash: BytesN<32>) {
+    // Initialize the Admin for the Factory Contract, the Admin has to authorize it
+    // `pool_wasm_hash` is registered as pool version 0 and used by default
+    pub fn init(e: Env, pool_wasm_hash: BytesN<32>, admin: Address) {
         assert_with_error!(
             &e,
-            !e.storage().instance().has(&DataKeyFactory::WasmHash),
+            !e.storage().instance().has(&DataKeyFactory::Admin),
             Error::AlreadyInitialized
         );
-        execute_init(e, pool_wasm_hash);
+        admin.require_auth();
+        execute_init(e, pool_wasm_hash, admin);
     }
 
-    // Create a new Comet Pool
+    // Proposes a new Admin, only can be called by the current Admin
+    // The new Admin has to call `accept_admin` to take over the role
+    pub fn propose_admin(e: Env, new_admin: Address) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+        execute_propose_admin(e, new_admin, admin);
+    }
+
+    // Accepts the Admin role, only can be called by the pending Admin
+    pub fn accept_admin(e: Env) {
+        let pending_admin = read_pending_admin(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, Error::ErrNoPendingAdmin));
+        pending_admin.require_auth();
+        let admin = read_admin(&e);
+        execute_accept_admin(e, pending_admin, admin);
+    }
+
+    // Cancels the pending Admin transfer, only can be called by the current Admin
+    pub fn cancel_admin(e: Env) {
+        let admin = read_admin(&e);
+        admin.require_auth();
+        execute_cancel_admin(e, admin);
+    }
+
+    // Sets the protocol fee recipient and the share of the swap fee it receives
+    // Existing pools apply the new settings once `sync_protocol_fee` is called on them
+    pub fn set_protocol_fee(e: Env, fee_recipient: Address, protocol_fee: i128) {
+        read_admin(&e).require_auth();
+        execute_set_protocol_fee(e, fee_recipient, protocol_fee);
+    }
+
+    // Registers a new pool WASM version, only can be called by the Admin
+    // Returns the id of the new version
+    pub fn add_pool_version(e: Env, wasm_hash: BytesN<32>) -> u32 {
+        read_admin(&e).require_auth();
+        execute_add_pool_version(e, wasm_hash)
+    }
+
+    // Sets the pool version used by `new_c_pool`, only can be called by the Admin
+    pub fn set_default_version(e: Env, version: u32) {
+        read_admin(&e).require_auth();
+        execute_set_default_version(e, version);
+    }
+
+    // Deprecates a pool version, only can be called by the Admin
+    // No new pools are deployed from it and no pools are upgraded to it
+    pub fn deprecate_pool_version(e: Env, version: u32) {
+        read_admin(&e).require_auth();
+        execute_deprecate_pool_version(e, version);
+    }
+
+    // Proposes an upgrade of a Pool to a pool version, only can be called by the Admin
+    // The Pool's upgrade delay has to pass before `upgrade_pool` executes it
+    pub fn propose_pool_upgrade(e: Env, pool: Address, version: u32) {
+        read_admin(&e).require_auth();
+        execute_propose_pool_upgrade(e, pool, version);
+    }
+
+    // Upgrades a Pool to the proposed pool version and migrates its storage,
+    // only can be called by the Admin
+    pub fn upgrade_pool(e: Env, pool: Address, version: u32) {
+        read_admin(&e).require_auth();
+        execute_upgrade_pool(e, pool, version);
+    }
+
+    // Create a new Comet Pool from the default pool version
     pub fn new_c_pool(
         e: Env,
         salt: BytesN<32>,
@@ -52,13 +179,117 @@ impl Factory {
         swap_fee: i128,
     ) -> Address {
         controller.require_auth();
-        execute_new_c_pool(e, salt, controller, tokens, weights, balances, swap_fee)
+        let version = read_default_version(&e);
+        execute_new_c_pool(
+            e, salt, controller, version, tokens, weights, balances, swap_fee,
+        )
+    }
+
+    // Create a new Comet Pool from the given pool version
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_c_pool_with_version(
+        e: Env,
+        salt: BytesN<32>,
+        controller: Address,
+        version: u32,
+        tokens: Vec<Address>,
+        weights: Vec<i128>,
+        balances: Vec<i128>,
+        swap_fee: i128,
+    ) -> Address {
+        controller.require_auth();
+        execute_new_c_pool(
+            e, salt, controller, version, tokens, weights, balances, swap_fee,
+        )
+    }
+
+    // Get the Admin Address
+    pub fn get_admin(e: Env) -> Address {
+        read_admin(&e)
+    }
+
+    // Get the pending Admin Address, if a transfer has been proposed
+    pub fn get_pending_admin(e: Env) -> Option<Address> {
+        read_pending_admin(&e)
+    }
+
+    // Get the protocol fee settings, if a protocol fee is set
+    pub fn get_protocol_fee(e: Env) -> Option<ProtocolFee> {
+        read_protocol_fee(&e)
+    }
+
+    // Get the Address a Pool created with `salt` by `controller` will be deployed to
+    pub fn predict_pool_address(e: Env, salt: BytesN<32>, controller: Address) -> Address {
+        execute_predict_pool_address(e, salt, controller)
+    }
+
+    // Get the number of pool versions registered
+    pub fn get_version_count(e: Env) -> u32 {
+        read_version_count(&e)
+    }
+
+    // Get the pool version used by `new_c_pool`
+    pub fn get_default_version(e: Env) -> u32 {
+        read_default_version(&e)
+    }
+
+    // Get the WASM hash of a pool version
+    pub fn get_wasm_hash(e: Env, version: u32) -> BytesN<32> {
+        read_wasm_hash(&e, version)
+    }
+
+    // Returns true if the pool version has been deprecated
+    pub fn is_version_deprecated(e: Env, version: u32) -> bool {
+        read_version_deprecated(&e, version)
+    }
+
+    // Get the version a Pool was deployed from or last upgraded to through
+    // `upgrade_pool`, if it was created by the Factory. Upgrades done by the
+    // Pool's controller are not tracked
+    pub fn get_pool_version(e: Env, pool: Address) -> Option<u32> {
+        read_pool_version(&e, &pool)
     }
 
     // Returns true if the passed Address is a valid Pool
     pub fn is_c_pool(e: Env, addr: Address) -> bool {
         execute_is_c_pool(e, addr)
     }
+
+    // Get the number of Pools created by the Factory
+    pub fn get_pool_count(e: Env) -> u32 {
+        read_pool_count(&e)
+    }
+
+    // Get up to `limit` Pools in order of creation, starting at index `start`
+    pub fn get_pools(e: Env, start: u32, limit: u32) -> Vec<Address> {
+        execute_get_pools(e, start, limit)
+    }
+
+    // Get the number of Pools holding `token`
+    pub fn get_token_pool_count(e: Env, token: Address) -> u32 {
+        read_token_pool_count(&e, &token)
+    }
+
+    // Get up to `limit` Pools holding `token`, starting at index `start`
+    pub fn get_pools_for_token(e: Env, token: Address, start: u32, limit: u32) -> Vec<Address> {
+        execute_get_pools_for_token(e, token, start, limit)
+    }
+
+    // Get the number of Pools holding both `token_a` and `token_b`
+    pub fn get_pair_pool_count(e: Env, token_a: Address, token_b: Address) -> u32 {
+        read_pair_pool_count(&e, token_a, token_b)
+    }
+
+    // Get up to `limit` Pools holding both `token_a` and `token_b`, starting at index `start`
+    pub fn get_pools_for_pair(
+        e: Env,
+        token_a: Address,
+        token_b: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Address> {
+        execute_get_pools_for_pair(e, token_a, token_b, start, limit)
+    }
 }
 
 mod test;
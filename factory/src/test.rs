@@ -2,10 +2,17 @@
 
 extern crate std;
 
-use crate::{Factory, FactoryClient};
-use soroban_sdk::{testutils::Address as _, token::StellarAssetClient, vec, Address, BytesN, Env};
+use crate::{error::Error as FactoryError, Factory, FactoryClient, ProtocolFee};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec,
+    xdr::{ContractDataDurability, LedgerKey, ScAddress, ScVal, ToXdr},
+    Address, BytesN, Env, Error, IntoVal, Map, TryFromVal, Val, Vec,
+};
 
 // The contract that will be deployed by the deployer contract.
+#[allow(clippy::too_many_arguments)]
 mod contract {
     soroban_sdk::contractimport!(file = "../target/wasm32-unknown-unknown/optimized/comet.wasm");
 }
@@ -13,13 +20,24 @@ mod contract {
 #[test]
 fn test_factory() {
     let env = Env::default();
-    env.mock_all_auths();
     env.budget().reset_unlimited();
 
     let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
 
+    // the admin has to authorize the initialization
+    let admin = Address::generate(&env);
     let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
-    client.init(&wasm_hash);
+    assert!(client.try_init(&wasm_hash, &admin).is_err());
+    assert_eq!(
+        client.try_get_admin().err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrNotController as u32
+        )))
+    );
+
+    env.mock_all_auths();
+    client.init(&wasm_hash, &admin);
+    assert_eq!(env.auths()[0].0, admin);
 
     let controller = Address::generate(&env);
     let token_1 = env.register_stellar_asset_contract(controller.clone());
@@ -44,4 +62,652 @@ fn test_factory() {
     assert_eq!(pool_client.get_tokens(), tokens);
     assert_eq!(pool_client.get_swap_fee(), swap_fee);
     assert_eq!(pool_client.get_total_supply(), 100 * 1_0000000);
+    assert_eq!(pool_client.get_factory(), Some(client.address.clone()));
+}
+
+#[test]
+fn test_factory_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+    let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &admin);
+    assert_eq!(client.get_admin(), admin);
+
+    let result = client.try_accept_admin();
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrNoPendingAdmin as u32
+        )))
+    );
+
+    client.propose_admin(&new_admin);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_pending_admin(), Some(new_admin.clone()));
+    client.cancel_admin();
+    assert_eq!(client.get_pending_admin(), None);
+
+    client.propose_admin(&new_admin);
+    client.accept_admin();
+    assert_eq!(env.auths()[0].0, new_admin);
+    assert_eq!(client.get_admin(), new_admin);
+    assert_eq!(client.get_pending_admin(), None);
+}
+
+#[test]
+fn test_protocol_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+
+    let admin = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &admin);
+
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let token_1 = env.register_stellar_asset_contract(controller.clone());
+    let token_1_client = StellarAssetClient::new(&env, &token_1);
+    let token_2 = env.register_stellar_asset_contract(controller.clone());
+    let token_2_client = StellarAssetClient::new(&env, &token_2);
+    token_1_client.mint(&controller, &100_0000000);
+    token_2_client.mint(&controller, &100_0000000);
+    token_1_client.mint(&user, &100_0000000);
+
+    let tokens = vec![&env, token_1.clone(), token_2.clone()];
+    let weights = vec![&env, 0_5000000, 0_5000000];
+    let balances = vec![&env, 100_0000000, 100_0000000];
+    let salt = BytesN::from_array(&env, &[0; 32]);
+    let pool_id = client.new_c_pool(&salt, &controller, &tokens, &weights, &balances, &0_0100000);
+    let pool_client = contract::Client::new(&env, &pool_id);
+
+    // validates the protocol fee
+    let result = client.try_set_protocol_fee(&fee_recipient, &0_5000001);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrInvalidProtocolFee as u32
+        )))
+    );
+
+    // no protocol fee is charged until one is set
+    pool_client.swap_exact_amount_in(&token_1, &10_0000000, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(pool_client.get_protocol_fees(&token_1), 0);
+    assert_eq!(pool_client.get_balance(&token_1), 110_0000000);
+
+    // 20% of the 1% swap fee is set aside for the protocol
+    client.set_protocol_fee(&fee_recipient, &0_2000000);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(
+        client.get_protocol_fee(),
+        Some(ProtocolFee {
+            fee_recipient: fee_recipient.clone(),
+            protocol_fee: 0_2000000,
+        })
+    );
+
+    // existing pools keep the cached settings until they are synced
+    pool_client.swap_exact_amount_in(&token_1, &10_0000000, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(pool_client.get_protocol_fees(&token_1), 0);
+    assert_eq!(pool_client.get_balance(&token_1), 120_0000000);
+    pool_client.sync_protocol_fee();
+    let settings = pool_client.get_protocol_fee().unwrap();
+    assert_eq!(settings.fee_recipient, fee_recipient);
+    assert_eq!(settings.protocol_fee, 0_2000000);
+
+    pool_client.swap_exact_amount_in(&token_1, &10_0000000, &token_2, &0, &i128::MAX, &user);
+    assert_eq!(pool_client.get_protocol_fees(&token_1), 0_0200000);
+    assert_eq!(pool_client.get_balance(&token_1), 129_9800000);
+
+    // gulp does not absorb the protocol fees
+    pool_client.gulp(&token_1);
+    assert_eq!(pool_client.get_balance(&token_1), 129_9800000);
+
+    // the fees go to the recipient currently set on the factory
+    let new_recipient = Address::generate(&env);
+    client.set_protocol_fee(&new_recipient, &0_2000000);
+    pool_client.collect_protocol_fees();
+    let token_1_client = TokenClient::new(&env, &token_1);
+    assert_eq!(token_1_client.balance(&new_recipient), 0_0200000);
+    assert_eq!(token_1_client.balance(&pool_id), 129_9800000);
+    assert_eq!(pool_client.get_protocol_fees(&token_1), 0);
+
+    // new pools start from the current settings, and only accept them from the factory
+    StellarAssetClient::new(&env, &token_1).mint(&controller, &1_0000000);
+    token_2_client.mint(&controller, &1_0000000);
+    let pool_id = client.new_c_pool(
+        &BytesN::from_array(&env, &[1; 32]),
+        &controller,
+        &tokens,
+        &weights,
+        &vec![&env, 1_0000000, 1_0000000],
+        &0_0100000,
+    );
+    let pool_client = contract::Client::new(&env, &pool_id);
+    assert_eq!(
+        pool_client.get_protocol_fee().unwrap().fee_recipient,
+        new_recipient
+    );
+    let result = pool_client.try_set_factory(&admin, &salt, &None);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            contract::Error::AlreadyInitialized as u32
+        )))
+    );
+}
+
+#[test]
+fn test_pool_upgrade() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+
+    let admin = Address::generate(&env);
+    let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &admin);
+
+    let controller = Address::generate(&env);
+    let token_1 = env.register_stellar_asset_contract(controller.clone());
+    let token_2 = env.register_stellar_asset_contract(controller.clone());
+    StellarAssetClient::new(&env, &token_1).mint(&controller, &1_0000000);
+    StellarAssetClient::new(&env, &token_2).mint(&controller, &1_0000000);
+
+    let tokens = vec![&env, token_1.clone(), token_2.clone()];
+    let weights = vec![&env, 0_5000000, 0_5000000];
+    let balances = vec![&env, 1_0000000, 1_0000000];
+    let salt = BytesN::from_array(&env, &[0; 32]);
+    let pool_id = client.new_c_pool(&salt, &controller, &tokens, &weights, &balances, &0_0030000);
+    let pool_client = contract::Client::new(&env, &pool_id);
+    assert_eq!(pool_client.get_storage_version(), 1);
+
+    // upgrades have to be proposed and wait for the pool's upgrade delay
+    let result = pool_client.try_upgrade(&wasm_hash, &controller);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            contract::ErrorExt::ErrNoPendingUpgrade as u32
+        )))
+    );
+    let result = client.try_propose_pool_upgrade(&Address::generate(&env), &0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrNotCPool as u32
+        )))
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    client.propose_pool_upgrade(&pool_id, &0);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(pool_client.get_pending_upgrade().unwrap().eta, 1000 + 86400);
+    env.ledger().with_mut(|li| li.timestamp = 1000 + 86399);
+    assert!(client.try_upgrade_pool(&pool_id, &0).is_err());
+
+    // a pool deployed before storage versioning is migrated along with the upgrade
+    env.as_contract(&pool_id, || {
+        env.storage()
+            .instance()
+            .remove(&contract::DataKey::StorageVersion);
+    });
+    assert!(!pool_client.is_migrated());
+    env.ledger().with_mut(|li| li.timestamp = 1000 + 86400);
+    client.upgrade_pool(&pool_id, &0);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(pool_client.get_pending_upgrade(), None);
+    assert!(pool_client.is_migrated());
+    assert_eq!(pool_client.get_storage_version(), 1);
+    assert_eq!(client.get_pool_version(&pool_id), Some(0));
+
+    // the pool keeps its state across the upgrade
+    assert_eq!(pool_client.get_controller(), controller);
+    assert_eq!(pool_client.get_tokens(), tokens);
+    assert_eq!(pool_client.get_balance(&token_1), 1_0000000);
+}
+
+#[test]
+fn test_pool_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+
+    let admin = Address::generate(&env);
+    let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &admin);
+    assert_eq!(client.get_pool_count(), 0);
+    assert_eq!(client.get_pools(&0, &10), vec![&env]);
+
+    let controller = Address::generate(&env);
+    let token_1 = env.register_stellar_asset_contract(controller.clone());
+    let token_2 = env.register_stellar_asset_contract(controller.clone());
+    let token_3 = env.register_stellar_asset_contract(controller.clone());
+    for token in [&token_1, &token_2, &token_3] {
+        StellarAssetClient::new(&env, token).mint(&controller, &10_0000000);
+    }
+
+    let weights = vec![&env, 0_5000000, 0_5000000];
+    let balances = vec![&env, 1_0000000, 1_0000000];
+    let pool_a = client.new_c_pool(
+        &BytesN::from_array(&env, &[0; 32]),
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    let pool_b = client.new_c_pool(
+        &BytesN::from_array(&env, &[1; 32]),
+        &controller,
+        &vec![&env, token_2.clone(), token_3.clone()],
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    let pool_c = client.new_c_pool(
+        &BytesN::from_array(&env, &[2; 32]),
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+
+    assert_eq!(client.get_pool_count(), 3);
+    assert_eq!(
+        client.get_pools(&0, &10),
+        vec![&env, pool_a.clone(), pool_b.clone(), pool_c.clone()]
+    );
+    assert_eq!(client.get_pools(&1, &1), vec![&env, pool_b.clone()]);
+    assert_eq!(client.get_pools(&3, &10), vec![&env]);
+    assert_eq!(client.get_pools(&u32::MAX, &u32::MAX), vec![&env]);
+
+    assert_eq!(client.get_token_pool_count(&token_1), 2);
+    assert_eq!(
+        client.get_pools_for_token(&token_1, &0, &10),
+        vec![&env, pool_a.clone(), pool_c.clone()]
+    );
+    assert_eq!(
+        client.get_pools_for_token(&token_2, &0, &10),
+        vec![&env, pool_a.clone(), pool_b.clone(), pool_c.clone()]
+    );
+    assert_eq!(
+        client.get_pools_for_token(&token_2, &1, &1),
+        vec![&env, pool_b.clone()]
+    );
+    assert_eq!(client.get_pools_for_token(&token_2, &3, &10), vec![&env]);
+    assert_eq!(
+        client.get_pools_for_token(&Address::generate(&env), &0, &10),
+        vec![&env]
+    );
+
+    // pairs are indexed in both orders
+    assert_eq!(client.get_pair_pool_count(&token_1, &token_2), 2);
+    assert_eq!(
+        client.get_pools_for_pair(&token_2, &token_1, &0, &10),
+        vec![&env, pool_a.clone(), pool_c.clone()]
+    );
+    assert_eq!(
+        client.get_pools_for_pair(&token_1, &token_2, &1, &10),
+        vec![&env, pool_c.clone()]
+    );
+    assert_eq!(
+        client.get_pools_for_pair(&token_3, &token_2, &0, &10),
+        vec![&env, pool_b.clone()]
+    );
+    assert_eq!(
+        client.get_pools_for_pair(&token_1, &token_3, &0, &10),
+        vec![&env]
+    );
+    assert_eq!(client.get_pair_pool_count(&token_1, &token_1), 0);
+    assert_eq!(
+        client.get_pools_for_pair(&token_1, &token_1, &0, &10),
+        vec![&env]
+    );
+    assert_eq!(
+        client.get_pools_for_pair(&token_1, &token_2, &u32::MAX, &u32::MAX),
+        vec![&env]
+    );
+}
+
+#[test]
+fn test_pool_versions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+
+    let admin = Address::generate(&env);
+    let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &admin);
+    assert_eq!(client.get_version_count(), 1);
+    assert_eq!(client.get_default_version(), 0);
+    assert_eq!(client.get_wasm_hash(&0), wasm_hash);
+
+    let result = client.try_set_default_version(&1);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrInvalidVersion as u32
+        )))
+    );
+
+    assert_eq!(client.add_pool_version(&wasm_hash), 1);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_version_count(), 2);
+    assert_eq!(client.get_wasm_hash(&1), wasm_hash);
+
+    let controller = Address::generate(&env);
+    let token_1 = env.register_stellar_asset_contract(controller.clone());
+    let token_2 = env.register_stellar_asset_contract(controller.clone());
+    StellarAssetClient::new(&env, &token_1).mint(&controller, &10_0000000);
+    StellarAssetClient::new(&env, &token_2).mint(&controller, &10_0000000);
+
+    let tokens = vec![&env, token_1.clone(), token_2.clone()];
+    let weights = vec![&env, 0_5000000, 0_5000000];
+    let balances = vec![&env, 1_0000000, 1_0000000];
+
+    // pools are deployed from the default version unless one is given
+    let pool_a = client.new_c_pool(
+        &BytesN::from_array(&env, &[0; 32]),
+        &controller,
+        &tokens,
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    assert_eq!(client.get_pool_version(&pool_a), Some(0));
+
+    client.set_default_version(&1);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.get_default_version(), 1);
+    let pool_b = client.new_c_pool(
+        &BytesN::from_array(&env, &[1; 32]),
+        &controller,
+        &tokens,
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    assert_eq!(client.get_pool_version(&pool_b), Some(1));
+
+    let pool_c = client.new_c_pool_with_version(
+        &BytesN::from_array(&env, &[2; 32]),
+        &controller,
+        &0,
+        &tokens,
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    assert_eq!(client.get_pool_version(&pool_c), Some(0));
+    assert!(client.is_c_pool(&pool_c));
+
+    let result = client.try_new_c_pool_with_version(
+        &BytesN::from_array(&env, &[3; 32]),
+        &controller,
+        &2,
+        &tokens,
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrInvalidVersion as u32
+        )))
+    );
+    assert_eq!(client.get_pool_version(&Address::generate(&env)), None);
+
+    // deprecated versions can't be deployed or made the default
+    let result = client.try_deprecate_pool_version(&1);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrDeprecatedVersion as u32
+        )))
+    );
+    assert!(!client.is_version_deprecated(&0));
+    client.deprecate_pool_version(&0);
+    assert_eq!(env.auths()[0].0, admin);
+    assert!(client.is_version_deprecated(&0));
+    let result = client.try_new_c_pool_with_version(
+        &BytesN::from_array(&env, &[3; 32]),
+        &controller,
+        &0,
+        &tokens,
+        &weights,
+        &balances,
+        &0_0030000,
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrDeprecatedVersion as u32
+        )))
+    );
+    let result = client.try_set_default_version(&0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrDeprecatedVersion as u32
+        )))
+    );
+
+    // pools deployed from it can still be upgraded to another version
+    let result = client.try_propose_pool_upgrade(&pool_c, &0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            FactoryError::ErrDeprecatedVersion as u32
+        )))
+    );
+    client.propose_pool_upgrade(&pool_c, &1);
+    env.ledger().with_mut(|li| li.timestamp += 86400);
+    client.upgrade_pool(&pool_c, &1);
+    assert_eq!(client.get_pool_version(&pool_c), Some(1));
+}
+
+#[test]
+fn test_predict_pool_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+
+    let admin = Address::generate(&env);
+    let client = FactoryClient::new(&env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &admin);
+
+    let controller = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token_1 = env.register_stellar_asset_contract(controller.clone());
+    let token_2 = env.register_stellar_asset_contract(controller.clone());
+    StellarAssetClient::new(&env, &token_1).mint(&controller, &1_0000000);
+    StellarAssetClient::new(&env, &token_2).mint(&controller, &1_0000000);
+
+    // the address depends on both the salt and the controller
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    let predicted = client.predict_pool_address(&salt, &controller);
+    assert_ne!(predicted, client.predict_pool_address(&salt, &other));
+    assert_ne!(
+        predicted,
+        client.predict_pool_address(&BytesN::from_array(&env, &[8; 32]), &controller)
+    );
+
+    let pool_id = client.new_c_pool(
+        &salt,
+        &controller,
+        &vec![&env, token_1.clone(), token_2.clone()],
+        &vec![&env, 0_5000000, 0_5000000],
+        &vec![&env, 1_0000000, 1_0000000],
+        &0_0030000,
+    );
+    assert_eq!(pool_id, predicted);
+}
+
+// An equal weight pool of `n` tokens deployed from the pool WASM,
+// and a user holding each of its tokens
+fn equal_weight_pool(env: &Env, n: u32) -> (contract::Client, Vec<Address>, Address) {
+    let wasm_hash = env.deployer().upload_contract_wasm(contract::WASM);
+    let client = FactoryClient::new(env, &env.register_contract(None, Factory));
+    client.init(&wasm_hash, &Address::generate(env));
+
+    let controller = Address::generate(env);
+    let user = Address::generate(env);
+    let mut tokens = Vec::new(env);
+    let mut weights = Vec::new(env);
+    let mut balances = Vec::new(env);
+    for _ in 0..n {
+        let token = env.register_stellar_asset_contract(controller.clone());
+        StellarAssetClient::new(env, &token).mint(&controller, &1000_0000000);
+        StellarAssetClient::new(env, &token).mint(&user, &1000_0000000);
+        tokens.push_back(token);
+        weights.push_back(1_0000000 / n as i128);
+        balances.push_back(1000_0000000);
+    }
+    let salt = BytesN::from_array(env, &[0; 32]);
+    let pool_id = client.new_c_pool(&salt, &controller, &tokens, &weights, &balances, &0_0030000);
+    (contract::Client::new(env, &pool_id), tokens, user)
+}
+
+#[test]
+fn test_swap_footprint() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    let (pool_client, tokens, user) = equal_weight_pool(&env, 8);
+    let pool_id = pool_client.address.clone();
+    let token_in = tokens.get_unchecked(0);
+    let token_out = tokens.get_unchecked(1);
+    let pool = ScAddress::try_from(&pool_id).unwrap();
+    let data_key = |key: contract::DataKey| {
+        let key: Val = key.into_val(&env);
+        ScVal::try_from_val(&env, &key).unwrap()
+    };
+
+    // the keys of the persistent pool entries read or written by a swap
+    let swap_keys = || {
+        env.host()
+            .with_mut_storage(|storage| {
+                storage.footprint = Default::default();
+                Ok(())
+            })
+            .unwrap();
+        pool_client.swap_exact_amount_in(&token_in, &1_0000000, &token_out, &0, &i128::MAX, &user);
+        let budget = env.host().budget_cloned();
+        env.host()
+            .with_mut_storage(|storage| {
+                Ok(storage
+                    .footprint
+                    .0
+                    .iter(&budget)?
+                    .filter_map(|(key, _)| match key.as_ref() {
+                        LedgerKey::ContractData(data)
+                            if data.contract == pool
+                                && data.durability == ContractDataDurability::Persistent =>
+                        {
+                            Some(data.key.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect::<std::vec::Vec<ScVal>>())
+            })
+            .unwrap()
+    };
+
+    // a swap only touches the records of the two tokens it trades, in the
+    // ledger of a previous swap and in a new ledger where the oracle updates
+    swap_keys();
+    let same_ledger = swap_keys();
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 1;
+        li.timestamp += 5;
+    });
+    let new_ledger = swap_keys();
+    for keys in [same_ledger, new_ledger] {
+        let touched: std::vec::Vec<Address> = tokens
+            .iter()
+            .filter(|token| keys.contains(&data_key(contract::DataKey::Record(token.clone()))))
+            .collect();
+        assert_eq!(touched, [token_in.clone(), token_out.clone()]);
+        assert!(!keys.contains(&data_key(contract::DataKey::AllRecordData)));
+    }
+
+    // and reads and writes at most a quarter of the bytes of the map of every
+    // record, which each swap read and wrote before storage version 1
+    let (record_bytes, map_bytes) = env.as_contract(&pool_id, || {
+        let mut records = Map::<Address, contract::Record>::new(&env);
+        for token in tokens.iter() {
+            let key = contract::DataKey::Record(token.clone());
+            let record: contract::Record = env.storage().persistent().get(&key).unwrap();
+            records.set(token, record);
+        }
+        let record_bytes = records.get_unchecked(token_in.clone()).to_xdr(&env).len()
+            + records.get_unchecked(token_out.clone()).to_xdr(&env).len();
+        (record_bytes, records.to_xdr(&env).len())
+    });
+    assert!(record_bytes * 4 <= map_bytes);
+}
+
+// CPU instructions and memory bytes of a swap in a pool of `n` tokens, in the
+// ledger of a previous swap, and in a new ledger where the oracle accumulates
+// the prices of every pair
+fn bench_swap(n: u32) -> [(u64, u64); 2] {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    let (pool_client, tokens, user) = equal_weight_pool(&env, n);
+    let token_in = tokens.get_unchecked(0);
+    let token_out = tokens.get_unchecked(1);
+    let swap = || {
+        env.budget().reset_unlimited();
+        pool_client.swap_exact_amount_in(&token_in, &1_0000000, &token_out, &0, &i128::MAX, &user);
+        (
+            env.budget().cpu_instruction_cost(),
+            env.budget().memory_bytes_cost(),
+        )
+    };
+
+    swap();
+    let same_ledger = swap();
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 1;
+        li.timestamp += 5;
+    });
+    let new_ledger = swap();
+    [same_ledger, new_ledger]
+}
+
+#[test]
+fn bench_swap_budget() {
+    let two = bench_swap(2);
+    let eight = bench_swap(8);
+    std::println!("swap budget              cpu        mem");
+    for (name, i) in [("same ledger", 0), ("new ledger", 1)] {
+        std::println!("2 tokens, {:<11} {:>10} {:>10}", name, two[i].0, two[i].1);
+        std::println!(
+            "8 tokens, {:<11} {:>10} {:>10}",
+            name,
+            eight[i].0,
+            eight[i].1
+        );
+    }
+
+    // within a ledger, a swap costs about the same whatever the number of tokens
+    assert!(eight[0].0 * 100 <= two[0].0 * 101);
+    assert!(eight[0].1 * 100 <= two[0].1 * 103);
 }
@@ -0,0 +1,36 @@
+use std::fmt;
+
+// Errors returned while reading and decoding events
+#[derive(Debug)]
+pub enum Error {
+    // The input is not valid base64 XDR
+    Xdr(stellar_xdr::curr::Error),
+    // The input is not a valid JSON array of events
+    Json(serde_json::Error),
+    // A pool event is missing a field or has a field of the wrong type
+    InvalidEvent(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Xdr(err) => write!(f, "invalid xdr: {}", err),
+            Error::Json(err) => write!(f, "invalid json: {}", err),
+            Error::InvalidEvent(field) => write!(f, "invalid event field: {}", field),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<stellar_xdr::curr::Error> for Error {
+    fn from(err: stellar_xdr::curr::Error) -> Self {
+        Error::Xdr(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
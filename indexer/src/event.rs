@@ -0,0 +1,239 @@
+//! Decoding of the Comet pool and factory events
+use stellar_xdr::curr::{
+    ContractEvent, ContractEventBody, ContractEventType, Limits, ReadXdr, ScAddress, ScMap, ScVal,
+};
+
+use crate::error::Error;
+
+// A pool or factory event with its addresses as strkeys
+//
+// Fields added by later event versions are optional, so events published
+// before EVENT_VERSION 2 can still be replayed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PoolEvent {
+    // LOG/NEW_POOL, published by the factory
+    NewPool {
+        pool: String,
+    },
+    // POOL/swap
+    Swap {
+        token_in: String,
+        token_out: String,
+        amount_in: i128,
+        amount_out: i128,
+        fee_amount: Option<i128>,
+        balance_in: Option<i128>,
+        balance_out: Option<i128>,
+        total_supply: Option<i128>,
+    },
+    // POOL/join_pool, once per token
+    Join {
+        token: String,
+        amount_in: i128,
+        balance: Option<i128>,
+        total_supply: Option<i128>,
+    },
+    // POOL/exit_pool, once per token
+    Exit {
+        token: String,
+        amount_out: i128,
+        exit_fee_amount: Option<i128>,
+        balance: Option<i128>,
+        total_supply: Option<i128>,
+    },
+    // POOL/deposit
+    Deposit {
+        token: String,
+        amount_in: i128,
+        fee_amount: Option<i128>,
+        balance: Option<i128>,
+        total_supply: Option<i128>,
+    },
+    // POOL/withdraw
+    Withdraw {
+        token: String,
+        amount_out: i128,
+        fee_amount: Option<i128>,
+        exit_fee_amount: Option<i128>,
+        balance: Option<i128>,
+        total_supply: Option<i128>,
+    },
+    // POOL/flashloan
+    FlashLoan {
+        token: String,
+        fee: i128,
+        balance: Option<i128>,
+        total_supply: Option<i128>,
+    },
+    // POOL/sync, the full pool state
+    Sync {
+        tokens: Vec<String>,
+        balances: Vec<i128>,
+        weights: Vec<i128>,
+        swap_fee: i128,
+        total_supply: i128,
+    },
+}
+
+// Read events encoded as one base64 XDR `ContractEvent` per line
+pub fn read_xdr(input: &str) -> Result<Vec<ContractEvent>, Error> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(ContractEvent::from_xdr_base64(line, Limits::none())?))
+        .collect()
+}
+
+// Read events encoded as a JSON array of `ContractEvent`
+pub fn read_json(input: &str) -> Result<Vec<ContractEvent>, Error> {
+    Ok(serde_json::from_str(input)?)
+}
+
+// Get the address of the contract that published `event`, None for system
+// and diagnostic events
+pub fn event_contract(event: &ContractEvent) -> Option<String> {
+    match (&event.type_, &event.contract_id) {
+        (ContractEventType::Contract, Some(hash)) => {
+            Some(ScAddress::Contract(hash.clone()).to_string())
+        }
+        _ => None,
+    }
+}
+
+// Decode `event` into the address of the contract that published it and a `PoolEvent`
+//
+// Returns None for events that are not published by a pool or a factory
+pub fn decode_event(event: &ContractEvent) -> Result<Option<(String, PoolEvent)>, Error> {
+    let contract = match event_contract(event) {
+        Some(contract) => contract,
+        None => return Ok(None),
+    };
+    let ContractEventBody::V0(body) = &event.body;
+    let (prefix, name) = match body.topics.as_slice() {
+        [prefix, name] => match (as_symbol(prefix), as_symbol(name)) {
+            (Some(prefix), Some(name)) => (prefix, name),
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    let data = match &body.data {
+        ScVal::Map(Some(map)) => map,
+        _ => return Ok(None),
+    };
+
+    let event = match (prefix, name) {
+        ("LOG", "NEW_POOL") => PoolEvent::NewPool {
+            pool: required(data, "pool", as_address)?,
+        },
+        ("POOL", "swap") => PoolEvent::Swap {
+            token_in: required(data, "token_in", as_address)?,
+            token_out: required(data, "token_out", as_address)?,
+            amount_in: required(data, "token_amount_in", as_i128)?,
+            amount_out: required(data, "token_amount_out", as_i128)?,
+            fee_amount: optional(data, "fee_amount", as_i128)?,
+            balance_in: optional(data, "balance_in", as_i128)?,
+            balance_out: optional(data, "balance_out", as_i128)?,
+            total_supply: optional(data, "total_supply", as_i128)?,
+        },
+        ("POOL", "join_pool") => PoolEvent::Join {
+            token: required(data, "token_in", as_address)?,
+            amount_in: required(data, "token_amount_in", as_i128)?,
+            balance: optional(data, "balance", as_i128)?,
+            total_supply: optional(data, "total_supply", as_i128)?,
+        },
+        ("POOL", "exit_pool") => PoolEvent::Exit {
+            token: required(data, "token_out", as_address)?,
+            amount_out: required(data, "token_amount_out", as_i128)?,
+            exit_fee_amount: optional(data, "exit_fee_amount", as_i128)?,
+            balance: optional(data, "balance", as_i128)?,
+            total_supply: optional(data, "total_supply", as_i128)?,
+        },
+        ("POOL", "deposit") => PoolEvent::Deposit {
+            token: required(data, "token_in", as_address)?,
+            amount_in: required(data, "token_amount_in", as_i128)?,
+            fee_amount: optional(data, "fee_amount", as_i128)?,
+            balance: optional(data, "balance", as_i128)?,
+            total_supply: optional(data, "total_supply", as_i128)?,
+        },
+        ("POOL", "withdraw") => PoolEvent::Withdraw {
+            token: required(data, "token_out", as_address)?,
+            amount_out: required(data, "token_amount_out", as_i128)?,
+            fee_amount: optional(data, "fee_amount", as_i128)?,
+            exit_fee_amount: optional(data, "exit_fee_amount", as_i128)?,
+            balance: optional(data, "balance", as_i128)?,
+            total_supply: optional(data, "total_supply", as_i128)?,
+        },
+        ("POOL", "flashloan") => PoolEvent::FlashLoan {
+            token: required(data, "token", as_address)?,
+            fee: required(data, "fee", as_i128)?,
+            balance: optional(data, "balance", as_i128)?,
+            total_supply: optional(data, "total_supply", as_i128)?,
+        },
+        ("POOL", "sync") => PoolEvent::Sync {
+            tokens: required(data, "tokens", |v| as_vec(v, as_address))?,
+            balances: required(data, "balances", |v| as_vec(v, as_i128))?,
+            weights: required(data, "weights", |v| as_vec(v, as_i128))?,
+            swap_fee: required(data, "swap_fee", as_i128)?,
+            total_supply: required(data, "total_supply", as_i128)?,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some((contract, event)))
+}
+
+fn field<'a>(map: &'a ScMap, name: &str) -> Option<&'a ScVal> {
+    map.iter()
+        .find(|entry| as_symbol(&entry.key) == Some(name))
+        .map(|entry| &entry.val)
+}
+
+fn required<T>(
+    map: &ScMap,
+    name: &'static str,
+    decode: impl Fn(&ScVal) -> Option<T>,
+) -> Result<T, Error> {
+    field(map, name)
+        .and_then(decode)
+        .ok_or(Error::InvalidEvent(name))
+}
+
+// A missing field is None, a field of the wrong type is an error
+fn optional<T>(
+    map: &ScMap,
+    name: &'static str,
+    decode: impl Fn(&ScVal) -> Option<T>,
+) -> Result<Option<T>, Error> {
+    match field(map, name) {
+        Some(val) => decode(val).map(Some).ok_or(Error::InvalidEvent(name)),
+        None => Ok(None),
+    }
+}
+
+fn as_symbol(val: &ScVal) -> Option<&str> {
+    match val {
+        ScVal::Symbol(symbol) => std::str::from_utf8(symbol.0.as_vec()).ok(),
+        _ => None,
+    }
+}
+
+fn as_address(val: &ScVal) -> Option<String> {
+    match val {
+        ScVal::Address(address) => Some(address.to_string()),
+        _ => None,
+    }
+}
+
+fn as_i128(val: &ScVal) -> Option<i128> {
+    match val {
+        ScVal::I128(parts) => Some(parts.into()),
+        _ => None,
+    }
+}
+
+fn as_vec<T>(val: &ScVal, decode: impl Fn(&ScVal) -> Option<T>) -> Option<Vec<T>> {
+    match val {
+        ScVal::Vec(Some(vec)) => vec.iter().map(decode).collect(),
+        _ => None,
+    }
+}
@@ -0,0 +1,83 @@
+//! Off-chain indexer which rebuilds the state of Comet pools from their events
+use std::collections::{BTreeMap, BTreeSet};
+
+use stellar_xdr::curr::ContractEvent;
+
+// Errors Listed
+pub mod error;
+// Event Decoding
+pub mod event;
+// Pool State and Metrics
+pub mod pool;
+
+mod test;
+
+use crate::{
+    error::Error,
+    event::{decode_event, event_contract, PoolEvent},
+    pool::Pool,
+};
+
+// Pools keyed by their contract address
+//
+// Only pools announced by a trusted factory are indexed, the events of any
+// other contract are ignored
+#[derive(Clone, Debug)]
+pub struct Indexer {
+    factories: BTreeSet<String>,
+    pools: BTreeMap<String, Pool>,
+}
+
+impl Indexer {
+    // Index the pools deployed by `factories`
+    pub fn new(factories: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            factories: factories.into_iter().collect(),
+            pools: BTreeMap::new(),
+        }
+    }
+
+    // Apply `event`, events which are not published by a trusted factory or
+    // one of its pools are ignored without being decoded, so a malformed event
+    // of another contract cannot stop the indexing
+    pub fn ingest(&mut self, event: &ContractEvent) -> Result<(), Error> {
+        match event_contract(event) {
+            Some(contract)
+                if self.factories.contains(&contract) || self.pools.contains_key(&contract) => {}
+            _ => return Ok(()),
+        }
+        match decode_event(event)? {
+            Some((factory, PoolEvent::NewPool { pool })) => {
+                if self.factories.contains(&factory) {
+                    self.pools.entry(pool).or_insert_with(|| Pool {
+                        factory,
+                        ..Pool::default()
+                    });
+                }
+            }
+            Some((pool, event)) => {
+                if let Some(pool) = self.pools.get_mut(&pool) {
+                    pool.apply(&event);
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    // Apply `events` in order
+    pub fn ingest_all<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+    ) -> Result<(), Error> {
+        events.into_iter().try_for_each(|event| self.ingest(event))
+    }
+
+    pub fn pool(&self, id: &str) -> Option<&Pool> {
+        self.pools.get(id)
+    }
+
+    pub fn pools(&self) -> &BTreeMap<String, Pool> {
+        &self.pools
+    }
+}
@@ -0,0 +1,60 @@
+//! Replay recorded events and print the state and metrics of every pool
+//!
+//! usage: indexer <events.xdr | events.json> <factory>...
+//!
+//! Only the pools deployed by the given factories are indexed
+use std::{env, fs, process};
+
+use indexer::{
+    event::{read_json, read_xdr},
+    Indexer,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (path, factories) = match args.split_first() {
+        Some((path, factories)) if !factories.is_empty() => (path, factories),
+        _ => {
+            eprintln!("usage: indexer <events.xdr | events.json> <factory>...");
+            process::exit(2);
+        }
+    };
+    if let Err(err) = run(path, factories) {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    }
+}
+
+fn run(path: &str, factories: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let input = fs::read_to_string(path)?;
+    let events = if path.ends_with(".json") {
+        read_json(&input)?
+    } else {
+        read_xdr(&input)?
+    };
+
+    let mut indexer = Indexer::new(factories.iter().cloned());
+    indexer.ingest_all(&events)?;
+
+    for (id, pool) in indexer.pools() {
+        println!("pool {}", id);
+        println!("  factory      {}", pool.factory);
+        println!("  total shares {}", pool.total_shares);
+        println!("  swap fee     {}", pool.swap_fee);
+        println!("  swaps        {}", pool.metrics.swaps);
+        println!("  exit fees    {}", pool.metrics.exit_fees);
+        for token in &pool.tokens {
+            let record = &pool.records[token];
+            println!("  token {}", token);
+            println!("    balance {}", record.balance);
+            println!("    weight  {}", record.weight);
+            println!(
+                "    volume  {}",
+                pool.metrics.volume.get(token).unwrap_or(&0)
+            );
+            println!("    fees    {}", pool.metrics.fees.get(token).unwrap_or(&0));
+            println!("    tvl     {}", pool.tvl(token).unwrap_or(0));
+        }
+    }
+    Ok(())
+}
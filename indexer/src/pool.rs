@@ -0,0 +1,178 @@
+//! In-memory model of a Comet pool, rebuilt from its events
+use std::collections::BTreeMap;
+
+use crate::event::PoolEvent;
+
+pub const STROOP: i128 = 10i128.pow(7);
+
+// Balance and weight of a bound token, as in the pool `Record` map
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    pub balance: i128,
+    pub weight: i128,
+}
+
+// Metrics derived from the trades of a pool, keyed by token
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Metrics {
+    // Amount of each token swapped into the pool
+    pub volume: BTreeMap<String, i128>,
+    // Swap fees paid in each token, including the fees of single token deposits and withdrawals
+    pub fees: BTreeMap<String, i128>,
+    // Exit fees paid in LP tokens
+    pub exit_fees: i128,
+    pub swaps: u64,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Pool {
+    // The factory which announced the pool
+    pub factory: String,
+    pub tokens: Vec<String>,
+    pub records: BTreeMap<String, Record>,
+    pub total_shares: i128,
+    pub swap_fee: i128,
+    pub metrics: Metrics,
+}
+
+impl Pool {
+    // Apply an event published by the pool
+    //
+    // Balances and total supply are taken from the event when it carries them,
+    // otherwise balances are updated from the traded amounts
+    pub fn apply(&mut self, event: &PoolEvent) {
+        match event {
+            PoolEvent::NewPool { .. } => {}
+            PoolEvent::Swap {
+                token_in,
+                token_out,
+                amount_in,
+                amount_out,
+                fee_amount,
+                balance_in,
+                balance_out,
+                total_supply,
+            } => {
+                self.set_balance(token_in, *balance_in, *amount_in);
+                self.set_balance(token_out, *balance_out, -amount_out);
+                self.set_total_shares(*total_supply);
+                let fee_amount = fee_amount.unwrap_or(amount_in * self.swap_fee / STROOP);
+                *self.metrics.volume.entry(token_in.clone()).or_default() += amount_in;
+                *self.metrics.fees.entry(token_in.clone()).or_default() += fee_amount;
+                self.metrics.swaps += 1;
+            }
+            PoolEvent::Join {
+                token,
+                amount_in,
+                balance,
+                total_supply,
+            } => {
+                self.set_balance(token, *balance, *amount_in);
+                self.set_total_shares(*total_supply);
+            }
+            PoolEvent::Exit {
+                token,
+                amount_out,
+                exit_fee_amount,
+                balance,
+                total_supply,
+            } => {
+                self.set_balance(token, *balance, -amount_out);
+                self.set_total_shares(*total_supply);
+                // the exit fee is reported once per token of the same exit
+                if Some(token) == self.tokens.first() {
+                    self.metrics.exit_fees += exit_fee_amount.unwrap_or(0);
+                }
+            }
+            PoolEvent::Deposit {
+                token,
+                amount_in,
+                fee_amount,
+                balance,
+                total_supply,
+            } => {
+                self.set_balance(token, *balance, *amount_in);
+                self.set_total_shares(*total_supply);
+                *self.metrics.fees.entry(token.clone()).or_default() += fee_amount.unwrap_or(0);
+            }
+            PoolEvent::Withdraw {
+                token,
+                amount_out,
+                fee_amount,
+                exit_fee_amount,
+                balance,
+                total_supply,
+            } => {
+                self.set_balance(token, *balance, -amount_out);
+                self.set_total_shares(*total_supply);
+                *self.metrics.fees.entry(token.clone()).or_default() += fee_amount.unwrap_or(0);
+                self.metrics.exit_fees += exit_fee_amount.unwrap_or(0);
+            }
+            PoolEvent::FlashLoan {
+                token,
+                fee,
+                balance,
+                total_supply,
+            } => {
+                self.set_balance(token, *balance, *fee);
+                self.set_total_shares(*total_supply);
+                *self.metrics.fees.entry(token.clone()).or_default() += fee;
+            }
+            PoolEvent::Sync {
+                tokens,
+                balances,
+                weights,
+                swap_fee,
+                total_supply,
+            } => {
+                self.tokens = tokens.clone();
+                self.records = tokens
+                    .iter()
+                    .zip(balances.iter().zip(weights.iter()))
+                    .map(|(token, (balance, weight))| {
+                        let record = Record {
+                            balance: *balance,
+                            weight: *weight,
+                        };
+                        (token.clone(), record)
+                    })
+                    .collect();
+                self.swap_fee = *swap_fee;
+                self.total_shares = *total_supply;
+            }
+        }
+    }
+
+    // Spot price of `token_out` in `token_in`, without the swap fee, scaled by STROOP
+    pub fn spot_price(&self, token_in: &str, token_out: &str) -> Option<i128> {
+        let record_in = self.records.get(token_in)?;
+        let record_out = self.records.get(token_out)?;
+        let numer = record_in.balance.checked_mul(record_out.weight)?;
+        let denom = record_out.balance.checked_mul(record_in.weight)?;
+        numer.checked_mul(STROOP)?.checked_div(denom)
+    }
+
+    // Value of all the pool balances in `quote` at the spot price
+    //
+    // Every token holds its weight's share of the value, so the total is the
+    // balance of `quote` divided by its share of the total weight
+    pub fn tvl(&self, quote: &str) -> Option<i128> {
+        let record = self.records.get(quote)?;
+        let total_weight: i128 = self.records.values().map(|r| r.weight).sum();
+        record
+            .balance
+            .checked_mul(total_weight)?
+            .checked_div(record.weight)
+    }
+
+    fn set_balance(&mut self, token: &str, balance: Option<i128>, delta: i128) {
+        let record = self.records.entry(token.to_string()).or_default();
+        record.balance = balance.unwrap_or(record.balance + delta);
+    }
+
+    fn set_total_shares(&mut self, total_supply: Option<i128>) {
+        if let Some(total_supply) = total_supply {
+            self.total_shares = total_supply;
+        }
+    }
+}
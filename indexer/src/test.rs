@@ -0,0 +1,178 @@
+#![cfg(test)]
+
+use stellar_xdr::curr::{ContractEvent, ContractEventBody, Hash, ScMap, ScVal, VecM};
+
+use crate::{
+    event::{read_json, read_xdr},
+    pool::{Record, STROOP},
+    Indexer,
+};
+
+// Events of a factory deployed pool with 80/20 weights and a 0.3% swap fee,
+// traded with every pool operation and then gulped
+const FIXTURE_XDR: &str = include_str!("../fixtures/pool_events.xdr");
+const FIXTURE_JSON: &str = include_str!("../fixtures/pool_events.json");
+// The factory which deployed the fixture pool
+const FACTORY: &str = "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAFCT4";
+
+fn replay(events: &[ContractEvent]) -> Indexer {
+    let mut indexer = Indexer::new([String::from(FACTORY)]);
+    indexer.ingest_all(events).unwrap();
+    indexer
+}
+
+#[test]
+fn test_replay_fixture() {
+    let indexer = replay(&read_xdr(FIXTURE_XDR).unwrap());
+
+    // token contracts and their events are ignored
+    assert_eq!(indexer.pools().len(), 1);
+    let pool = indexer.pools().values().next().unwrap();
+    assert_eq!(pool.factory, FACTORY);
+    assert_eq!(pool.tokens.len(), 2);
+    let token_1 = &pool.tokens[0];
+    let token_2 = &pool.tokens[1];
+
+    // state read from the pool at the end of the fixture
+    assert_eq!(
+        pool.records[token_1],
+        Record {
+            balance: 870721421,
+            weight: 0_8000000,
+        }
+    );
+    assert_eq!(
+        pool.records[token_2],
+        Record {
+            balance: 187968817,
+            weight: 0_2000000,
+        }
+    );
+    assert_eq!(pool.total_shares, 1045185742);
+    assert_eq!(pool.swap_fee, 0_0030000);
+
+    // both swaps trade token_1 for token_2
+    assert_eq!(pool.metrics.swaps, 2);
+    assert!(pool.metrics.volume[token_1] > 5 * STROOP);
+    assert_eq!(pool.metrics.volume.get(token_2), None);
+    assert!(pool.metrics.fees[token_1] > 5 * STROOP * 30 / 10000);
+    // the token_2 deposit pays the swap fee on the 80% traded for token_1
+    assert_eq!(pool.metrics.fees[token_2], 2 * STROOP * 8 / 10 * 30 / 10000);
+    assert_eq!(pool.metrics.exit_fees, 0);
+
+    assert_eq!(pool.tvl(token_1), Some(870721421 * 10 / 8));
+    assert_eq!(pool.tvl(token_2), Some(187968817 * 10 / 2));
+    assert_eq!(
+        pool.spot_price(token_2, token_1),
+        Some(187968817 * 8 * STROOP / (870721421 * 2))
+    );
+}
+
+#[test]
+fn test_replay_json_matches_xdr() {
+    let from_xdr = replay(&read_xdr(FIXTURE_XDR).unwrap());
+    let from_json = replay(&read_json(FIXTURE_JSON).unwrap());
+    assert_eq!(from_xdr.pools(), from_json.pools());
+}
+
+#[test]
+fn test_replay_version_1_events() {
+    // drop the fields added by EVENT_VERSION 2 from the trade events
+    let v1_fields = [
+        "caller",
+        "token_in",
+        "token_out",
+        "token_amount_in",
+        "token_amount_out",
+    ];
+    let mut events = read_xdr(FIXTURE_XDR).unwrap();
+    for event in events.iter_mut() {
+        let ContractEventBody::V0(body) = &mut event.body;
+        let is_trade = body.topics.len() == 2
+            && matches!(&body.topics[0], ScVal::Symbol(s) if s.0.as_vec() == b"POOL")
+            && !matches!(&body.topics[1], ScVal::Symbol(s) if s.0.as_vec() == b"sync");
+        if let (ScVal::Map(Some(map)), true) = (&mut body.data, is_trade) {
+            let entries: Vec<_> = map
+                .iter()
+                .filter(|entry| match &entry.key {
+                    ScVal::Symbol(s) => v1_fields.iter().any(|f| s.0.as_vec() == f.as_bytes()),
+                    _ => true,
+                })
+                .cloned()
+                .collect();
+            *map = ScMap(VecM::try_from(entries).unwrap());
+        }
+    }
+
+    // balances follow the traded amounts and the gulp syncs the total supply
+    let from_v1 = replay(&events);
+    let from_v2 = replay(&read_xdr(FIXTURE_XDR).unwrap());
+    let pool_v1 = from_v1.pools().values().next().unwrap();
+    let pool_v2 = from_v2.pools().values().next().unwrap();
+    assert_eq!(pool_v1.records, pool_v2.records);
+    assert_eq!(pool_v1.total_shares, pool_v2.total_shares);
+    assert_eq!(pool_v1.metrics.volume, pool_v2.metrics.volume);
+    assert_eq!(pool_v1.metrics.swaps, 2);
+    // swap fees are computed from the swap fee of the pool
+    let token_1 = &pool_v1.tokens[0];
+    assert!(pool_v1.metrics.fees[token_1] > 0);
+}
+
+#[test]
+fn test_untrusted_events_ignored() {
+    let events = read_xdr(FIXTURE_XDR).unwrap();
+
+    // pools of other factories are not indexed
+    let mut indexer = Indexer::new([String::from(
+        "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAHK3M",
+    )]);
+    indexer.ingest_all(&events).unwrap();
+    assert!(indexer.pools().is_empty());
+
+    // nor are contracts publishing pool events without being announced
+    let unannounced: Vec<_> = events
+        .into_iter()
+        .filter(|event| {
+            let ContractEventBody::V0(body) = &event.body;
+            !matches!(body.topics.get(1), Some(ScVal::Symbol(s)) if s.0.as_vec() == b"NEW_POOL")
+        })
+        .collect();
+    assert!(replay(&unannounced).pools().is_empty());
+}
+
+#[test]
+fn test_untrusted_malformed_event_ignored() {
+    let events = read_xdr(FIXTURE_XDR).unwrap();
+    let swap = events
+        .iter()
+        .position(|event| {
+            let ContractEventBody::V0(body) = &event.body;
+            matches!(body.topics.get(1), Some(ScVal::Symbol(s)) if s.0.as_vec() == b"swap")
+        })
+        .unwrap();
+
+    // a pool swap event without any of its fields, from an unknown contract
+    let mut garbage = events[swap].clone();
+    garbage.contract_id = Some(Hash([7; 32]));
+    let ContractEventBody::V0(body) = &mut garbage.body;
+    body.data = ScVal::Map(Some(ScMap(VecM::default())));
+
+    let mut indexer = Indexer::new([String::from(FACTORY)]);
+    indexer.ingest_all(&events[..swap]).unwrap();
+    indexer.ingest(&garbage).unwrap();
+    indexer.ingest(&events[swap]).unwrap();
+    let pool = indexer.pools().values().next().unwrap();
+    assert_eq!(pool.metrics.swaps, 1);
+
+    // the same event from the pool is still rejected
+    let mut malformed = events[swap].clone();
+    let ContractEventBody::V0(body) = &mut malformed.body;
+    body.data = ScVal::Map(Some(ScMap(VecM::default())));
+    assert!(indexer.ingest(&malformed).is_err());
+}
+
+#[test]
+fn test_invalid_input() {
+    assert!(read_xdr("not xdr").is_err());
+    assert!(read_json("{}").is_err());
+}
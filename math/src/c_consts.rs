@@ -0,0 +1,13 @@
+//! Comet Pool Math Constants
+
+/// c_math 256 bit constants
+/// kept as i128 to be usable as constants by the contract
+pub const BONE: i128 = 10i128.pow(18);
+pub const MIN_CPOW_BASE: i128 = 1;
+pub const MAX_CPOW_BASE: i128 = (2 * BONE) - 1;
+pub const CPOW_PRECISION: i128 = 10i128.pow(8);
+
+/// constants
+pub const STROOP: i128 = 10i128.pow(7);
+pub const STROOP_SCALAR: i128 = 10i128.pow(11);
+pub const MAX_PROTOCOL_FEE: i128 = STROOP / 2; // 50% of the swap fee, shared with the factory
@@ -0,0 +1,403 @@
+//! Comet Pool Math Utilities
+use crate::{
+    c_consts::{BONE, STROOP, STROOP_SCALAR},
+    c_num::{c_pow, mul_div_ceil, mul_div_floor, sub_no_negative},
+    error::Error,
+    I256,
+};
+
+/// Balance, weight and decimals scalar of a bound token, as in the pool `Record`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    pub balance: i128,
+    pub weight: i128,
+    pub scalar: i128,
+}
+
+// Calculates the balance of a token per unit of its weight, the spot price
+// sans fee of a token pair is the ratio of their weighted balances
+//
+// Returns None on overflow
+pub fn calc_weighted_balance(record: &Record) -> Option<i128> {
+    // don't upscale to preserve "token in" / "token out" precision
+    mul_div_floor(record.balance, STROOP, record.weight)
+}
+
+// Calculates the spot price for a token pair
+// based on weights and balances for that pair of tokens,
+// accounting for fees
+//
+// Returns None on overflow
+pub fn calc_spot_price(in_record: &Record, out_record: &Record, swap_fee: i128) -> Option<i128> {
+    let numer = calc_weighted_balance(in_record)?;
+    let denom = calc_weighted_balance(out_record)?;
+    let ratio = mul_div_floor(numer, STROOP, denom)?;
+    mul_div_floor(ratio, STROOP, STROOP - swap_fee)
+}
+
+/// Calculates the amount of token out sent to user,
+/// for a given amount of token in
+///
+/// Rounds down to benefit the pool
+pub fn calc_token_out_given_token_in(
+    in_record: &Record,
+    out_record: &Record,
+    amount_in: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_in = upscale(in_record.balance, in_record.scalar);
+    let token_balance_out = upscale(out_record.balance, out_record.scalar);
+    let token_amount_in = upscale(amount_in, in_record.scalar);
+
+    let fee_adjust_ratio = upscale(STROOP - swap_fee, STROOP_SCALAR);
+    let weight_ratio = upscale(
+        mul_div_floor(in_record.weight, STROOP, out_record.weight).unwrap(),
+        STROOP_SCALAR,
+    );
+
+    let adjusted_in = token_amount_in.fixed_mul_floor(&fee_adjust_ratio, &bone);
+
+    let base = token_balance_in.fixed_div_floor(&token_balance_in.add(&adjusted_in), &bone);
+    let power = c_pow(&base, &weight_ratio, true)?;
+    let balance_ratio = sub_no_negative(&bone, &power)?;
+    let result = token_balance_out.fixed_mul_floor(&balance_ratio, &bone);
+
+    downscale_floor(&result, out_record.scalar)
+}
+
+/// Calculates the amount of token in required by pool,
+/// for a given amount of token out
+///
+/// Rounds up to benefit the pool
+pub fn calc_token_in_given_token_out(
+    in_record: &Record,
+    out_record: &Record,
+    amount_out: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_in = upscale(in_record.balance, in_record.scalar);
+    let token_balance_out = upscale(out_record.balance, out_record.scalar);
+    let token_amount_out = upscale(amount_out, out_record.scalar);
+
+    let fee_adjust_ratio = upscale(STROOP - swap_fee, STROOP_SCALAR);
+    let weight_ratio = upscale(
+        mul_div_ceil(out_record.weight, STROOP, in_record.weight).unwrap(),
+        STROOP_SCALAR,
+    );
+
+    let base = token_balance_out.fixed_div_ceil(&token_balance_out.sub(&token_amount_out), &bone);
+    let power = c_pow(&base, &weight_ratio, true)?;
+    let balance_ratio = sub_no_negative(&power, &bone)?;
+
+    let token_amount_in = token_balance_in.fixed_mul_ceil(&balance_ratio, &bone);
+    let adjusted_in = token_amount_in.fixed_div_ceil(&fee_adjust_ratio, &bone);
+    downscale_ceil(&adjusted_in, in_record.scalar)
+}
+
+/// Calculates the amount of LP tokens being minted to user,
+/// for a given amount of deposited tokens
+///
+/// Rounds down to benefit the pool
+pub fn calc_lp_token_amount_given_token_deposits_in(
+    in_record: &Record,
+    pool_supply: i128,
+    token_amount_in: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_in = upscale(in_record.balance, in_record.scalar);
+    let token_amount_in = upscale(token_amount_in, in_record.scalar);
+    let pool_supply = upscale(pool_supply, STROOP_SCALAR);
+    let fee = upscale(swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(in_record.weight, STROOP_SCALAR);
+    let zaz = bone.sub(&normalized_weight).fixed_mul_floor(&fee, &bone);
+    let token_amount_in_after_fee = token_amount_in.fixed_mul_floor(&bone.sub(&zaz), &bone);
+
+    let new_token_balance_in = token_balance_in.add(&token_amount_in_after_fee);
+    let balance_ratio = new_token_balance_in.fixed_div_floor(&token_balance_in, &bone);
+
+    let pool_ratio = c_pow(&balance_ratio, &normalized_weight, false)?;
+    let new_pool_supply = pool_ratio.fixed_mul_floor(&pool_supply, &bone);
+
+    downscale_floor(
+        &sub_no_negative(&new_pool_supply, &pool_supply)?,
+        STROOP_SCALAR,
+    )
+}
+
+/// Calculates the amount of deposited tokens required by pool,
+/// for a given amount of LP tokens being minted
+///
+/// Rounds up to benefit the pool
+pub fn calc_token_deposits_in_given_lp_token_amount(
+    in_record: &Record,
+    pool_supply: i128,
+    pool_amount_out: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_in = upscale(in_record.balance, in_record.scalar);
+    let pool_amount_out = upscale(pool_amount_out, STROOP_SCALAR);
+    let pool_supply = upscale(pool_supply, STROOP_SCALAR);
+    let fee = upscale(swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(in_record.weight, STROOP_SCALAR);
+
+    let new_pool_supply = pool_supply.add(&pool_amount_out);
+    let pool_ratio = new_pool_supply.fixed_div_ceil(&pool_supply, &bone);
+
+    let boo = bone.fixed_div_ceil(&normalized_weight, &bone);
+    let token_in_ratio = c_pow(&pool_ratio, &boo, false)?;
+    let new_token_balance_in = token_balance_in.fixed_mul_ceil(&token_in_ratio, &bone);
+
+    let token_amount_in_after_fee = sub_no_negative(&new_token_balance_in, &token_balance_in)?;
+    let zar = bone.sub(&normalized_weight).fixed_mul_floor(&fee, &bone);
+    let result = token_amount_in_after_fee.fixed_div_ceil(&bone.sub(&zar), &bone);
+
+    downscale_ceil(&result, in_record.scalar)
+}
+
+/// Calculating the amount of LP tokens a user needs to burn,
+/// for a given amount of tokens being withdrawn.
+/// The result includes the exit fee kept from the LP tokens.
+///
+/// Rounds up to benefit the pool
+pub fn calc_lp_token_amount_given_token_withdrawal_amount(
+    out_record: &Record,
+    pool_supply: i128,
+    token_amount_out: i128,
+    swap_fee: i128,
+    exit_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_out = upscale(out_record.balance, out_record.scalar);
+    let token_amount_out = upscale(token_amount_out, out_record.scalar);
+    let pool_supply = upscale(pool_supply, STROOP_SCALAR);
+    let fee = upscale(swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(out_record.weight, STROOP_SCALAR);
+
+    let zoo = bone.sub(&normalized_weight);
+    let zar = zoo.fixed_mul_floor(&fee, &bone);
+
+    let token_amount_out_before_fee = token_amount_out.fixed_div_ceil(&bone.sub(&zar), &bone);
+    let new_token_balance_out = token_balance_out.sub(&token_amount_out_before_fee);
+    let balance_ratio = new_token_balance_out.fixed_div_ceil(&token_balance_out, &bone);
+
+    let pool_ratio = c_pow(&balance_ratio, &normalized_weight, true)?;
+    let new_pool_supply = pool_ratio.fixed_mul_ceil(&pool_supply, &bone);
+    let pool_amount_in_after_exit_fee = sub_no_negative(&pool_supply, &new_pool_supply)?;
+
+    let exit_fee = upscale(exit_fee, STROOP_SCALAR);
+    let result = pool_amount_in_after_exit_fee.fixed_div_ceil(&bone.sub(&exit_fee), &bone);
+
+    downscale_ceil(&result, STROOP_SCALAR)
+}
+
+/// Calculating the amount of tokens being withdrawn,
+/// given how many LP tokens the user wants to burn.
+/// The exit fee is kept from `pool_amount_in` before the withdrawal.
+///
+/// Rounds down to benefit the pool
+pub fn calc_token_withdrawal_amount_given_lp_token_amount(
+    out_record: &Record,
+    pool_supply: i128,
+    pool_amount_in: i128,
+    swap_fee: i128,
+    exit_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_out = upscale(out_record.balance, out_record.scalar);
+    let pool_amount_in = upscale(pool_amount_in, STROOP_SCALAR);
+    let pool_supply = upscale(pool_supply, STROOP_SCALAR);
+    let fee = upscale(swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(out_record.weight, STROOP_SCALAR);
+
+    let exit_fee = upscale(exit_fee, STROOP_SCALAR);
+    let pool_amount_in_after_exit_fee = pool_amount_in.fixed_mul_floor(&bone.sub(&exit_fee), &bone);
+
+    let new_pool_supply = pool_supply.sub(&pool_amount_in_after_exit_fee);
+    let pool_ratio = new_pool_supply.fixed_div_floor(&pool_supply, &bone);
+
+    let exp = bone.fixed_div_floor(&normalized_weight, &bone);
+    let token_out_ratio = c_pow(&pool_ratio, &exp, false)?;
+    let new_token_balance_out = token_balance_out.fixed_mul_floor(&token_out_ratio, &bone);
+
+    let token_amount_out_before_fee = sub_no_negative(&token_balance_out, &new_token_balance_out)?;
+
+    let zaz = bone.sub(&normalized_weight).fixed_mul_floor(&fee, &bone);
+    let result = token_amount_out_before_fee.fixed_mul_floor(&bone.sub(&zaz), &bone);
+
+    downscale_floor(&result, out_record.scalar)
+}
+
+/// Calculate the join balance ratio
+///
+/// Rounds up to benefit the pool
+pub fn calc_join_ratio(pool_supply: i128, pool_amount_out: i128) -> I256 {
+    let bone = I256::from_i128(BONE);
+    let pool_supply = upscale(pool_supply, STROOP_SCALAR);
+    let pool_amount_out = upscale(pool_amount_out, STROOP_SCALAR);
+
+    pool_amount_out.fixed_div_ceil(&pool_supply, &bone)
+}
+
+/// Calculate the join deposit amount given the join balance ratio
+///
+/// Rounds up to benefit the pool
+pub fn calc_join_deposit_amount(in_record: &Record, join_ratio: &I256) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_in = upscale(in_record.balance, in_record.scalar);
+
+    let result = token_balance_in.fixed_mul_ceil(join_ratio, &bone);
+    downscale_ceil(&result, in_record.scalar)
+}
+
+/// Calculate the exit balance ratio
+///
+/// Rounds down to benefit the pool
+pub fn calc_exit_ratio(pool_supply: i128, pool_amount_in: i128) -> I256 {
+    let bone = I256::from_i128(BONE);
+    let pool_supply = upscale(pool_supply, STROOP_SCALAR);
+    let pool_amount_in = upscale(pool_amount_in, STROOP_SCALAR);
+
+    pool_amount_in.fixed_div_floor(&pool_supply, &bone)
+}
+
+/// Calculate the exit withdrawal amount given the exit balance ratio
+///
+/// Rounds down to benefit the pool
+pub fn calc_exit_withdrawal_amount(out_record: &Record, exit_ratio: &I256) -> Result<i128, Error> {
+    let bone = I256::from_i128(BONE);
+    let token_balance_out = upscale(out_record.balance, out_record.scalar);
+
+    let result = token_balance_out.fixed_mul_floor(exit_ratio, &bone);
+    downscale_floor(&result, out_record.scalar)
+}
+
+/// Calculate the weights of a linear weight schedule at the given ledger sequence into `weights`
+///
+/// Each weight is rounded down, then the remainder is handed out one stroop at a time
+/// to weights that were rounded, such that the weights always sum to the same total
+/// as the start and end weights and each weight stays between its start and end weight.
+pub fn calc_scheduled_weights(
+    start_ledger: u32,
+    end_ledger: u32,
+    start_weights: &[i128],
+    end_weights: &[i128],
+    sequence: u32,
+    weights: &mut [i128],
+) {
+    if sequence <= start_ledger {
+        weights.copy_from_slice(start_weights);
+        return;
+    }
+    if sequence >= end_ledger {
+        weights.copy_from_slice(end_weights);
+        return;
+    }
+
+    let elapsed = (sequence - start_ledger) as i128;
+    let duration = (end_ledger - start_ledger) as i128;
+    let numer = |i: usize| (end_weights[i] - start_weights[i]) * elapsed;
+    let mut total: i128 = 0;
+    let mut scheduled_total: i128 = 0;
+    for (i, weight) in weights.iter_mut().enumerate() {
+        *weight = start_weights[i] + numer(i).div_euclid(duration);
+        total += *weight;
+        scheduled_total += end_weights[i];
+    }
+
+    let mut remainder = scheduled_total - total;
+    for (i, weight) in weights.iter_mut().enumerate() {
+        if remainder == 0 {
+            break;
+        }
+        if numer(i).rem_euclid(duration) != 0 {
+            *weight += 1;
+            remainder -= 1;
+        }
+    }
+}
+
+/********** Scaling Utils **********/
+
+/// Upscale a number to 18 decimals and 256 bits for use in pool math
+///
+/// Requires that "amount" is less that 1.7e19 * scalar
+///
+/// Will fail if `amount` is greater than 1e18 * scalar
+fn upscale(amount: i128, scalar: i128) -> I256 {
+    I256::from_i128(amount * scalar)
+}
+
+/// Downscale a number from 18 decimals and 256 bits to i128 to represent a token amount.
+///
+/// Rounds floor if there is any remainder.
+fn downscale_floor(amount: &I256, scalar: i128) -> Result<i128, Error> {
+    let scale_256 = I256::from_i128(scalar);
+    let one = I256::from_i32(1);
+    amount
+        .fixed_div_floor(&scale_256, &one)
+        .to_i128()
+        .ok_or(Error::ErrMathApprox)
+}
+
+/// Descale a number from 18 decimals and 256 bits to i128 to represent a token amount.
+///
+/// Rounds up if there is any remainder.
+fn downscale_ceil(amount: &I256, scalar: i128) -> Result<i128, Error> {
+    let scale_256 = I256::from_i128(scalar);
+    let one = I256::from_i32(1);
+    amount
+        .fixed_div_ceil(&scale_256, &one)
+        .to_i128()
+        .ok_or(Error::ErrMathApprox)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_stroop() {
+        let x: i128 = 12345_1234567i128;
+
+        let mut scaled = upscale(x, STROOP_SCALAR);
+        let expected = I256::from_i128(12345_1234567_00_000_000_000i128);
+        assert_eq!(scaled, expected);
+
+        // takes floor
+        scaled = scaled.add(&I256::from_i128(STROOP_SCALAR / 10));
+        let floor = downscale_floor(&scaled, STROOP_SCALAR);
+        assert_eq!(floor, Ok(x));
+
+        // takes ceil
+        let ceil = downscale_ceil(&scaled, STROOP_SCALAR);
+        assert_eq!(ceil, Ok(x + 1));
+    }
+
+    #[test]
+    fn test_downscale_floor_too_large() {
+        let x = I256::from_i128(i128::MAX);
+        let too_large = x.mul(&I256::from_i128(STROOP_SCALAR)).add(&x);
+        assert_eq!(
+            downscale_floor(&too_large, STROOP_SCALAR),
+            Err(Error::ErrMathApprox)
+        );
+    }
+
+    #[test]
+    fn test_downscale_ceil_too_large() {
+        let x = I256::from_i128(i128::MAX);
+        let too_large = x.mul(&I256::from_i128(STROOP_SCALAR)).add(&x);
+        assert_eq!(
+            downscale_ceil(&too_large, STROOP_SCALAR),
+            Err(Error::ErrMathApprox)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Comet Pool Arithmetic Primitives
+
+use crate::{
+    c_consts::{BONE, CPOW_PRECISION, MAX_CPOW_BASE, MIN_CPOW_BASE},
+    error::Error,
+    I256,
+};
+
+/// Perform a - b, or fail if a < b
+pub fn sub_no_negative(a: &I256, b: &I256) -> Result<I256, Error> {
+    if a < b {
+        return Err(Error::ErrSubUnderflow);
+    }
+    Ok(a.sub(b))
+}
+
+/// Calculate base^exp where base and exp are fixed point numbers with 18 decimals.
+///
+/// Approximates the result such that:
+/// -> base^(int exp) * approximate of base^(decimal exp)
+pub fn c_pow(base: &I256, exp: &I256, round_up: bool) -> Result<I256, Error> {
+    if base < &I256::from_i128(MIN_CPOW_BASE) {
+        return Err(Error::ErrCPowBaseTooLow);
+    }
+    if base > &I256::from_i128(MAX_CPOW_BASE) {
+        return Err(Error::ErrCPowBaseTooHigh);
+    }
+
+    let bone = I256::from_i128(BONE);
+    let int = exp.div(&bone);
+    let remain = exp.sub(&int.mul(&bone));
+    let whole_pow = c_powi(base, int.to_i128().unwrap() as u32);
+    if remain == I256::ZERO {
+        return Ok(whole_pow);
+    }
+    let partial_result = c_pow_approx(base, &remain, &I256::from_i128(CPOW_PRECISION), round_up);
+    if round_up {
+        Ok(whole_pow.fixed_mul_ceil(&partial_result, &bone))
+    } else {
+        Ok(whole_pow.fixed_mul_floor(&partial_result, &bone))
+    }
+}
+
+// Calculate a^n where n is an integer
+fn c_powi(a: &I256, n: u32) -> I256 {
+    let bone = I256::from_i128(BONE);
+    let mut z = if n % 2 != 0 { *a } else { bone };
+
+    let mut a = *a;
+    let mut n = n / 2;
+    while n != 0 {
+        a = a.fixed_mul_floor(&a, &bone);
+        if n % 2 != 0 {
+            z = z.fixed_mul_floor(&a, &bone);
+        }
+        n /= 2
+    }
+    z
+}
+
+// Calculate approximate Power Value
+fn c_pow_approx(base: &I256, exp: &I256, precision: &I256, round_up: bool) -> I256 {
+    // term 0
+    let bone = I256::from_i128(BONE);
+    let zero = I256::ZERO;
+    let n_1 = I256::from_i32(-1);
+    let x = base.sub(&bone);
+    let mut term = bone;
+    let mut sum = term;
+    // Capped to limit iterations in the event of a poor approximation
+    for i in 1..51 {
+        let big_k = I256::from_i128(i * BONE);
+        let c = exp.sub(&big_k.sub(&bone));
+        term = term.fixed_mul_floor(&c.fixed_mul_floor(&x, &bone), &bone);
+        term = term.fixed_div_floor(&big_k, &bone);
+        sum = sum.add(&term);
+
+        let abs_term = if term < zero { term.mul(&n_1) } else { term };
+        if abs_term <= *precision {
+            break;
+        }
+    }
+    // the series has predicatable approximations bounds, so we can adjust the final sum by
+    // the final term to (almost) ensure the sum is either an under or over estimate based
+    // on the rounding direction.
+    if x > zero {
+        // series will oscillate due to negative `c` values and a starting positive value.
+        if term > zero && !round_up {
+            // the final applied term was additive - the current sum is likely an overestimate
+            sum = sum.sub(&term);
+        } else if term < zero && round_up {
+            // the final applied term was subtractive - the current sum is likely an understimate
+            sum = sum.sub(&term);
+        }
+    } else if !round_up {
+        // series is monotonically decreasing, so the final term is an overestimate
+        sum = sum.add(&term);
+    }
+    sum
+}
+
+/// Calculate floor(x * y / z) on i128, or None on overflow
+///
+/// Rounds like `FixedPoint` of `soroban-fixed-point-math`
+pub fn mul_div_floor(x: i128, y: i128, z: i128) -> Option<i128> {
+    let r = x.checked_mul(y)?;
+    if r < 0 || (r > 0 && z < 0) {
+        // ceiling is taken by default for a negative result
+        let remainder = r.checked_rem_euclid(z)?;
+        (r / z).checked_sub(if remainder > 0 { 1 } else { 0 })
+    } else {
+        // floor taken by default for a positive or zero result
+        r.checked_div(z)
+    }
+}
+
+/// Calculate ceil(x * y / z) on i128, or None on overflow
+pub fn mul_div_ceil(x: i128, y: i128, z: i128) -> Option<i128> {
+    let r = x.checked_mul(y)?;
+    if r <= 0 || z < 0 {
+        // ceiling is taken by default for a negative or zero result
+        r.checked_div(z)
+    } else {
+        // floor taken by default for a positive result
+        let remainder = r.checked_rem_euclid(z)?;
+        (r / z).checked_add(if remainder > 0 { 1 } else { 0 })
+    }
+}
@@ -0,0 +1,10 @@
+//! Errors of the pool math, numbered like the matching pool contract errors
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Error {
+    ErrMathApprox = 18,
+    ErrSubUnderflow = 31,
+    ErrCPowBaseTooLow = 34,
+    ErrCPowBaseTooHigh = 35,
+}
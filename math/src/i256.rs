@@ -0,0 +1,382 @@
+//! Signed 256 bit integer with the semantics of the Soroban host `I256`
+//!
+//! Arithmetic panics on overflow and division by zero, as the host does,
+//! and division truncates towards zero.
+use core::cmp::Ordering;
+
+// Unsigned 256 bit magnitude, as little endian 64 bit limbs
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct U256([u64; 4]);
+
+impl U256 {
+    const ZERO: U256 = U256([0; 4]);
+    // 2^255, the magnitude of I256::MIN
+    const SIGN_BIT: U256 = U256([0, 0, 0, 1 << 63]);
+
+    fn from_u128(v: u128) -> U256 {
+        U256([v as u64, (v >> 64) as u64, 0, 0])
+    }
+
+    // The value if it fits in 128 bits
+    fn to_u128(self) -> Option<u128> {
+        if self.0[2] == 0 && self.0[3] == 0 {
+            Some(self.0[0] as u128 | (self.0[1] as u128) << 64)
+        } else {
+            None
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + 64 - self.0[i].leading_zeros();
+            }
+        }
+        0
+    }
+
+    fn bit(&self, i: u32) -> bool {
+        (self.0[(i / 64) as usize] >> (i % 64)) & 1 == 1
+    }
+
+    fn overflowing_add(self, other: U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(result), carry)
+    }
+
+    // self - other, for self >= other
+    fn sub(self, other: U256) -> U256 {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        U256(result)
+    }
+
+    // The product, or None if it does not fit in 256 bits
+    fn checked_mul(self, other: U256) -> Option<U256> {
+        let mut result = [0u64; 8];
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * other.0[j] as u128 + result[i + j] as u128 + carry;
+                result[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            result[i + 4] = carry as u64;
+        }
+        if result[4..] != [0; 4] {
+            return None;
+        }
+        Some(U256([result[0], result[1], result[2], result[3]]))
+    }
+
+    fn shl1(self, low_bit: bool) -> U256 {
+        let mut result = [0u64; 4];
+        let mut carry = low_bit as u64;
+        for (i, limb) in result.iter_mut().enumerate() {
+            *limb = (self.0[i] << 1) | carry;
+            carry = self.0[i] >> 63;
+        }
+        U256(result)
+    }
+
+    // Quotient and remainder, for a non-zero divisor
+    fn div_rem(self, divisor: U256) -> (U256, U256) {
+        if let (Some(a), Some(b)) = (self.to_u128(), divisor.to_u128()) {
+            return (U256::from_u128(a / b), U256::from_u128(a % b));
+        }
+        if divisor.0[1..] == [0; 3] {
+            let d = divisor.0[0] as u128;
+            let mut quotient = [0u64; 4];
+            let mut rem: u128 = 0;
+            for i in (0..4).rev() {
+                let cur = (rem << 64) | self.0[i] as u128;
+                quotient[i] = (cur / d) as u64;
+                rem = cur % d;
+            }
+            return (U256(quotient), U256::from_u128(rem));
+        }
+        let mut quotient = U256::ZERO;
+        let mut rem = U256::ZERO;
+        for i in (0..self.bits()).rev() {
+            rem = rem.shl1(self.bit(i));
+            quotient = quotient.shl1(false);
+            if rem >= divisor {
+                rem = rem.sub(divisor);
+                quotient.0[0] |= 1;
+            }
+        }
+        (quotient, rem)
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Signed 256 bit integer, stored as a sign and a magnitude
+//
+// Zero is never negative, and the magnitude is at most 2^255 - 1,
+// or 2^255 for negative values
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct I256 {
+    negative: bool,
+    magnitude: U256,
+}
+
+impl I256 {
+    pub const ZERO: I256 = I256 {
+        negative: false,
+        magnitude: U256::ZERO,
+    };
+
+    fn new(negative: bool, magnitude: U256) -> Option<I256> {
+        let max = if negative {
+            U256::SIGN_BIT
+        } else {
+            U256::SIGN_BIT.sub(U256([1, 0, 0, 0]))
+        };
+        if magnitude > max {
+            return None;
+        }
+        Some(I256 {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        })
+    }
+
+    pub fn from_i32(v: i32) -> I256 {
+        I256::from_i128(v as i128)
+    }
+
+    pub fn from_i128(v: i128) -> I256 {
+        I256 {
+            negative: v < 0,
+            magnitude: U256::from_u128(v.unsigned_abs()),
+        }
+    }
+
+    // The value if it fits in an i128
+    pub fn to_i128(&self) -> Option<i128> {
+        let magnitude = self.magnitude.to_u128()?;
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    // Two's complement big endian bytes, as `I256::to_be_bytes` of the Soroban SDK
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut limbs = self.magnitude.0;
+        if self.negative {
+            let (negated, _) = U256(limbs.map(|limb| !limb)).overflowing_add(U256([1, 0, 0, 0]));
+            limbs = negated.0;
+        }
+        let mut bytes = [0u8; 32];
+        for (i, limb) in limbs.iter().rev().enumerate() {
+            bytes[i * 8..i * 8 + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> I256 {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().rev().enumerate() {
+            let mut chunk = [0u8; 8];
+            chunk.copy_from_slice(&bytes[i * 8..i * 8 + 8]);
+            *limb = u64::from_be_bytes(chunk);
+        }
+        let negative = bytes[0] & 0x80 != 0;
+        if negative {
+            let (magnitude, _) = U256(limbs.map(|limb| !limb)).overflowing_add(U256([1, 0, 0, 0]));
+            // the magnitude of I256::MIN wraps to itself
+            let magnitude = if magnitude.is_zero() {
+                U256::SIGN_BIT
+            } else {
+                magnitude
+            };
+            I256 {
+                negative,
+                magnitude,
+            }
+        } else {
+            I256 {
+                negative,
+                magnitude: U256(limbs),
+            }
+        }
+    }
+
+    pub fn checked_add(&self, other: &I256) -> Option<I256> {
+        if self.negative == other.negative {
+            let (magnitude, carry) = self.magnitude.overflowing_add(other.magnitude);
+            if carry {
+                return None;
+            }
+            I256::new(self.negative, magnitude)
+        } else if self.magnitude >= other.magnitude {
+            I256::new(self.negative, self.magnitude.sub(other.magnitude))
+        } else {
+            I256::new(other.negative, other.magnitude.sub(self.magnitude))
+        }
+    }
+
+    pub fn checked_sub(&self, other: &I256) -> Option<I256> {
+        let negated = I256 {
+            negative: !other.negative && !other.magnitude.is_zero(),
+            magnitude: other.magnitude,
+        };
+        self.checked_add(&negated)
+    }
+
+    pub fn checked_mul(&self, other: &I256) -> Option<I256> {
+        let magnitude = self.magnitude.checked_mul(other.magnitude)?;
+        I256::new(self.negative != other.negative, magnitude)
+    }
+
+    // Division truncated towards zero
+    pub fn checked_div(&self, other: &I256) -> Option<I256> {
+        if other.magnitude.is_zero() {
+            return None;
+        }
+        let (quotient, _) = self.magnitude.div_rem(other.magnitude);
+        I256::new(self.negative != other.negative, quotient)
+    }
+
+    // Remainder of the euclidean division, never negative
+    pub fn checked_rem_euclid(&self, other: &I256) -> Option<I256> {
+        if other.magnitude.is_zero() {
+            return None;
+        }
+        let (_, rem) = self.magnitude.div_rem(other.magnitude);
+        if self.negative && !rem.is_zero() {
+            I256::new(false, other.magnitude.sub(rem))
+        } else {
+            I256::new(false, rem)
+        }
+    }
+
+    pub fn add(&self, other: &I256) -> I256 {
+        self.checked_add(other)
+            .expect("attempt to add with overflow")
+    }
+
+    pub fn sub(&self, other: &I256) -> I256 {
+        self.checked_sub(other)
+            .expect("attempt to subtract with overflow")
+    }
+
+    pub fn mul(&self, other: &I256) -> I256 {
+        self.checked_mul(other)
+            .expect("attempt to multiply with overflow")
+    }
+
+    pub fn div(&self, other: &I256) -> I256 {
+        self.checked_div(other)
+            .expect("attempt to divide by zero or with overflow")
+    }
+
+    pub fn rem_euclid(&self, other: &I256) -> I256 {
+        self.checked_rem_euclid(other)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+    }
+
+    /********** Fixed Point **********/
+
+    // floor(self * y / denominator)
+    pub fn fixed_mul_floor(&self, y: &I256, denominator: &I256) -> I256 {
+        mul_div_floor(self, y, denominator)
+    }
+
+    // ceil(self * y / denominator)
+    pub fn fixed_mul_ceil(&self, y: &I256, denominator: &I256) -> I256 {
+        mul_div_ceil(self, y, denominator)
+    }
+
+    // floor(self * denominator / y)
+    pub fn fixed_div_floor(&self, y: &I256, denominator: &I256) -> I256 {
+        mul_div_floor(self, denominator, y)
+    }
+
+    // ceil(self * denominator / y)
+    pub fn fixed_div_ceil(&self, y: &I256, denominator: &I256) -> I256 {
+        mul_div_ceil(self, denominator, y)
+    }
+}
+
+// Rounding follows `soroban-fixed-point-math` to the bit
+fn mul_div_floor(x: &I256, y: &I256, z: &I256) -> I256 {
+    let zero = I256::ZERO;
+    let r = x.mul(y);
+    if r < zero || (r > zero && *z < zero) {
+        // ceiling is taken by default for a negative result
+        let remainder = r.rem_euclid(z);
+        let one = I256::from_i32(1);
+        r.div(z).sub(if remainder > zero { &one } else { &zero })
+    } else {
+        // floor taken by default for a positive or zero result
+        r.div(z)
+    }
+}
+
+fn mul_div_ceil(x: &I256, y: &I256, z: &I256) -> I256 {
+    let zero = I256::ZERO;
+    let r = x.mul(y);
+    if r <= zero || *z < zero {
+        // ceiling is taken by default for a negative or zero result
+        r.div(z)
+    } else {
+        // floor taken by default for a positive result
+        let remainder = r.rem_euclid(z);
+        let one = I256::from_i32(1);
+        r.div(z).add(if remainder > zero { &one } else { &zero })
+    }
+}
+
+impl Ord for I256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<i128> for I256 {
+    fn from(v: i128) -> Self {
+        I256::from_i128(v)
+    }
+}
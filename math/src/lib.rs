@@ -0,0 +1,18 @@
+//! Comet pool math, shared by the pool contract and off-chain code
+//!
+//! Results are bit-identical to the math run by the pool contract on the Soroban host
+#![no_std]
+
+pub mod c_consts;
+pub mod c_math;
+pub mod c_num;
+pub mod error;
+pub mod i256;
+
+pub use i256::I256;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+extern crate std;
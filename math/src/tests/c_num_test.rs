@@ -0,0 +1,15 @@
+#![cfg(test)]
+
+use crate::{c_consts::BONE, c_num::c_pow, error::Error, I256};
+
+#[test]
+fn test_c_pow_low() {
+    let result = c_pow(&I256::from_i32(0), &I256::from_i32(2), false);
+    assert_eq!(result, Err(Error::ErrCPowBaseTooLow));
+}
+
+#[test]
+fn test_c_pow_high() {
+    let result = c_pow(&I256::from_i128(2 * BONE), &I256::from_i32(2), false);
+    assert_eq!(result, Err(Error::ErrCPowBaseTooHigh));
+}
@@ -0,0 +1,316 @@
+#![cfg(test)]
+//! Differential tests of the pool math against the math run on the Soroban host
+use soroban_fixed_point_math::SorobanFixedPoint;
+use soroban_sdk::{Bytes, Env};
+
+use super::reference;
+use crate::{
+    c_consts::{BONE, MAX_CPOW_BASE, MIN_CPOW_BASE, STROOP},
+    c_math::{self, Record},
+    c_num, I256,
+};
+
+const CASES: u32 = 2000;
+
+// Deterministic splitmix64, so failures can be replayed
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [min, max]
+    fn range(&mut self, min: i128, max: i128) -> i128 {
+        let span = (max - min) as u128 + 1;
+        let r = (self.next_u64() as u128) << 64 | self.next_u64() as u128;
+        min + (r % span) as i128
+    }
+
+    // A value of up to `bits` bits, with a random magnitude and sign
+    fn i256(&mut self, bits: u32) -> I256 {
+        let bits = self.range(0, bits as i128) as usize;
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().rev().enumerate() {
+            if i * 8 < bits {
+                *byte = self.next_u64() as u8;
+                if bits - i * 8 < 8 {
+                    *byte &= (1u8 << (bits - i * 8)) - 1;
+                }
+            }
+        }
+        let magnitude = I256::from_be_bytes(&bytes);
+        if self.next_u64() % 2 == 0 {
+            I256::ZERO.sub(&magnitude)
+        } else {
+            magnitude
+        }
+    }
+
+    // A token record with `decimals` decimals
+    fn record(&mut self, decimals: u32) -> Record {
+        let unit = 10i128.pow(decimals);
+        Record {
+            balance: self.range(1, 1_000_000_000) * unit + self.range(0, unit - 1),
+            weight: self.range(STROOP / 10, STROOP * 9 / 10),
+            scalar: 10i128.pow(18 - decimals),
+        }
+    }
+
+    fn decimals(&mut self) -> u32 {
+        [6, 7, 8, 9, 12, 18][self.range(0, 5) as usize]
+    }
+}
+
+fn to_host(e: &Env, v: &I256) -> soroban_sdk::I256 {
+    soroban_sdk::I256::from_be_bytes(e, &Bytes::from_array(e, &v.to_be_bytes()))
+}
+
+fn from_host(v: &soroban_sdk::I256) -> I256 {
+    let mut bytes = [0u8; 32];
+    v.to_be_bytes().copy_into_slice(&mut bytes);
+    I256::from_be_bytes(&bytes)
+}
+
+// A new environment every 100 cases keeps the host object table small
+fn env_for(case: u32, env: &mut Env) {
+    if case % 100 == 0 {
+        *env = Env::default();
+        env.budget().reset_unlimited();
+    }
+}
+
+#[test]
+fn test_i256_matches_host() {
+    let mut rng = Rng(1);
+    let mut e = Env::default();
+    for case in 0..CASES {
+        env_for(case, &mut e);
+        let a = rng.i256(200);
+        let b = rng.i256(50);
+        let c = rng.i256(127);
+        if b == I256::ZERO || c == I256::ZERO {
+            continue;
+        }
+        let (ha, hb, hc) = (to_host(&e, &a), to_host(&e, &b), to_host(&e, &c));
+        assert_eq!(from_host(&ha), a);
+        assert_eq!(a.to_i128(), ha.to_i128());
+        assert_eq!(a.cmp(&b), ha.cmp(&hb));
+
+        assert_eq!(a.add(&b), from_host(&ha.add(&hb)));
+        assert_eq!(a.sub(&b), from_host(&ha.sub(&hb)));
+        assert_eq!(a.mul(&b), from_host(&ha.mul(&hb)));
+        assert_eq!(a.div(&b), from_host(&ha.div(&hb)));
+        assert_eq!(a.div(&c), from_host(&ha.div(&hc)));
+        assert_eq!(a.rem_euclid(&b), from_host(&ha.rem_euclid(&hb)));
+        assert_eq!(a.rem_euclid(&c), from_host(&ha.rem_euclid(&hc)));
+
+        assert_eq!(
+            a.fixed_mul_floor(&b, &c),
+            from_host(&ha.fixed_mul_floor(&e, &hb, &hc))
+        );
+        assert_eq!(
+            a.fixed_mul_ceil(&b, &c),
+            from_host(&ha.fixed_mul_ceil(&e, &hb, &hc))
+        );
+        assert_eq!(
+            a.fixed_div_floor(&c, &b),
+            from_host(&ha.fixed_div_floor(&e, &hc, &hb))
+        );
+        assert_eq!(
+            a.fixed_div_ceil(&c, &b),
+            from_host(&ha.fixed_div_ceil(&e, &hc, &hb))
+        );
+    }
+}
+
+#[test]
+fn test_i256_bounds_match_host() {
+    let e = Env::default();
+    let mut bytes = [0xff; 32];
+    bytes[0] = 0x7f;
+    let max = I256::from_be_bytes(&bytes);
+    let mut bytes = [0; 32];
+    bytes[0] = 0x80;
+    let min = I256::from_be_bytes(&bytes);
+    let one = I256::from_i32(1);
+    assert_eq!(from_host(&to_host(&e, &max)), max);
+    assert_eq!(from_host(&to_host(&e, &min)), min);
+    assert_eq!(max.checked_add(&one), None);
+    assert_eq!(min.checked_sub(&one), None);
+    assert_eq!(min.checked_div(&I256::from_i32(-1)), None);
+    assert_eq!(max.checked_mul(&I256::from_i32(2)), None);
+    assert_eq!(one.checked_div(&I256::ZERO), None);
+    assert_eq!(min.add(&max), I256::from_i32(-1));
+    assert_eq!(min.to_i128(), None);
+    assert_eq!(I256::from_i128(i128::MIN).to_i128(), Some(i128::MIN));
+}
+
+#[test]
+fn test_c_pow_matches_host() {
+    let mut rng = Rng(2);
+    let mut e = Env::default();
+    for case in 0..CASES {
+        env_for(case, &mut e);
+        let base = rng.range(MIN_CPOW_BASE - 10, MAX_CPOW_BASE + 10);
+        let exp = rng.range(0, 10 * BONE);
+        let round_up = rng.next_u64() % 2 == 0;
+
+        let expected = reference::c_pow(
+            &e,
+            &soroban_sdk::I256::from_i128(&e, base),
+            &soroban_sdk::I256::from_i128(&e, exp),
+            round_up,
+        );
+        let result = c_num::c_pow(&I256::from_i128(base), &I256::from_i128(exp), round_up);
+        assert_eq!(result, expected.map(|v| from_host(&v)), "{base} {exp}");
+    }
+}
+
+#[test]
+fn test_swap_math_matches_host() {
+    let mut rng = Rng(3);
+    let mut e = Env::default();
+    for case in 0..CASES {
+        env_for(case, &mut e);
+        let (decimals_in, decimals_out) = (rng.decimals(), rng.decimals());
+        let in_record = rng.record(decimals_in);
+        let out_record = rng.record(decimals_out);
+        let swap_fee = rng.range(10, STROOP / 10);
+
+        assert_eq!(
+            c_math::calc_spot_price(&in_record, &out_record, swap_fee),
+            Some(reference::calc_spot_price(
+                &in_record,
+                &out_record,
+                swap_fee
+            ))
+        );
+
+        let amount_in = rng.range(1, in_record.balance / 2);
+        assert_eq!(
+            c_math::calc_token_out_given_token_in(&in_record, &out_record, amount_in, swap_fee),
+            reference::calc_token_out_given_token_in(
+                &e,
+                &in_record,
+                &out_record,
+                amount_in,
+                swap_fee
+            ),
+        );
+
+        let amount_out = rng.range(1, out_record.balance * 3 / 4);
+        assert_eq!(
+            c_math::calc_token_in_given_token_out(&in_record, &out_record, amount_out, swap_fee),
+            reference::calc_token_in_given_token_out(
+                &e,
+                &in_record,
+                &out_record,
+                amount_out,
+                swap_fee
+            ),
+        );
+    }
+}
+
+#[test]
+fn test_lp_math_matches_host() {
+    let mut rng = Rng(4);
+    let mut e = Env::default();
+    for case in 0..CASES {
+        env_for(case, &mut e);
+        let decimals = rng.decimals();
+        let record = rng.record(decimals);
+        let supply = rng.range(STROOP, 1_000_000_000 * STROOP);
+        let swap_fee = rng.range(10, STROOP / 10);
+        let exit_fee = rng.range(0, STROOP / 100);
+
+        let token_amount = rng.range(1, record.balance / 2);
+        assert_eq!(
+            c_math::calc_lp_token_amount_given_token_deposits_in(
+                &record,
+                supply,
+                token_amount,
+                swap_fee
+            ),
+            reference::calc_lp_token_amount_given_token_deposits_in(
+                &e,
+                &record,
+                supply,
+                token_amount,
+                swap_fee
+            ),
+        );
+        assert_eq!(
+            c_math::calc_lp_token_amount_given_token_withdrawal_amount(
+                &record,
+                supply,
+                token_amount,
+                swap_fee,
+                exit_fee
+            ),
+            reference::calc_lp_token_amount_given_token_withdrawal_amount(
+                &e,
+                &record,
+                supply,
+                token_amount,
+                swap_fee,
+                exit_fee
+            ),
+        );
+
+        let pool_amount = rng.range(1, supply / 2);
+        assert_eq!(
+            c_math::calc_token_deposits_in_given_lp_token_amount(
+                &record,
+                supply,
+                pool_amount,
+                swap_fee
+            ),
+            reference::calc_token_deposits_in_given_lp_token_amount(
+                &e,
+                &record,
+                supply,
+                pool_amount,
+                swap_fee
+            ),
+        );
+        assert_eq!(
+            c_math::calc_token_withdrawal_amount_given_lp_token_amount(
+                &record,
+                supply,
+                pool_amount,
+                swap_fee,
+                exit_fee
+            ),
+            reference::calc_token_withdrawal_amount_given_lp_token_amount(
+                &e,
+                &record,
+                supply,
+                pool_amount,
+                swap_fee,
+                exit_fee
+            ),
+        );
+
+        let join_ratio = c_math::calc_join_ratio(supply, pool_amount);
+        let expected_join_ratio = reference::calc_join_ratio(&e, supply, pool_amount);
+        assert_eq!(join_ratio, from_host(&expected_join_ratio));
+        assert_eq!(
+            c_math::calc_join_deposit_amount(&record, &join_ratio),
+            reference::calc_join_deposit_amount(&e, &record, &expected_join_ratio),
+        );
+
+        let exit_ratio = c_math::calc_exit_ratio(supply, pool_amount);
+        let expected_exit_ratio = reference::calc_exit_ratio(&e, supply, pool_amount);
+        assert_eq!(exit_ratio, from_host(&expected_exit_ratio));
+        assert_eq!(
+            c_math::calc_exit_withdrawal_amount(&record, &exit_ratio),
+            reference::calc_exit_withdrawal_amount(&e, &record, &expected_exit_ratio),
+        );
+    }
+}
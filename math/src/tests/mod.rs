@@ -0,0 +1,4 @@
+mod reference;
+
+pub mod c_num_test;
+pub mod differential;
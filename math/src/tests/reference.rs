@@ -0,0 +1,373 @@
+#![cfg(test)]
+//! The pool math as run by the contract before it moved to this crate,
+//! on the Soroban host `I256` and `soroban-fixed-point-math`
+//!
+//! Contract errors are returned instead of raised, everything else is unchanged
+use soroban_fixed_point_math::{FixedPoint, SorobanFixedPoint};
+use soroban_sdk::{unwrap::UnwrapOptimized, Env, I256};
+
+use crate::{
+    c_consts::{BONE, CPOW_PRECISION, MAX_CPOW_BASE, MIN_CPOW_BASE, STROOP, STROOP_SCALAR},
+    c_math::Record,
+    error::Error,
+};
+
+pub fn sub_no_negative(a: &I256, b: &I256) -> Result<I256, Error> {
+    if a < b {
+        return Err(Error::ErrSubUnderflow);
+    }
+    Ok(a.sub(b))
+}
+
+pub fn c_pow(e: &Env, base: &I256, exp: &I256, round_up: bool) -> Result<I256, Error> {
+    if base < &I256::from_i128(e, MIN_CPOW_BASE) {
+        return Err(Error::ErrCPowBaseTooLow);
+    }
+    if base > &I256::from_i128(e, MAX_CPOW_BASE) {
+        return Err(Error::ErrCPowBaseTooHigh);
+    }
+
+    let bone = I256::from_i128(e, BONE);
+    let int = exp.div(&bone);
+    let remain = exp.sub(&int.mul(&bone));
+    let whole_pow = c_powi(e, base, &(int.to_i128().unwrap_optimized() as u32));
+    if remain == I256::from_i128(e, 0) {
+        return Ok(whole_pow);
+    }
+    let partial_result = c_pow_approx(
+        e,
+        base,
+        &remain,
+        &I256::from_i128(e, CPOW_PRECISION),
+        round_up,
+    );
+    if round_up {
+        Ok(whole_pow.fixed_mul_ceil(e, &partial_result, &bone))
+    } else {
+        Ok(whole_pow.fixed_mul_floor(e, &partial_result, &bone))
+    }
+}
+
+fn c_powi(e: &Env, a: &I256, n: &u32) -> I256 {
+    let bone = I256::from_i128(e, BONE);
+    let mut z = if n % 2 != 0 { a.clone() } else { bone.clone() };
+
+    let mut a = a.clone();
+    let mut n = n / 2;
+    while n != 0 {
+        a = a.fixed_mul_floor(e, &a, &bone);
+        if n % 2 != 0 {
+            z = z.fixed_mul_floor(e, &a, &bone);
+        }
+        n /= 2
+    }
+    z
+}
+
+fn c_pow_approx(e: &Env, base: &I256, exp: &I256, precision: &I256, round_up: bool) -> I256 {
+    let bone = I256::from_i128(e, BONE);
+    let zero = I256::from_i32(e, 0);
+    let n_1 = I256::from_i32(e, -1);
+    let x = base.sub(&bone);
+    let mut term = bone.clone();
+    let mut sum = term.clone();
+    let prec = precision.clone();
+    for i in 1..51 {
+        let big_k = I256::from_i128(e, i * BONE);
+        let c = exp.sub(&big_k.sub(&bone));
+        term = term.fixed_mul_floor(e, &c.fixed_mul_floor(e, &x, &bone), &bone);
+        term = term.fixed_div_floor(e, &big_k, &bone);
+        sum = sum.add(&term);
+
+        let abs_term = if term < zero {
+            term.mul(&n_1)
+        } else {
+            term.clone()
+        };
+        if abs_term <= prec {
+            break;
+        }
+    }
+    if x > zero {
+        // series will oscillate due to negative `c` values and a starting positive value.
+        if term > zero && !round_up {
+            // the final applied term was additive - the current sum is likely an overestimate
+            sum = sum.sub(&term);
+        } else if term < zero && round_up {
+            // the final applied term was subtractive - the current sum is likely an understimate
+            sum = sum.sub(&term);
+        }
+    } else if !round_up {
+        // series is monotonically decreasing, so the final term is an overestimate
+        sum = sum.add(&term);
+    }
+    sum
+}
+
+pub fn calc_spot_price(in_record: &Record, out_record: &Record, swap_fee: i128) -> i128 {
+    let numer = in_record
+        .balance
+        .fixed_div_floor(in_record.weight, STROOP)
+        .unwrap_optimized();
+    let denom = out_record
+        .balance
+        .fixed_div_floor(out_record.weight, STROOP)
+        .unwrap_optimized();
+    let ratio = numer.fixed_div_floor(denom, STROOP).unwrap_optimized();
+    ratio
+        .fixed_div_floor(STROOP - swap_fee, STROOP)
+        .unwrap_optimized()
+}
+
+pub fn calc_token_out_given_token_in(
+    e: &Env,
+    in_record: &Record,
+    out_record: &Record,
+    amount_in: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
+    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
+    let token_amount_in = upscale(e, amount_in, in_record.scalar);
+
+    let fee_adjust_ratio = upscale(e, STROOP - swap_fee, STROOP_SCALAR);
+    let weight_ratio = upscale(
+        e,
+        in_record
+            .weight
+            .fixed_div_floor(out_record.weight, STROOP)
+            .unwrap_optimized(),
+        STROOP_SCALAR,
+    );
+
+    let adjusted_in = token_amount_in.fixed_mul_floor(e, &fee_adjust_ratio, &bone);
+
+    let base = token_balance_in.fixed_div_floor(e, &token_balance_in.add(&adjusted_in), &bone);
+    let power = c_pow(e, &base, &weight_ratio, true)?;
+    let balance_ratio = sub_no_negative(&bone, &power)?;
+    let result = token_balance_out.fixed_mul_floor(e, &balance_ratio, &bone);
+
+    downscale_floor(e, &result, out_record.scalar)
+}
+
+pub fn calc_token_in_given_token_out(
+    e: &Env,
+    in_record: &Record,
+    out_record: &Record,
+    amount_out: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
+    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
+    let token_amount_out = upscale(e, amount_out, out_record.scalar);
+
+    let fee_adjust_ratio = upscale(e, STROOP - swap_fee, STROOP_SCALAR);
+    let weight_ratio = upscale(
+        e,
+        out_record
+            .weight
+            .fixed_div_ceil(in_record.weight, STROOP)
+            .unwrap_optimized(),
+        STROOP_SCALAR,
+    );
+
+    let base =
+        token_balance_out.fixed_div_ceil(e, &token_balance_out.sub(&token_amount_out), &bone);
+    let power = c_pow(e, &base, &weight_ratio, true)?;
+    let balance_ratio = sub_no_negative(&power, &bone)?;
+
+    let token_amount_in = token_balance_in.fixed_mul_ceil(e, &balance_ratio, &bone);
+    let adjusted_in = token_amount_in.fixed_div_ceil(e, &fee_adjust_ratio, &bone);
+    downscale_ceil(e, &adjusted_in, in_record.scalar)
+}
+
+pub fn calc_lp_token_amount_given_token_deposits_in(
+    e: &Env,
+    in_record: &Record,
+    pool_supply: i128,
+    token_amount_in: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
+    let token_amount_in = upscale(e, token_amount_in, in_record.scalar);
+    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
+    let fee = upscale(e, swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(e, in_record.weight, STROOP_SCALAR);
+    let zaz = bone.sub(&normalized_weight).fixed_mul_floor(e, &fee, &bone);
+    let token_amount_in_after_fee = token_amount_in.fixed_mul_floor(e, &bone.sub(&zaz), &bone);
+
+    let new_token_balance_in = token_balance_in.add(&token_amount_in_after_fee);
+    let balance_ratio = new_token_balance_in.fixed_div_floor(e, &token_balance_in, &bone);
+
+    let pool_ratio = c_pow(e, &balance_ratio, &normalized_weight, false)?;
+    let new_pool_supply = pool_ratio.fixed_mul_floor(e, &pool_supply, &bone);
+
+    downscale_floor(
+        e,
+        &sub_no_negative(&new_pool_supply, &pool_supply)?,
+        STROOP_SCALAR,
+    )
+}
+
+pub fn calc_token_deposits_in_given_lp_token_amount(
+    e: &Env,
+    in_record: &Record,
+    pool_supply: i128,
+    pool_amount_out: i128,
+    swap_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
+    let pool_amount_out = upscale(e, pool_amount_out, STROOP_SCALAR);
+    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
+    let fee = upscale(e, swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(e, in_record.weight, STROOP_SCALAR);
+
+    let new_pool_supply = pool_supply.add(&pool_amount_out);
+    let pool_ratio = new_pool_supply.fixed_div_ceil(e, &pool_supply, &bone);
+
+    let boo = bone.fixed_div_ceil(e, &normalized_weight, &bone);
+    let token_in_ratio = c_pow(e, &pool_ratio, &boo, false)?;
+    let new_token_balance_in = token_balance_in.fixed_mul_ceil(e, &token_in_ratio, &bone);
+
+    let token_amount_in_after_fee = sub_no_negative(&new_token_balance_in, &token_balance_in)?;
+    let zar = bone.sub(&normalized_weight).fixed_mul_floor(e, &fee, &bone);
+    let result = token_amount_in_after_fee.fixed_div_ceil(e, &bone.sub(&zar), &bone);
+
+    downscale_ceil(e, &result, in_record.scalar)
+}
+
+pub fn calc_lp_token_amount_given_token_withdrawal_amount(
+    e: &Env,
+    out_record: &Record,
+    pool_supply: i128,
+    token_amount_out: i128,
+    swap_fee: i128,
+    exit_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
+    let token_amount_out = upscale(e, token_amount_out, out_record.scalar);
+    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
+    let fee = upscale(e, swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(e, out_record.weight, STROOP_SCALAR);
+
+    let zoo = bone.sub(&normalized_weight);
+    let zar = zoo.fixed_mul_floor(e, &fee, &bone);
+
+    let token_amount_out_before_fee = token_amount_out.fixed_div_ceil(e, &bone.sub(&zar), &bone);
+    let new_token_balance_out = token_balance_out.sub(&token_amount_out_before_fee);
+    let balance_ratio = new_token_balance_out.fixed_div_ceil(e, &token_balance_out, &bone);
+
+    let pool_ratio = c_pow(e, &balance_ratio, &normalized_weight, true)?;
+    let new_pool_supply = pool_ratio.fixed_mul_ceil(e, &pool_supply, &bone);
+    let pool_amount_in_after_exit_fee = sub_no_negative(&pool_supply, &new_pool_supply)?;
+
+    let exit_fee = upscale(e, exit_fee, STROOP_SCALAR);
+    let result = pool_amount_in_after_exit_fee.fixed_div_ceil(e, &bone.sub(&exit_fee), &bone);
+
+    downscale_ceil(e, &result, STROOP_SCALAR)
+}
+
+pub fn calc_token_withdrawal_amount_given_lp_token_amount(
+    e: &Env,
+    out_record: &Record,
+    pool_supply: i128,
+    pool_amount_in: i128,
+    swap_fee: i128,
+    exit_fee: i128,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
+    let pool_amount_in = upscale(e, pool_amount_in, STROOP_SCALAR);
+    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
+    let fee = upscale(e, swap_fee, STROOP_SCALAR);
+
+    let normalized_weight = upscale(e, out_record.weight, STROOP_SCALAR);
+
+    let exit_fee = upscale(e, exit_fee, STROOP_SCALAR);
+    let pool_amount_in_after_exit_fee =
+        pool_amount_in.fixed_mul_floor(e, &bone.sub(&exit_fee), &bone);
+
+    let new_pool_supply = pool_supply.sub(&pool_amount_in_after_exit_fee);
+    let pool_ratio = new_pool_supply.fixed_div_floor(e, &pool_supply, &bone);
+
+    let exp = bone.fixed_div_floor(e, &normalized_weight, &bone);
+    let token_out_ratio = c_pow(e, &pool_ratio, &exp, false)?;
+    let new_token_balance_out = token_balance_out.fixed_mul_floor(e, &token_out_ratio, &bone);
+
+    let token_amount_out_before_fee = sub_no_negative(&token_balance_out, &new_token_balance_out)?;
+
+    let zaz = bone.sub(&normalized_weight).fixed_mul_floor(e, &fee, &bone);
+    let result = token_amount_out_before_fee.fixed_mul_floor(e, &bone.sub(&zaz), &bone);
+
+    downscale_floor(e, &result, out_record.scalar)
+}
+
+pub fn calc_join_ratio(e: &Env, pool_supply: i128, pool_amount_out: i128) -> I256 {
+    let bone = I256::from_i128(e, BONE);
+    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
+    let pool_amount_out = upscale(e, pool_amount_out, STROOP_SCALAR);
+
+    pool_amount_out.fixed_div_ceil(e, &pool_supply, &bone)
+}
+
+pub fn calc_join_deposit_amount(
+    e: &Env,
+    in_record: &Record,
+    join_ratio: &I256,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_in = upscale(e, in_record.balance, in_record.scalar);
+
+    let result = token_balance_in.fixed_mul_ceil(e, join_ratio, &bone);
+    downscale_ceil(e, &result, in_record.scalar)
+}
+
+pub fn calc_exit_ratio(e: &Env, pool_supply: i128, pool_amount_in: i128) -> I256 {
+    let bone = I256::from_i128(e, BONE);
+    let pool_supply = upscale(e, pool_supply, STROOP_SCALAR);
+    let pool_amount_in = upscale(e, pool_amount_in, STROOP_SCALAR);
+
+    pool_amount_in.fixed_div_floor(e, &pool_supply, &bone)
+}
+
+pub fn calc_exit_withdrawal_amount(
+    e: &Env,
+    out_record: &Record,
+    exit_ratio: &I256,
+) -> Result<i128, Error> {
+    let bone = I256::from_i128(e, BONE);
+    let token_balance_out = upscale(e, out_record.balance, out_record.scalar);
+
+    let result = token_balance_out.fixed_mul_floor(e, exit_ratio, &bone);
+    downscale_floor(e, &result, out_record.scalar)
+}
+
+fn upscale(e: &Env, amount: i128, scalar: i128) -> I256 {
+    I256::from_i128(e, amount * scalar)
+}
+
+fn downscale_floor(e: &Env, amount: &I256, scalar: i128) -> Result<i128, Error> {
+    let scale_256 = I256::from_i128(e, scalar);
+    let one = I256::from_i32(e, 1);
+    amount
+        .fixed_div_floor(e, &scale_256, &one)
+        .to_i128()
+        .ok_or(Error::ErrMathApprox)
+}
+
+fn downscale_ceil(e: &Env, amount: &I256, scalar: i128) -> Result<i128, Error> {
+    let scale_256 = I256::from_i128(e, scalar);
+    let one = I256::from_i32(e, 1);
+    amount
+        .fixed_div_ceil(e, &scale_256, &one)
+        .to_i128()
+        .ok_or(Error::ErrMathApprox)
+}
@@ -0,0 +1,144 @@
+use math::c_consts::STROOP;
+use soroban_sdk::{assert_with_error, unwrap::UnwrapOptimized, Address, Env, Vec};
+
+use crate::{
+    error::Error,
+    interface::{FactoryClient, PoolClient},
+    DataKeyRouter, Hop,
+};
+
+pub(crate) const DAY_IN_LEDGERS: u32 = 17280;
+
+pub(crate) const SHARED_BUMP_AMOUNT: u32 = 31 * DAY_IN_LEDGERS;
+pub(crate) const SHARED_LIFETIME_THRESHOLD: u32 = SHARED_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub fn execute_init(e: Env, factory: Address) {
+    e.storage()
+        .instance()
+        .set(&DataKeyRouter::Factory, &factory);
+}
+
+// Read the Factory used to validate the pools
+pub fn read_factory(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<DataKeyRouter, Address>(&DataKeyRouter::Factory)
+        .unwrap_optimized()
+}
+
+// Check that the hops form a path and that every pool was created by the Factory
+// A pool can only be used once, so the quotes of the other hops stay valid while
+// the path is swapped
+fn check_path(e: &Env, hops: &Vec<Hop>) {
+    assert_with_error!(e, !hops.is_empty(), Error::ErrInvalidPath);
+
+    let factory = FactoryClient::new(e, &read_factory(e));
+    let mut token = hops.get_unchecked(0).token_in;
+    let mut pools = Vec::<Address>::new(e);
+    for hop in hops.iter() {
+        assert_with_error!(
+            e,
+            hop.token_in == token && hop.token_in != hop.token_out,
+            Error::ErrInvalidPath
+        );
+        assert_with_error!(e, !pools.contains(&hop.pool), Error::ErrInvalidPath);
+        assert_with_error!(e, factory.is_c_pool(&hop.pool), Error::ErrNotCPool);
+        token = hop.token_out;
+        pools.push_back(hop.pool);
+    }
+}
+
+// Check the price paid over the whole path, in the units of the pools' spot prices
+fn check_price(e: &Env, token_amount_in: i128, token_amount_out: i128, max_price: i128) {
+    assert_with_error!(e, token_amount_out > 0, Error::ErrNegativeOrZero);
+    let price = token_amount_in.checked_mul(STROOP).unwrap_optimized() / token_amount_out;
+    assert_with_error!(e, price <= max_price, Error::ErrLimitPrice);
+}
+
+// Swap `token_amount_in` of the first token in the path for the last one,
+// sending intermediate tokens back to `user` and the final tokens to `to`
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap_exact_amount_in(
+    e: Env,
+    hops: Vec<Hop>,
+    token_amount_in: i128,
+    min_amount_out: i128,
+    max_price: i128,
+    user: Address,
+    to: Address,
+    deadline: u64,
+) -> i128 {
+    assert_with_error!(&e, token_amount_in > 0, Error::ErrNegativeOrZero);
+    check_path(&e, &hops);
+
+    let last = hops.len() - 1;
+    let mut token_amount_out = token_amount_in;
+    for (i, hop) in hops.iter().enumerate() {
+        let recipient = if i as u32 == last { &to } else { &user };
+        let (amount_out, _) = PoolClient::new(&e, &hop.pool).swap_exact_amount_in_to(
+            &hop.token_in,
+            &token_amount_out,
+            &hop.token_out,
+            &0,
+            &i128::MAX,
+            &user,
+            recipient,
+            &deadline,
+        );
+        token_amount_out = amount_out;
+    }
+
+    assert_with_error!(&e, token_amount_out >= min_amount_out, Error::ErrLimitOut);
+    check_price(&e, token_amount_in, token_amount_out, max_price);
+    token_amount_out
+}
+
+// Swap the first token in the path for `token_amount_out` of the last one,
+// sending intermediate tokens back to `user` and the final tokens to `to`
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap_exact_amount_out(
+    e: Env,
+    hops: Vec<Hop>,
+    max_amount_in: i128,
+    token_amount_out: i128,
+    max_price: i128,
+    user: Address,
+    to: Address,
+    deadline: u64,
+) -> i128 {
+    assert_with_error!(&e, token_amount_out > 0, Error::ErrNegativeOrZero);
+    check_path(&e, &hops);
+
+    // quote the amount in of every hop, starting from the last one
+    let mut amounts = Vec::<i128>::new(&e);
+    amounts.push_front(token_amount_out);
+    for hop in hops.iter().rev() {
+        let (amount_in, _) = PoolClient::new(&e, &hop.pool).quote_swap_exact_amount_out(
+            &hop.token_in,
+            &hop.token_out,
+            &amounts.get_unchecked(0),
+        );
+        amounts.push_front(amount_in);
+    }
+
+    let token_amount_in = amounts.get_unchecked(0);
+    assert_with_error!(&e, token_amount_in <= max_amount_in, Error::ErrLimitIn);
+    check_price(&e, token_amount_in, token_amount_out, max_price);
+
+    let last = hops.len() - 1;
+    for (i, hop) in hops.iter().enumerate() {
+        let i = i as u32;
+        let recipient = if i == last { &to } else { &user };
+        PoolClient::new(&e, &hop.pool).swap_exact_amount_out_to(
+            &hop.token_in,
+            &amounts.get_unchecked(i),
+            &hop.token_out,
+            &amounts.get_unchecked(i + 1),
+            &i128::MAX,
+            &user,
+            recipient,
+            &deadline,
+        );
+    }
+    token_amount_in
+}
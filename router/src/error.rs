@@ -0,0 +1,15 @@
+use soroban_sdk::contracterror;
+
+// Error codes based on the Comet pool contract
+#[contracterror]
+#[derive(Copy, Clone, Debug)]
+#[repr(u32)]
+pub enum Error {
+    ErrNotCPool = 1,
+    AlreadyInitialized = 7,
+    ErrLimitIn = 19,
+    ErrLimitOut = 20,
+    ErrLimitPrice = 23,
+    ErrNegativeOrZero = 37,
+    ErrInvalidPath = 200,
+}
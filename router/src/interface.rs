@@ -0,0 +1,47 @@
+//! Interfaces of the Factory and Pool Contracts used by the Router
+use soroban_sdk::{contractclient, Address, Env};
+
+#[contractclient(name = "FactoryClient")]
+pub trait FactoryInterface {
+    // Returns true if the passed Address is a valid Pool
+    fn is_c_pool(e: Env, addr: Address) -> bool;
+}
+
+#[contractclient(name = "PoolClient")]
+pub trait PoolInterface {
+    // Swap `token_amount_in` of `token_in` for `token_out`, sending it to `to`
+    #[allow(clippy::too_many_arguments)]
+    fn swap_exact_amount_in_to(
+        e: Env,
+        token_in: Address,
+        token_amount_in: i128,
+        token_out: Address,
+        min_amount_out: i128,
+        max_price: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> (i128, i128);
+
+    // Swap `token_in` for `token_amount_out` of `token_out`, sending it to `to`
+    #[allow(clippy::too_many_arguments)]
+    fn swap_exact_amount_out_to(
+        e: Env,
+        token_in: Address,
+        max_amount_in: i128,
+        token_out: Address,
+        token_amount_out: i128,
+        max_price: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> (i128, i128);
+
+    // Quote the amount of `token_in` required to swap for `token_amount_out` of `token_out`
+    fn quote_swap_exact_amount_out(
+        e: Env,
+        token_in: Address,
+        token_out: Address,
+        token_amount_out: i128,
+    ) -> (i128, i128);
+}
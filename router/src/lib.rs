@@ -0,0 +1,111 @@
+#![no_std]
+
+use call_logic::router::{
+    execute_init, execute_swap_exact_amount_in, execute_swap_exact_amount_out, read_factory,
+    SHARED_BUMP_AMOUNT, SHARED_LIFETIME_THRESHOLD,
+};
+use soroban_sdk::{assert_with_error, contract, contractimpl, contracttype, Address, Env, Vec};
+
+pub mod call_logic;
+// Errors Listed
+pub mod error;
+// Factory and Pool Contract Interfaces
+pub mod interface;
+use crate::error::Error;
+
+// Keys which will give access to the corresponding data
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKeyRouter {
+    Factory,
+}
+
+// A swap of `token_in` for `token_out` through a single pool of a route
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hop {
+    pub pool: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+}
+
+#[contract]
+pub struct Router;
+
+#[contractimpl]
+impl Router {
+    // Initialize the Router with the Factory used to validate the pools
+    pub fn init(e: Env, factory: Address) {
+        assert_with_error!(
+            &e,
+            !e.storage().instance().has(&DataKeyRouter::Factory),
+            Error::AlreadyInitialized
+        );
+        execute_init(e, factory);
+    }
+
+    // Swap `token_amount_in` of the first token through every hop,
+    // `min_amount_out` and `max_price` are checked over the whole route
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_amount_in(
+        e: Env,
+        hops: Vec<Hop>,
+        token_amount_in: i128,
+        min_amount_out: i128,
+        max_price: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> i128 {
+        user.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_swap_exact_amount_in(
+            e,
+            hops,
+            token_amount_in,
+            min_amount_out,
+            max_price,
+            user,
+            to,
+            deadline,
+        )
+    }
+
+    // Swap through every hop for `token_amount_out` of the last token,
+    // `max_amount_in` and `max_price` are checked over the whole route
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_amount_out(
+        e: Env,
+        hops: Vec<Hop>,
+        max_amount_in: i128,
+        token_amount_out: i128,
+        max_price: i128,
+        user: Address,
+        to: Address,
+        deadline: u64,
+    ) -> i128 {
+        user.require_auth();
+        e.storage()
+            .instance()
+            .extend_ttl(SHARED_LIFETIME_THRESHOLD, SHARED_BUMP_AMOUNT);
+        execute_swap_exact_amount_out(
+            e,
+            hops,
+            max_amount_in,
+            token_amount_out,
+            max_price,
+            user,
+            to,
+            deadline,
+        )
+    }
+
+    // Get the Factory used to validate the pools
+    pub fn get_factory(e: Env) -> Address {
+        read_factory(&e)
+    }
+}
+
+mod test;
@@ -0,0 +1,283 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{error::Error as RouterError, Hop, Router, RouterClient};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, BytesN, Env, Error, IntoVal, Symbol,
+};
+
+#[allow(clippy::too_many_arguments)]
+mod comet {
+    soroban_sdk::contractimport!(file = "../target/wasm32-unknown-unknown/optimized/comet.wasm");
+}
+
+#[allow(clippy::too_many_arguments)]
+mod factory {
+    soroban_sdk::contractimport!(
+        file = "../target/wasm32-unknown-unknown/optimized/comet_factory.wasm"
+    );
+}
+
+fn create_pool(
+    env: &Env,
+    factory: &factory::Client,
+    controller: &Address,
+    token_1: &Address,
+    token_2: &Address,
+    salt: u8,
+) -> Address {
+    factory.new_c_pool(
+        &BytesN::from_array(env, &[salt; 32]),
+        controller,
+        &vec![env, token_1.clone(), token_2.clone()],
+        &vec![env, 0_5000000, 0_5000000],
+        &vec![env, 100_0000000, 100_0000000],
+        &0_0030000,
+    )
+}
+
+#[test]
+fn test_multi_hop_swaps() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+
+    let admin = Address::generate(&env);
+    let controller = Address::generate(&env);
+    let user = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    let factory_id = env.register_contract_wasm(None, factory::WASM);
+    let factory = factory::Client::new(&env, &factory_id);
+    factory.init(&env.deployer().upload_contract_wasm(comet::WASM), &admin);
+
+    let token_1 = env.register_stellar_asset_contract(admin.clone());
+    let token_2 = env.register_stellar_asset_contract(admin.clone());
+    let token_3 = env.register_stellar_asset_contract(admin.clone());
+    for token in [&token_1, &token_2, &token_3] {
+        let client = StellarAssetClient::new(&env, token);
+        client.mint(&controller, &1000_0000000);
+        client.mint(&user, &100_0000000);
+    }
+    let pool_a = create_pool(&env, &factory, &controller, &token_1, &token_2, 1);
+    let pool_b = create_pool(&env, &factory, &controller, &token_2, &token_3, 2);
+
+    let router_id = env.register_contract(None, Router);
+    let router = RouterClient::new(&env, &router_id);
+    router.init(&factory_id);
+    assert_eq!(router.get_factory(), factory_id);
+
+    let hops = vec![
+        &env,
+        Hop {
+            pool: pool_a.clone(),
+            token_in: token_1.clone(),
+            token_out: token_2.clone(),
+        },
+        Hop {
+            pool: pool_b.clone(),
+            token_in: token_2.clone(),
+            token_out: token_3.clone(),
+        },
+    ];
+
+    // exact in
+    let amount_out =
+        router.swap_exact_amount_in(&hops, &1_0000000, &0, &i128::MAX, &user, &to, &1000);
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, user);
+    assert_eq!(
+        env.auths()[0].1.function,
+        soroban_sdk::testutils::AuthorizedFunction::Contract((
+            router_id.clone(),
+            Symbol::new(&env, "swap_exact_amount_in"),
+            (
+                hops.clone(),
+                1_0000000_i128,
+                0_i128,
+                i128::MAX,
+                user.clone(),
+                to.clone(),
+                1000_u64
+            )
+                .into_val(&env),
+        ))
+    );
+    let token_1_client = TokenClient::new(&env, &token_1);
+    let token_2_client = TokenClient::new(&env, &token_2);
+    let token_3_client = TokenClient::new(&env, &token_3);
+    assert_eq!(token_1_client.balance(&user), 99_0000000);
+    assert_eq!(token_2_client.balance(&user), 100_0000000);
+    assert_eq!(token_3_client.balance(&to), amount_out);
+    assert!(amount_out > 0_9000000 && amount_out < 1_0000000);
+
+    // slippage and price are checked over the whole route
+    let result = router.try_swap_exact_amount_in(
+        &hops,
+        &1_0000000,
+        &1_0000000,
+        &i128::MAX,
+        &user,
+        &to,
+        &1000,
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrLimitOut as u32
+        )))
+    );
+    let result =
+        router.try_swap_exact_amount_in(&hops, &1_0000000, &0, &1_0000000, &user, &to, &1000);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrLimitPrice as u32
+        )))
+    );
+
+    // exact out
+    let balance_1 = token_1_client.balance(&user);
+    let balance_3 = token_3_client.balance(&to);
+    let amount_in =
+        router.swap_exact_amount_out(&hops, &i128::MAX, &1_0000000, &i128::MAX, &user, &to, &1000);
+    assert_eq!(token_1_client.balance(&user), balance_1 - amount_in);
+    assert_eq!(token_2_client.balance(&user), 100_0000000);
+    assert_eq!(token_3_client.balance(&to), balance_3 + 1_0000000);
+    assert!(amount_in > 1_0000000 && amount_in < 1_1000000);
+
+    let result = router.try_swap_exact_amount_out(
+        &hops,
+        &1_0000000,
+        &1_0000000,
+        &i128::MAX,
+        &user,
+        &to,
+        &1000,
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrLimitIn as u32
+        )))
+    );
+
+    // the deadline is passed to every pool
+    let result =
+        router.try_swap_exact_amount_in(&hops, &1_0000000, &0, &i128::MAX, &user, &to, &999);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(51))));
+}
+
+#[test]
+fn test_invalid_paths() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let factory_id = env.register_contract_wasm(None, factory::WASM);
+    let factory = factory::Client::new(&env, &factory_id);
+    factory.init(&env.deployer().upload_contract_wasm(comet::WASM), &admin);
+
+    let token_1 = env.register_stellar_asset_contract(admin.clone());
+    let token_2 = env.register_stellar_asset_contract(admin.clone());
+    let token_3 = env.register_stellar_asset_contract(admin.clone());
+    StellarAssetClient::new(&env, &token_1).mint(&admin, &100_0000000);
+    StellarAssetClient::new(&env, &token_2).mint(&admin, &100_0000000);
+    let pool = create_pool(&env, &factory, &admin, &token_1, &token_2, 1);
+
+    let router = RouterClient::new(&env, &env.register_contract(None, Router));
+    router.init(&factory_id);
+    let result = router.try_init(&factory_id);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::AlreadyInitialized as u32
+        )))
+    );
+
+    let result =
+        router.try_swap_exact_amount_in(&vec![&env], &1_0000000, &0, &i128::MAX, &user, &user, &0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrInvalidPath as u32
+        )))
+    );
+
+    // hops have to be chained
+    let hops = vec![
+        &env,
+        Hop {
+            pool: pool.clone(),
+            token_in: token_1.clone(),
+            token_out: token_2.clone(),
+        },
+        Hop {
+            pool: pool.clone(),
+            token_in: token_3.clone(),
+            token_out: token_1.clone(),
+        },
+    ];
+    let result =
+        router.try_swap_exact_amount_in(&hops, &1_0000000, &0, &i128::MAX, &user, &user, &0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrInvalidPath as u32
+        )))
+    );
+
+    // a pool can only be used once
+    let hops = vec![
+        &env,
+        Hop {
+            pool: pool.clone(),
+            token_in: token_1.clone(),
+            token_out: token_2.clone(),
+        },
+        Hop {
+            pool: pool.clone(),
+            token_in: token_2.clone(),
+            token_out: token_1.clone(),
+        },
+    ];
+    let result = router.try_swap_exact_amount_out(
+        &hops,
+        &i128::MAX,
+        &1_0000000,
+        &i128::MAX,
+        &user,
+        &user,
+        &0,
+    );
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrInvalidPath as u32
+        )))
+    );
+
+    // pools have to be created by the factory
+    let hops = vec![
+        &env,
+        Hop {
+            pool: Address::generate(&env),
+            token_in: token_1.clone(),
+            token_out: token_2.clone(),
+        },
+    ];
+    let result =
+        router.try_swap_exact_amount_in(&hops, &1_0000000, &0, &i128::MAX, &user, &user, &0);
+    assert_eq!(
+        result.err(),
+        Some(Ok(Error::from_contract_error(
+            RouterError::ErrNotCPool as u32
+        )))
+    );
+}